@@ -1,12 +1,7 @@
 // TODO: Factor out scene archival
 //       * Currently loading / unloading entire scenes instead of archiving individual entities
 //         * Suboptimal, no longer necessary now archive bundles can be defined in blender
-//       * Switching from InsertSceneArchive to InsertScene doesn't work as-is
-//         * Scene is spawned without GLTF extras deserialized
-//         * Appears to be a timing issue - scene is spawned before deserialization
-//           * However, setting up system bounds hasn't fixed it,
-//             and forcing a changed event doesn't cause the scene to reinstance with extras
-//           * Need to investigate further; using stock scene machinery would be ideal
+//       * InsertScene now waits on GltfScenesReady, so stock scene machinery is viable
 //
 // TODO: Migrate remaining entity assemblages to Blender scenes
 //       * Will need to use a tag component to identify ship, force rotation targets
@@ -20,25 +15,6 @@
 //       * Investigate custom properties
 //         * https://blog.hamaluik.ca/posts/dynamic-blender-properties/
 //
-// FIXME: Exporting tangents breaks the sprite shader
-//        * Previously working due to mesh being non-triangulated
-//        * Need to strip tangents as part of the ConvertMesh2d process
-//
-// TODO: Better material support so line / point meshes are viable
-//       * Can't render with StandardMaterial due to lack of normals
-//       * Need an override component to convert the automatic StandardMaterial into something else
-//       * Opens the door to custom line / point use-cases
-//         * Point sprites
-//           * Not an API-level feature in WGPU
-//         * Geometry lines
-//         * GPU instancing would be ideal, but CPU mesh puppeting is probably more viable as-is
-//
-// FIXME: Incorrect clipping rect for rightmost panel if it contains a world inspector
-//
-// TODO: Formalize mesh rotation
-//       * Enemies should be able to animate their mesh rotation
-//       * Currently impeded by gimbal lock caused by top-level rotation + playfield rotation
-//
 // TODO: Enemy pooling
 //       * Generalize bullet pool system
 //
@@ -54,9 +30,6 @@
 // TODO: Investigate using DynamicAnimation to drive bullet transform animations
 //       * Can likely share code with ship rotation system
 //
-// TODO: Rewindable player and force movement
-//       * Replace LinearMove with dynamic animation
-//
 // TODO: Extension patterns for ship, force and enemy behavior
 //       * Ship
 //         * Vulcan gun
@@ -100,10 +73,6 @@
 // TODO: Integrate shambler for mapping pipeline
 //       * Will involve formalizing it into a proper API and writing a bevy integration
 //
-// FIXME: Rewinding to beginning of timeline causes a noticeable visual rewind
-//        * Should be instant
-//        * Appears to be caused by archival / unarchival occurring a frame late
-//
 // TODO: Change force rotation direction and speed based on movement
 //       * Should use an acceleration -> velocity -> rotation animation
 //       * Acceleration is set based on the sign of the force's Y velocity
@@ -122,8 +91,6 @@
 //
 // TODO: Drag handle for resizing timeline viewport
 //
-// TODO: Formalize Z-ordering for playfield entities
-//
 // FIXME: Better accuracy for shapecast normal calculation
 //        * Currently averaging source collider positions, projecting onto target collider
 //          * Would be more accurate to check source colliders individually
@@ -23,6 +23,20 @@ where
     _phantom: PhantomData<T>,
 }
 
+impl<T> Dilate<T>
+where
+    T: 'static + Send + Sync + Default,
+{
+    /// Map a parent time into the time seen by the wrapped animation
+    pub fn map_time(&self, time: AnimationTime) -> AnimationTime {
+        AnimationTime {
+            t: time.t * self.factor,
+            prev_t: time.prev_t * self.factor,
+            ..time
+        }
+    }
+}
+
 impl<T> Animate for Dilate<T>
 where
     T: 'static + Send + Sync + Default,
@@ -31,17 +45,8 @@ where
 
     fn animate(world: &mut World, animation: Entity, time: AnimationTime) -> Self::Type {
         info_span!("Dilate").in_scope(|| {
-            let data = Self::data(world, animation).unwrap();
-            let factor = data.factor;
-
-            Self::handle::<T>(world, animation).animate(
-                world,
-                AnimationTime {
-                    t: time.t * factor,
-                    prev_t: time.prev_t * factor,
-                    ..time
-                },
-            )
+            let time = Self::data(world, animation).unwrap().map_time(time);
+            Self::handle::<T>(world, animation).animate(world, time)
         })
     }
 }
@@ -118,3 +123,27 @@ where
         commands
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_time_dilate_scales_time() {
+        let dilate = Dilate::<()> {
+            factor: 0.5,
+            _phantom: default(),
+        };
+
+        let time = dilate.map_time(AnimationTime {
+            t: 3.0,
+            prev_t: 2.0,
+            paused: true,
+            ..default()
+        });
+
+        assert_eq!(time.t, 1.5);
+        assert_eq!(time.prev_t, 1.0);
+        assert!(time.paused);
+    }
+}
@@ -24,6 +24,20 @@ where
     _phantom: PhantomData<T>,
 }
 
+impl<T> Offset<T>
+where
+    T: 'static + Send + Sync + Default,
+{
+    /// Map a parent time into the time seen by the wrapped animation
+    pub fn map_time(&self, time: AnimationTime) -> AnimationTime {
+        AnimationTime {
+            t: time.t + self.offset,
+            prev_t: time.prev_t + self.offset,
+            ..time
+        }
+    }
+}
+
 impl<T> Animate for Offset<T>
 where
     T: 'static + Send + Sync + Default,
@@ -32,17 +46,8 @@ where
 
     fn animate(world: &mut World, animation: Entity, time: AnimationTime) -> Self::Type {
         info_span!("Offset").in_scope(|| {
-            let data = Self::data(world, animation).unwrap();
-            let offset = data.offset;
-
-            Self::handle::<T>(world, animation).animate(
-                world,
-                AnimationTime {
-                    t: time.t + offset,
-                    prev_t: time.prev_t + offset,
-                    ..time
-                },
-            )
+            let time = Self::data(world, animation).unwrap().map_time(time);
+            Self::handle::<T>(world, animation).animate(world, time)
         })
     }
 }
@@ -118,3 +123,27 @@ where
         commands
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_time_offset_shifts_time() {
+        let offset = Offset::<()> {
+            offset: 1.5,
+            _phantom: default(),
+        };
+
+        let time = offset.map_time(AnimationTime {
+            t: 2.0,
+            prev_t: 1.0,
+            paused: true,
+            ..default()
+        });
+
+        assert_eq!(time.t, 3.5);
+        assert_eq!(time.prev_t, 2.5);
+        assert!(time.paused);
+    }
+}
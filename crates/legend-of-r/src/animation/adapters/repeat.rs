@@ -24,6 +24,20 @@ where
     _phantom: PhantomData<T>,
 }
 
+impl<T> Repeat<T>
+where
+    T: 'static + Send + Sync + Default,
+{
+    /// Map a parent time into the time seen by the wrapped animation
+    pub fn map_time(&self, time: AnimationTime) -> AnimationTime {
+        AnimationTime {
+            t: time.t % self.interval,
+            prev_t: time.prev_t % self.interval,
+            ..time
+        }
+    }
+}
+
 impl<T> Animate for Repeat<T>
 where
     T: 'static + Send + Sync + Default,
@@ -32,17 +46,8 @@ where
 
     fn animate(world: &mut World, animation: Entity, time: AnimationTime) -> Self::Type {
         info_span!("Repeat").in_scope(|| {
-            let data = Self::data(world, animation).unwrap();
-            let interval = data.interval;
-
-            Self::handle::<T>(world, animation).animate(
-                world,
-                AnimationTime {
-                    t: time.t % interval,
-                    prev_t: time.prev_t % interval,
-                    ..time
-                },
-            )
+            let time = Self::data(world, animation).unwrap().map_time(time);
+            Self::handle::<T>(world, animation).animate(world, time)
         })
     }
 }
@@ -121,3 +126,27 @@ where
         commands
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_time_repeat_wraps_time() {
+        let repeat = Repeat::<()> {
+            interval: 2.0,
+            _phantom: default(),
+        };
+
+        let time = repeat.map_time(AnimationTime {
+            t: 5.0,
+            prev_t: 3.5,
+            paused: true,
+            ..default()
+        });
+
+        assert_eq!(time.t, 1.0);
+        assert_eq!(time.prev_t, 1.5);
+        assert!(time.paused);
+    }
+}
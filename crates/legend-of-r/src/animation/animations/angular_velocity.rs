@@ -0,0 +1,114 @@
+use bevy::{
+    ecs::reflect::ReflectComponent,
+    prelude::{default, Component, Entity, Name, Quat, Vec3, World},
+    reflect::Reflect,
+};
+
+use crate::prelude::{
+    Animate, AnimationBuilder, AnimationBuilderTrait, AnimationEntityBuilder, AnimationTime,
+    ReflectIntegrationBlacklist, TimelineAnimation, TimelineAnimationContext,
+};
+
+/// Animation that produces a continuous rotation about a fixed axis
+///
+/// The angle is evaluated in closed form from the animation time,
+/// so spins of any length can be scrubbed or rewound without accumulating drift,
+/// and aren't limited to the shortest path between two orientations like slerped stops are.
+#[derive(Debug, Copy, Clone, PartialEq, Component, Reflect)]
+#[reflect(Component, IntegrationBlacklist)]
+pub struct AngularVelocity {
+    pub axis: Vec3,
+    /// Initial angular velocity, in radians per second
+    pub velocity: f64,
+    /// Change in angular velocity per second
+    pub acceleration: f64,
+    /// Magnitude past which acceleration stops increasing angular velocity
+    pub max_velocity: f64,
+}
+
+impl Default for AngularVelocity {
+    fn default() -> Self {
+        Self {
+            axis: Vec3::Z,
+            velocity: 0.0,
+            acceleration: 0.0,
+            max_velocity: f64::INFINITY,
+        }
+    }
+}
+
+impl AngularVelocity {
+    pub fn new(axis: Vec3, velocity: f64) -> Self {
+        AngularVelocity {
+            axis,
+            velocity,
+            ..default()
+        }
+    }
+
+    pub fn with_acceleration(mut self, acceleration: f64, max_velocity: f64) -> Self {
+        self.acceleration = acceleration;
+        self.max_velocity = max_velocity;
+        self
+    }
+
+    /// Angle travelled after `t` seconds, wrapped into a single revolution
+    pub fn angle(&self, t: f64) -> f64 {
+        let max = self.max_velocity.abs();
+        let velocity = self.velocity.clamp(-max, max);
+
+        let limit = if self.acceleration > 0.0 { max } else { -max };
+
+        // Time at which acceleration is cut off by the velocity limit
+        let t_limit = if self.acceleration != 0.0 {
+            (limit - velocity) / self.acceleration
+        } else {
+            f64::INFINITY
+        };
+
+        let angle = if t <= t_limit {
+            velocity * t + 0.5 * self.acceleration * t * t
+        } else {
+            velocity * t_limit + 0.5 * self.acceleration * t_limit * t_limit + limit * (t - t_limit)
+        };
+
+        angle % std::f64::consts::TAU
+    }
+}
+
+impl Animate for AngularVelocity {
+    type Type = Quat;
+
+    fn animate(world: &mut World, animation: Entity, time: AnimationTime) -> Self::Type {
+        let data = Self::data(world, animation).unwrap();
+        Quat::from_axis_angle(data.axis.normalize_or_zero(), data.angle(time.t) as f32)
+    }
+}
+
+impl TimelineAnimation for AngularVelocity {
+    fn visit(_world: &World, _animation: Entity, _timeline_ui: TimelineAnimationContext) {}
+}
+
+pub trait AngularVelocityTrait<'w, 's, 'a, I> {
+    fn from_angular_velocity(
+        self,
+        angular_velocity: AngularVelocity,
+    ) -> AnimationEntityBuilder<'a, I, AngularVelocity>;
+}
+
+impl<'w, 's, 'a, I> AngularVelocityTrait<'w, 's, 'a, I> for AnimationBuilder<'a, I>
+where
+    I: AnimationBuilderTrait,
+{
+    fn from_angular_velocity(
+        self,
+        angular_velocity: AngularVelocity,
+    ) -> AnimationEntityBuilder<'a, I, AngularVelocity> {
+        let mut commands = self.spawn();
+        commands
+            .insert(Name::new("Angular Velocity"))
+            .insert(angular_velocity);
+
+        commands
+    }
+}
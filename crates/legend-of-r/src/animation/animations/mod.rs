@@ -3,3 +3,5 @@ pub mod entity_component_field;
 pub mod from_function;
 pub mod discrete;
 pub mod axial_function;
+pub mod angular_velocity;
+pub mod rotation_stops;
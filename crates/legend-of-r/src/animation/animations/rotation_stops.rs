@@ -0,0 +1,69 @@
+use bevy::prelude::{default, EulerRot, Quat, Vec3};
+
+use crate::prelude::{
+    AnimationBuilder, AnimationBuilderTrait, AnimationEntityBuilder, Discrete, DiscreteStop,
+    DiscreteStopsTrait,
+};
+
+/// Largest rotation about any one axis between two generated stops
+///
+/// Slerp always takes the shortest path between its endpoints,
+/// so stops are kept well under a half turn apart to preserve the intended direction.
+pub const ROTATION_STOP_MAX_ANGLE: f32 = std::f32::consts::FRAC_PI_2;
+
+/// Convert XYZ euler angle stops into quaternion stops suitable for slerping
+///
+/// Segments turning further than [`ROTATION_STOP_MAX_ANGLE`] are subdivided,
+/// so e.g. a stop at 0 followed by one at 2π produces a full revolution instead of no motion.
+pub fn rotation_stops(stops: impl IntoIterator<Item = (f64, Vec3)>) -> Vec<DiscreteStop<Quat>> {
+    let stops = stops.into_iter().collect::<Vec<_>>();
+
+    let quat = |euler: Vec3| Quat::from_euler(EulerRot::XYZ, euler.x, euler.y, euler.z);
+
+    let mut out = vec![];
+
+    for (i, (t, euler)) in stops.iter().copied().enumerate() {
+        out.push(DiscreteStop {
+            t,
+            value: quat(euler),
+            ..default()
+        });
+
+        let (next_t, next_euler) = if let Some(next) = stops.get(i + 1) {
+            *next
+        } else {
+            continue;
+        };
+
+        let delta = next_euler - euler;
+        let steps = (delta.abs().max_element() / ROTATION_STOP_MAX_ANGLE).ceil() as usize;
+
+        for step in 1..steps {
+            let f = step as f32 / steps as f32;
+            out.push(DiscreteStop {
+                t: t + (next_t - t) * f as f64,
+                value: quat(euler + delta * f),
+                ..default()
+            });
+        }
+    }
+
+    out
+}
+
+pub trait RotationStopsTrait<'w, 's, 'a, I, C>
+where
+    I: IntoIterator<Item = (f64, Vec3)>,
+{
+    fn from_rotation_stops(self, stops: I) -> AnimationEntityBuilder<'a, C, Discrete<Quat>>;
+}
+
+impl<'w, 's, 'a, I, C> RotationStopsTrait<'w, 's, 'a, I, C> for AnimationBuilder<'a, C>
+where
+    I: IntoIterator<Item = (f64, Vec3)>,
+    C: AnimationBuilderTrait,
+{
+    fn from_rotation_stops(self, stops: I) -> AnimationEntityBuilder<'a, C, Discrete<Quat>> {
+        self.from_discrete_stops(rotation_stops(stops))
+    }
+}
@@ -1,11 +1,14 @@
 //! Utility module for spawning two-stop animations designed to be
 //! updated by other animations
 
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    ops::{Div, Sub},
+};
 
 use bevy::{
     ecs::system::Command,
-    prelude::{default, BuildWorldChildren, Component, Entity, Name},
+    prelude::{default, BuildWorldChildren, Component, Entity, Name, Query},
 };
 
 use crate::util::default_entity;
@@ -18,8 +21,8 @@ use super::{
         time_source::TimeSourceTrait,
         try_animate_component_fields::TryAnimateComponentFieldsTrait,
     },
-    animations::discrete::{Discrete, DiscreteStop, DiscreteStopsTrait},
-    timeline::TimelineTime,
+    animations::discrete::{Determinisms, Discrete, DiscreteStop, DiscreteStopsTrait},
+    timeline::{Timeline, TimelineTime},
     AnimationTagTrait, BuildAnimation, Update,
 };
 
@@ -150,3 +153,167 @@ where
             });
     }
 }
+
+/// A recorded segment of a dynamic animation, changing linearly from one value to another
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DynamicSegment<T> {
+    pub from: DiscreteStop<T>,
+    pub to: DiscreteStop<T>,
+}
+
+impl<T> DynamicSegment<T>
+where
+    T: Copy + Default + Lerp + Sub<Output = T> + Div<f32, Output = T>,
+{
+    /// Value along the segment at time `t`
+    pub fn value_at(&self, t: f64) -> T {
+        if t >= self.to.t {
+            return self.to.value;
+        }
+
+        let len = self.to.t - self.from.t;
+        let f = if len > 0.0 {
+            ((t - self.from.t) / len).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        self.from.value.lerp(&self.to.value, f as f32)
+    }
+
+    /// Change in value per second at time `t`, or zero once the segment has ended
+    pub fn rate_at(&self, t: f64) -> T {
+        if self.to.t > self.from.t && t < self.to.t {
+            (self.to.value - self.from.value) / (self.to.t - self.from.t) as f32
+        } else {
+            T::default()
+        }
+    }
+}
+
+/// The segment of a dynamic animation in effect at its timeline's current time
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DynamicSegmentPlayback<T> {
+    pub t: f64,
+    /// Time the segment stops were last evaluated at
+    pub prev_t: f64,
+    pub segment: DynamicSegment<T>,
+}
+
+impl<C, T> DynamicAnimation<C, T>
+where
+    T: 'static + Send + Sync + Default + Clone,
+{
+    /// Fetch the current segment if a new one may be recorded in its place
+    ///
+    /// Returns None outside of regular playback, as pausing and scrubbing replay existing stops,
+    /// or if a segment has already been recorded at the current time
+    pub fn recordable_segment(
+        &self,
+        query_timeline: &Query<&Timeline>,
+        query_stops: &Query<&mut Discrete<DiscreteStop<T>>>,
+    ) -> Option<DynamicSegmentPlayback<T>> {
+        let timeline = query_timeline.get(self.timeline).ok()?;
+        if timeline.paused || timeline.scrub_rate != 0.0 {
+            return None;
+        }
+
+        let t = timeline.t;
+
+        let from_stops = query_stops.get(self.from_stops).ok()?;
+        let to_stops = query_stops.get(self.to_stops).ok()?;
+
+        if from_stops.stops().any(|stop| stop.t == t) {
+            return None;
+        }
+
+        Some(DynamicSegmentPlayback {
+            t,
+            prev_t: from_stops.t,
+            segment: DynamicSegment {
+                from: from_stops.stop_at(t)?.value.clone(),
+                to: to_stops.stop_at(t)?.value.clone(),
+            },
+        })
+    }
+
+    /// Record a new segment from `origin` at time `t` to `target` after `duration` seconds
+    pub fn record_segment(
+        &self,
+        determinisms: &mut Determinisms,
+        query_stops: &mut Query<&mut Discrete<DiscreteStop<T>>>,
+        t: f64,
+        origin: T,
+        target: T,
+        duration: f64,
+    ) {
+        let determinism = determinisms.next();
+
+        let mut from_stops = query_stops.get_mut(self.from_stops).unwrap();
+        from_stops.insert_stop(DiscreteStop {
+            t,
+            value: DiscreteStop {
+                t,
+                value: origin,
+                ..default()
+            },
+            determinism,
+            ..default()
+        });
+
+        let mut to_stops = query_stops.get_mut(self.to_stops).unwrap();
+        to_stops.insert_stop(DiscreteStop {
+            t,
+            value: DiscreteStop {
+                t: t + duration,
+                value: target,
+                ..default()
+            },
+            determinism,
+            ..default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment() -> DynamicSegment<f32> {
+        DynamicSegment {
+            from: DiscreteStop {
+                t: 1.0,
+                value: 2.0,
+                ..default()
+            },
+            to: DiscreteStop {
+                t: 3.0,
+                value: 6.0,
+                ..default()
+            },
+        }
+    }
+
+    #[test]
+    fn value_at_interpolates_and_clamps() {
+        let segment = segment();
+        assert_eq!(segment.value_at(0.0), 2.0);
+        assert_eq!(segment.value_at(2.0), 4.0);
+        assert_eq!(segment.value_at(4.0), 6.0);
+    }
+
+    #[test]
+    fn rate_at_is_zero_after_the_segment() {
+        let segment = segment();
+        assert_eq!(segment.rate_at(2.0), 2.0);
+        assert_eq!(segment.rate_at(3.0), 0.0);
+    }
+
+    #[test]
+    fn empty_segment_holds_its_target() {
+        let mut segment = segment();
+        segment.to.t = segment.from.t;
+        assert_eq!(segment.value_at(1.0), 6.0);
+        assert_eq!(segment.rate_at(0.0), 0.0);
+    }
+}
@@ -17,10 +17,11 @@ use bevy::{
 };
 
 use crate::prelude::{
-    adapters::offset::Offset, default_entity, evaluate, evaluate_tagged, After, AnimateComponents,
-    AxialFunction, Before, Curve, Dilate, Disable, Discrete, Discretize, EntityComponent, Evaluate,
-    Flatten, Multiply, Repeat, Sequence, TimelineAnimation, TimelineAnimationContext,
-    TimelinePlugin, TryAnimateComponents, TryReplaceComponents, VisitPointer,
+    adapters::offset::Offset, default_entity, evaluate, evaluate_tagged, After, AngularVelocity,
+    AnimateComponents, AxialFunction, Before, Curve, Dilate, Disable, Discrete, Discretize,
+    EntityComponent, Evaluate, Flatten, Multiply, Repeat, Sequence, TimelineAnimation,
+    TimelineAnimationContext, TimelinePlugin, TryAnimateComponents, TryReplaceComponents,
+    VisitPointer,
 };
 
 use self::animations::discrete::Determinisms;
@@ -33,6 +34,7 @@ pub struct AnimationPlugin {
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.register_type::<AxialFunction>()
+            .register_type::<AngularVelocity>()
             .register_type::<Evaluate>();
 
         app.add_plugin(TimelinePlugin {
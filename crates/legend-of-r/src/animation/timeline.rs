@@ -37,8 +37,10 @@ impl Plugin for TimelinePlugin {
 
         app.add_system_to_stage(CoreStage::First, timeline);
 
+        // Also read by the camera viewport, so present even without the UI
+        app.init_resource::<TimelineUiState>();
+
         if self.ui {
-            app.init_resource::<TimelineUiState>();
             app.add_system_to_stage(CoreStage::PreUpdate, timeline_ui::<()>);
         }
     }
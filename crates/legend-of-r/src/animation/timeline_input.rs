@@ -1,52 +1,59 @@
 use bevy::{
     input::{gamepad::gamepad_event_system, keyboard::keyboard_input_system},
     prelude::{
-        CoreStage, GamepadButton, GamepadButtonType, Input, KeyCode,
+        Axis, CoreStage, GamepadAxis, GamepadButton, GamepadButtonType, Gamepads, Input, KeyCode,
         ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, SystemLabel, SystemSet,
     },
     reflect::Reflect,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::prelude::Timeline;
+use crate::prelude::{InputBinding, InputBindings, InputState, Timeline};
 
 #[derive(SystemLabel)]
 pub struct TimelineInputLabel;
 
 pub struct TimelineInputPlugin;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, Serialize, Deserialize,
+)]
 pub enum TimelineInput {
     TogglePause,
     Rewind,
 }
 
-impl TryFrom<GamepadButton> for TimelineInput {
-    type Error = ();
-
-    fn try_from(value: GamepadButton) -> Result<Self, Self::Error> {
-        Ok(match value.button_type {
-            GamepadButtonType::Start => TimelineInput::TogglePause,
-            GamepadButtonType::Select => TimelineInput::Rewind,
-            _ => return Err(()),
-        })
-    }
+/// Input bindings for timeline controls, read from any gamepad
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineInputMap {
+    pub bindings: InputBindings<TimelineInput>,
 }
 
-impl TryFrom<KeyCode> for TimelineInput {
-    type Error = ();
-
-    fn try_from(value: KeyCode) -> Result<Self, Self::Error> {
-        Ok(match value {
-            KeyCode::Space => TimelineInput::TogglePause,
-            KeyCode::Back => TimelineInput::Rewind,
-            _ => return Err(()),
-        })
+impl Default for TimelineInputMap {
+    fn default() -> Self {
+        Self {
+            bindings: [
+                (TimelineInput::TogglePause, InputBinding::Key(KeyCode::Space)),
+                (TimelineInput::Rewind, InputBinding::Key(KeyCode::Back)),
+                (
+                    TimelineInput::TogglePause,
+                    InputBinding::GamepadButton(GamepadButtonType::Start),
+                ),
+                (
+                    TimelineInput::Rewind,
+                    InputBinding::GamepadButton(GamepadButtonType::Select),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        }
     }
 }
 
 impl Plugin for TimelineInputPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.insert_resource(Input::<TimelineInput>::default());
+        app.insert_resource(Input::<TimelineInput>::default())
+            .init_resource::<TimelineInputMap>();
 
         app.add_system_set_to_stage(
             CoreStage::PreUpdate,
@@ -55,16 +62,10 @@ impl Plugin for TimelineInputPlugin {
                     timeline_input_clear
                         .after(keyboard_input_system)
                         .after(gamepad_event_system)
-                        .before(timeline_input_press)
-                        .before(timeline_input_release),
-                )
-                .with_system(timeline_input_press)
-                .with_system(timeline_input_release)
-                .with_system(
-                    timeline_input
-                        .after(timeline_input_press)
-                        .after(timeline_input_release),
+                        .before(timeline_input_update),
                 )
+                .with_system(timeline_input_update)
+                .with_system(timeline_input.after(timeline_input_update))
                 .label(TimelineInputLabel),
         );
     }
@@ -74,40 +75,24 @@ fn timeline_input_clear(mut timeline_input: ResMut<Input<TimelineInput>>) {
     timeline_input.clear();
 }
 
-pub fn timeline_input_press(
-    key_input: Res<Input<KeyCode>>,
-    gamepad_input: Res<Input<GamepadButton>>,
-    mut timeline_input: ResMut<Input<TimelineInput>>,
-) {
-    for press in key_input.get_just_pressed() {
-        if let Ok(input) = TimelineInput::try_from(*press) {
-            timeline_input.press(input);
-        }
-    }
-
-    for press in gamepad_input.get_just_pressed() {
-        if let Ok(input) = TimelineInput::try_from(*press) {
-            timeline_input.press(input);
-        }
-    }
-}
-
-pub fn timeline_input_release(
+pub fn timeline_input_update(
     key_input: Res<Input<KeyCode>>,
     gamepad_input: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    timeline_input_map: Res<TimelineInputMap>,
     mut timeline_input: ResMut<Input<TimelineInput>>,
 ) {
-    for press in key_input.get_just_released() {
-        if let Ok(input) = TimelineInput::try_from(*press) {
-            timeline_input.release(input);
-        }
-    }
-
-    for press in gamepad_input.get_just_released() {
-        if let Ok(input) = TimelineInput::try_from(*press) {
-            timeline_input.release(input);
-        }
-    }
+    let state = InputState {
+        keys: &key_input,
+        buttons: &gamepad_input,
+        axes: &gamepad_axes,
+        gamepads: &gamepads,
+    };
+
+    timeline_input_map
+        .bindings
+        .update(&state, None, &mut timeline_input);
 }
 
 pub fn timeline_input(timeline_input: Res<Input<TimelineInput>>, mut query: Query<&mut Timeline>) {
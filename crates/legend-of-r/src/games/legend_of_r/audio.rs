@@ -0,0 +1,102 @@
+//! Sound cues for gameplay events
+//!
+//! The clips below aren't tracked in the repository, see `assets/audio/README.md`.
+//! Missing files are logged by the asset server and play silence.
+
+use bevy::prelude::{
+    default, AssetServer, AudioSource, CoreStage, EventReader, EventWriter, FromWorld, Handle,
+    ParallelSystemDescriptorCoercion, Plugin, Query, Res, World,
+};
+
+use crate::prelude::{
+    lives_death, timeline_death, DeathEvent, RespawnEvent, SoundCue, SoundCueEvent, Timeline,
+    TimelineDamage,
+};
+
+/// Stage 1 music, relative to the assets directory
+pub const MUSIC_STAGE_1: &str = "audio/stage_1.ogg";
+/// Played when a ship or enemy is destroyed
+pub const SOUND_DEATH: &str = "audio/death.ogg";
+/// Played when a ship respawns
+pub const SOUND_RESPAWN: &str = "audio/respawn.ogg";
+
+pub struct LegendOfRAudioPlugin;
+
+impl Plugin for LegendOfRAudioPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<LegendOfRSounds>();
+
+        app.add_system_to_stage(CoreStage::PostUpdate, death_sound.after(timeline_death))
+            .add_system_to_stage(CoreStage::PostUpdate, respawn_sound.after(lives_death));
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LegendOfRSounds {
+    pub death: Handle<AudioSource>,
+    pub respawn: Handle<AudioSource>,
+}
+
+impl FromWorld for LegendOfRSounds {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+
+        LegendOfRSounds {
+            death: asset_server.load(SOUND_DEATH),
+            respawn: asset_server.load(SOUND_RESPAWN),
+        }
+    }
+}
+
+pub fn death_sound(
+    sounds: Res<LegendOfRSounds>,
+    mut death_events: EventReader<DeathEvent>,
+    query_damage: Query<&TimelineDamage>,
+    query_timeline: Query<&Timeline>,
+    mut sound_cue_events: EventWriter<SoundCueEvent>,
+) {
+    for event in death_events.iter() {
+        let timeline_damage = if let Ok(components) = query_damage.get(event.entity) {
+            components
+        } else {
+            continue;
+        };
+
+        let timeline = query_timeline.get(timeline_damage.timeline).unwrap();
+
+        sound_cue_events.send(SoundCueEvent {
+            timeline: timeline_damage.timeline,
+            t: timeline.t,
+            cue: SoundCue {
+                sound: sounds.death.clone(),
+                ..default()
+            },
+            determinism: event.determinism,
+        });
+    }
+}
+
+pub fn respawn_sound(
+    sounds: Res<LegendOfRSounds>,
+    mut respawn_events: EventReader<RespawnEvent>,
+    query_damage: Query<&TimelineDamage>,
+    mut sound_cue_events: EventWriter<SoundCueEvent>,
+) {
+    for event in respawn_events.iter() {
+        let timeline_damage = if let Ok(components) = query_damage.get(event.entity) {
+            components
+        } else {
+            continue;
+        };
+
+        sound_cue_events.send(SoundCueEvent {
+            timeline: timeline_damage.timeline,
+            t: event.t,
+            cue: SoundCue {
+                sound: sounds.respawn.clone(),
+                ..default()
+            },
+            determinism: event.determinism,
+        });
+    }
+}
@@ -0,0 +1,860 @@
+//! Generational campaign structure
+//!
+//! Each of the five generations acts as one credit. Running out of lives moves the run on to
+//! the next, more powerful generation instead of ending it, and clearing every stage with a
+//! generation counts as a successive clear before moving on to the next.
+//!
+//! Run progress is derived from a log of timed campaign events,
+//! so rewinding past a clear or a game over undoes it along with the rest of the timeline.
+//! Unlocked generations and reached endings persist between sessions.
+
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::{
+    default, info, warn, AssetServer, Commands, CoreStage, DespawnRecursiveExt, Entity,
+    EventReader, EventWriter, ParallelSystemDescriptorCoercion, Parent, Plugin, Query, Res,
+    ResMut, Transform, With, World,
+};
+use bevy_inspector_egui::egui::{ProgressBar, Response, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    lives_death, lives_respawn, player_input_update, timeline_death, Alive, Boss, BossCore,
+    DeathEvent, Discrete, DiscreteStop, GameOverEvent, InsertStage, Lives, PlayerId,
+    PlayerInput, PlayerInputs, RespawnEvent, Ship, SimulationArgs, TimeAbilities, TimeRewind,
+    TimeScale, Timeline, TimelineLives, Vulcan, Widget, Widgets, REWIND_TIME,
+    REWIND_TIME_UNLIMITED, SLOW_TIME, STAGE_1, STOP_TIME,
+};
+
+pub const CAMPAIGN_FILE: &str = "campaign.ron";
+
+/// Stages played by each generation, in order
+pub const CAMPAIGN_STAGES: &[&str] = &[STAGE_1];
+
+/// Timeline seconds between clearing a stage and the next one starting
+pub const STAGE_INTERVAL: f64 = 3.0;
+
+/// A generation of ship technology, from the weakest to the strongest
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GenerationDesc {
+    pub name: &'static str,
+    pub vulcan_emitters: usize,
+    pub slow_time: Option<TimeScale>,
+    pub stop_time: Option<TimeScale>,
+    pub rewind_time: Option<TimeRewind>,
+}
+
+impl GenerationDesc {
+    /// Vulcan emitters spread vertically across the nose of the ship
+    pub fn emitters(&self) -> Vec<Transform> {
+        let center = (self.vulcan_emitters.max(1) - 1) as f32 * 0.5;
+
+        (0..self.vulcan_emitters)
+            .map(|i| Transform::from_xyz(0.75, (i as f32 - center) * 0.2, 0.0))
+            .collect()
+    }
+}
+
+pub const GENERATIONS: [GenerationDesc; 5] = [
+    GenerationDesc {
+        name: "R+",
+        vulcan_emitters: 1,
+        slow_time: None,
+        stop_time: None,
+        rewind_time: None,
+    },
+    GenerationDesc {
+        name: "R+ DX",
+        vulcan_emitters: 2,
+        slow_time: None,
+        stop_time: None,
+        rewind_time: None,
+    },
+    GenerationDesc {
+        name: "Exotic",
+        vulcan_emitters: 3,
+        slow_time: Some(SLOW_TIME),
+        stop_time: Some(STOP_TIME),
+        rewind_time: None,
+    },
+    GenerationDesc {
+        name: "Exotic II",
+        vulcan_emitters: 4,
+        slow_time: Some(SLOW_TIME),
+        stop_time: Some(STOP_TIME),
+        rewind_time: Some(REWIND_TIME),
+    },
+    GenerationDesc {
+        name: "Maximum Exotica",
+        vulcan_emitters: 5,
+        slow_time: Some(SLOW_TIME),
+        stop_time: Some(STOP_TIME),
+        rewind_time: Some(REWIND_TIME_UNLIMITED),
+    },
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Ending {
+    /// Ran out of generations without clearing the game
+    Defeat,
+    /// First cleared with the given generation; earlier generations give better endings
+    Generation(usize),
+    /// Cleared with every generation in a single run
+    True,
+}
+
+/// Progress persisted between sessions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CampaignSave {
+    /// Number of generations available from ship select
+    pub unlocked: usize,
+    /// Most successive clears achieved in a single run
+    pub best_clears: usize,
+    pub endings: Vec<Ending>,
+}
+
+impl Default for CampaignSave {
+    fn default() -> Self {
+        Self {
+            unlocked: 1,
+            best_clears: 0,
+            endings: default(),
+        }
+    }
+}
+
+impl CampaignSave {
+    pub fn load(path: &Path) -> Option<Self> {
+        let save = std::fs::read_to_string(path).ok()?;
+        match ron::from_str(&save) {
+            Ok(save) => Some(save),
+            Err(e) => {
+                warn!("Failed to parse campaign save {path:?}: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let save = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, save)?;
+        Ok(())
+    }
+}
+
+/// Location that campaign progress is saved to, or None to keep it in memory only
+#[derive(Debug, Clone)]
+pub struct CampaignSavePath(pub Option<PathBuf>);
+
+/// Path to the campaign save next to the running executable
+pub fn campaign_save_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+        .join(CAMPAIGN_FILE)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CampaignEvent {
+    StageCleared,
+    GameOver,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CampaignEntry {
+    /// Timeline that t was measured on
+    pub timeline: Entity,
+    pub t: f64,
+    pub event: CampaignEvent,
+    /// Stage spawned in response, despawned again if the entry is rewound
+    pub stage: Option<Entity>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CampaignState {
+    /// Timeline is held while the first player picks a starting generation
+    ShipSelect { selected: usize },
+    Playing,
+}
+
+/// Where new stages are spawned, and what their enemies target
+#[derive(Debug, Clone, PartialEq)]
+pub struct CampaignStages {
+    pub playfield: Entity,
+    pub timeline: Entity,
+    pub targets: Vec<Entity>,
+    /// Stage spawned at startup
+    pub first: Entity,
+}
+
+/// Run progress derived from the campaign log
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CampaignProgress {
+    /// Index into GENERATIONS
+    pub generation: usize,
+    /// Index into CAMPAIGN_STAGES
+    pub stage: usize,
+    pub clears: usize,
+    pub first_clear: Option<usize>,
+    pub ending: Option<Ending>,
+}
+
+impl CampaignProgress {
+    pub fn generation_desc(&self) -> &'static GenerationDesc {
+        &GENERATIONS[self.generation]
+    }
+
+    fn apply(&mut self, event: CampaignEvent) {
+        if self.ending.is_some() {
+            return;
+        }
+
+        let last_generation = self.generation + 1 >= GENERATIONS.len();
+
+        match event {
+            CampaignEvent::StageCleared => {
+                self.stage += 1;
+                if self.stage < CAMPAIGN_STAGES.len() {
+                    return;
+                }
+
+                self.clears += 1;
+                self.first_clear.get_or_insert(self.generation);
+
+                if last_generation {
+                    self.ending = Some(if self.clears >= GENERATIONS.len() {
+                        Ending::True
+                    } else {
+                        Ending::Generation(self.first_clear.unwrap())
+                    });
+                } else {
+                    self.generation += 1;
+                    self.stage = 0;
+                }
+            }
+            // Continue from the same stage with the next generation
+            CampaignEvent::GameOver => {
+                if last_generation {
+                    self.ending = Some(
+                        self.first_clear
+                            .map(Ending::Generation)
+                            .unwrap_or(Ending::Defeat),
+                    );
+                } else {
+                    self.generation += 1;
+                }
+            }
+        }
+    }
+
+    /// Whether clearing the current stage unlocks the next generation
+    ///
+    /// Generation N+1 unlocks once stage N has been cleared with generation N.
+    pub fn clear_unlocks(&self) -> bool {
+        self.stage >= self.generation.min(CAMPAIGN_STAGES.len() - 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Campaign {
+    pub state: CampaignState,
+    pub starting_generation: usize,
+    pub stages: Option<CampaignStages>,
+    pub log: Vec<CampaignEntry>,
+}
+
+impl Default for Campaign {
+    fn default() -> Self {
+        Self {
+            state: CampaignState::Playing,
+            starting_generation: 0,
+            stages: None,
+            log: default(),
+        }
+    }
+}
+
+impl Campaign {
+    pub fn progress(&self) -> CampaignProgress {
+        let mut progress = CampaignProgress {
+            generation: self.starting_generation,
+            ..default()
+        };
+
+        for entry in self.log.iter() {
+            progress.apply(entry.event);
+        }
+
+        progress
+    }
+
+    /// Stage currently being played
+    pub fn current_stage(&self) -> Option<Entity> {
+        self.log
+            .iter()
+            .rev()
+            .find_map(|entry| entry.stage)
+            .or_else(|| self.stages.as_ref().map(|stages| stages.first))
+    }
+}
+
+pub struct CampaignPlugin {
+    pub path: Option<PathBuf>,
+}
+
+impl Default for CampaignPlugin {
+    fn default() -> Self {
+        Self {
+            path: Some(campaign_save_path()),
+        }
+    }
+}
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        // Simulations start from a fresh campaign so their results don't depend on local saves
+        let path = self
+            .path
+            .clone()
+            .filter(|_| !app.world.contains_resource::<SimulationArgs>());
+
+        let save = path
+            .as_deref()
+            .and_then(CampaignSave::load)
+            .unwrap_or_default();
+
+        let state = if save.unlocked > 1 {
+            CampaignState::ShipSelect {
+                selected: save.unlocked - 1,
+            }
+        } else {
+            CampaignState::Playing
+        };
+
+        app.insert_resource(Campaign { state, ..default() })
+            .insert_resource(save)
+            .insert_resource(CampaignSavePath(path));
+
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            campaign_ship_select.after(player_input_update),
+        )
+        .add_system(campaign_rewind)
+        .add_system(campaign_generation.after(campaign_rewind))
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            campaign_game_over.after(lives_death).before(lives_respawn),
+        )
+        .add_system_to_stage(CoreStage::PostUpdate, campaign_stage_clear.after(timeline_death));
+
+        app.add_startup_system(setup_campaign_widget);
+    }
+}
+
+fn save_campaign(save: &CampaignSave, path: &CampaignSavePath) {
+    let path = if let Some(path) = &path.0 {
+        path
+    } else {
+        return;
+    };
+
+    if let Err(e) = save.save(path) {
+        warn!("Failed to save campaign to {path:?}: {e}");
+    }
+}
+
+/// Record the unlocks and ending earned by a newly logged campaign event
+fn campaign_progress(
+    campaign: &Campaign,
+    event: CampaignEvent,
+    save: &mut CampaignSave,
+    path: &CampaignSavePath,
+) {
+    let before = campaign.progress();
+    let mut after = before;
+    after.apply(event);
+
+    let mut changed = false;
+
+    if event == CampaignEvent::StageCleared && before.clear_unlocks() {
+        let unlocked = (before.generation + 2).min(GENERATIONS.len());
+        if unlocked > save.unlocked {
+            info!("Unlocked generation {}", unlocked);
+            save.unlocked = unlocked;
+            changed = true;
+        }
+    }
+
+    if after.clears > save.best_clears {
+        save.best_clears = after.clears;
+        changed = true;
+    }
+
+    if let Some(ending) = after.ending {
+        info!("Campaign reached ending {ending:?} after {} clears", after.clears);
+        if !save.endings.contains(&ending) {
+            save.endings.push(ending);
+            save.endings.sort();
+            changed = true;
+        }
+    }
+
+    if changed {
+        save_campaign(save, path);
+    }
+}
+
+/// Hold the timeline while a starting generation is picked
+pub fn campaign_ship_select(
+    save: Res<CampaignSave>,
+    inputs: Res<PlayerInputs>,
+    mut campaign: ResMut<Campaign>,
+    mut query_timeline: Query<&mut Timeline>,
+) {
+    let mut selected = if let CampaignState::ShipSelect { selected } = campaign.state {
+        selected
+    } else {
+        return;
+    };
+
+    let player = PlayerId(0);
+
+    if inputs.just_pressed(player, PlayerInput::Up) {
+        selected = (selected + 1).min(save.unlocked - 1);
+    }
+
+    if inputs.just_pressed(player, PlayerInput::Down) {
+        selected = selected.saturating_sub(1);
+    }
+
+    let start = inputs.just_pressed(player, PlayerInput::Fire);
+    if start {
+        info!("Starting campaign with generation {}", selected + 1);
+        campaign.starting_generation = selected;
+        campaign.state = CampaignState::Playing;
+    } else {
+        campaign.state = CampaignState::ShipSelect { selected };
+    }
+
+    for mut timeline in query_timeline.iter_mut() {
+        if timeline.paused != !start {
+            timeline.paused = !start;
+        }
+    }
+}
+
+/// Forget campaign events that haven't happened yet at the rewound time
+pub fn campaign_rewind(
+    mut campaign: ResMut<Campaign>,
+    query_timeline: Query<&Timeline>,
+    mut commands: Commands,
+) {
+    // Entries can be on different timelines, so a later entry may outlive an earlier one
+    let rewound = |entry: &CampaignEntry| {
+        query_timeline
+            .get(entry.timeline)
+            .map(|timeline| entry.t > timeline.t)
+            .unwrap_or_default()
+    };
+
+    if !campaign.log.iter().any(rewound) {
+        return;
+    }
+
+    campaign.log.retain(|entry| {
+        if !rewound(entry) {
+            return true;
+        }
+
+        if let Some(stage) = entry.stage {
+            commands.entity(stage).despawn_recursive();
+        }
+
+        false
+    });
+}
+
+/// Fit ships with the equipment of the current generation
+pub fn campaign_generation(
+    campaign: Res<Campaign>,
+    mut query_ship: Query<(&mut Vulcan, Option<&mut TimeAbilities>), With<Ship>>,
+) {
+    let generation = campaign.progress().generation_desc();
+    let emitters = generation.emitters();
+
+    for (mut vulcan, time_abilities) in query_ship.iter_mut() {
+        if vulcan.emitters != emitters {
+            vulcan.emitters = emitters.clone();
+        }
+
+        let mut time_abilities = if let Some(time_abilities) = time_abilities {
+            time_abilities
+        } else {
+            continue;
+        };
+
+        if time_abilities.slow != generation.slow_time
+            || time_abilities.stop != generation.stop_time
+            || time_abilities.rewind != generation.rewind_time
+        {
+            time_abilities.slow = generation.slow_time;
+            time_abilities.stop = generation.stop_time;
+            time_abilities.rewind = generation.rewind_time;
+        }
+    }
+}
+
+/// Move on to the next generation when lives run out, refilling lives and respawning
+pub fn campaign_game_over(
+    save_path: Res<CampaignSavePath>,
+    mut save: ResMut<CampaignSave>,
+    mut campaign: ResMut<Campaign>,
+    mut game_over_events: EventReader<GameOverEvent>,
+    query_lives: Query<&TimelineLives>,
+    mut query_lives_stops: Query<&mut Discrete<Lives>>,
+    mut respawn_events: EventWriter<RespawnEvent>,
+) {
+    for event in game_over_events.iter() {
+        // Players sharing lives can run out together, which only costs one generation
+        let simultaneous = matches!(
+            campaign.log.last(),
+            Some(entry) if entry.event == CampaignEvent::GameOver && entry.t == event.t
+        );
+
+        if simultaneous || campaign.progress().ending.is_some() {
+            continue;
+        }
+
+        let timeline_lives = if let Ok(components) = query_lives.get(event.lives) {
+            components
+        } else {
+            continue;
+        };
+
+        campaign_progress(&campaign, CampaignEvent::GameOver, &mut save, &save_path);
+
+        campaign.log.push(CampaignEntry {
+            timeline: timeline_lives.timeline,
+            t: event.t,
+            event: CampaignEvent::GameOver,
+            stage: None,
+        });
+
+        let progress = campaign.progress();
+        if progress.ending.is_some() {
+            continue;
+        }
+
+        info!(
+            "Game over, continuing with generation {} ({})",
+            progress.generation + 1,
+            progress.generation_desc().name
+        );
+
+        // Refill lives, including the one spent on the respawn
+        let mut lives_stops = query_lives_stops
+            .get_mut(timeline_lives.lives_stops)
+            .unwrap();
+
+        lives_stops.insert_stop(DiscreteStop {
+            t: event.t,
+            value: Lives {
+                lives: Lives::default().lives + 1,
+            },
+            determinism: event.determinism,
+            ..default()
+        });
+
+        respawn_events.send(RespawnEvent {
+            entity: event.entity,
+            lives: event.lives,
+            t: event.t + 1.0,
+            determinism: event.determinism,
+        });
+    }
+}
+
+/// Advance to the next stage once every boss in the current one has been destroyed
+pub fn campaign_stage_clear(
+    asset_server: Res<AssetServer>,
+    save_path: Res<CampaignSavePath>,
+    mut save: ResMut<CampaignSave>,
+    mut campaign: ResMut<Campaign>,
+    mut death_events: EventReader<DeathEvent>,
+    query_core: Query<&BossCore>,
+    query_boss: Query<(&Boss, &Parent)>,
+    query_alive: Query<&Alive>,
+    query_timeline: Query<&Timeline>,
+    mut commands: Commands,
+) {
+    for event in death_events.iter() {
+        let core = if let Ok(components) = query_core.get(event.entity) {
+            components
+        } else {
+            continue;
+        };
+
+        let (boss, parent) = query_boss.get(core.boss).unwrap();
+        let stage = parent.get();
+
+        if campaign.current_stage() != Some(stage) || campaign.progress().ending.is_some() {
+            continue;
+        }
+
+        // The dying core may not have been marked dead yet
+        let cleared = query_boss
+            .iter()
+            .filter(|(_, parent)| parent.get() == stage)
+            .all(|(boss, _)| {
+                boss.core == event.entity
+                    || query_alive.get(boss.core).map(|alive| !alive.0).unwrap_or(true)
+            });
+
+        if !cleared {
+            continue;
+        }
+
+        let t = query_timeline.get(boss.timeline).unwrap().t;
+
+        campaign_progress(&campaign, CampaignEvent::StageCleared, &mut save, &save_path);
+
+        let mut progress = campaign.progress();
+        progress.apply(CampaignEvent::StageCleared);
+
+        info!("Stage cleared with {} successive clears", progress.clears);
+
+        // Start the next stage after a short break, unless the run has ended
+        let next_stage = match (&campaign.stages, progress.ending) {
+            (Some(stages), None) => {
+                let entity = commands.spawn().id();
+
+                commands.add(InsertStage {
+                    playfield: stages.playfield,
+                    timeline: stages.timeline,
+                    entity,
+                    stage: asset_server.load(CAMPAIGN_STAGES[progress.stage]),
+                    targets: stages.targets.clone(),
+                    offset: t + STAGE_INTERVAL,
+                });
+
+                Some(entity)
+            }
+            _ => None,
+        };
+
+        campaign.log.push(CampaignEntry {
+            timeline: boss.timeline,
+            t,
+            event: CampaignEvent::StageCleared,
+            stage: next_stage,
+        });
+    }
+}
+
+pub fn setup_campaign_widget(widgets: Option<Res<Widgets>>) {
+    if let Some(widgets) = widgets {
+        widgets
+            .panel_outer_max
+            .add_widget(CampaignWidget::default());
+    }
+}
+
+/// UI widget displaying run progress, ship select and endings
+#[derive(Debug, Default, Clone)]
+pub struct CampaignWidget {
+    state: Option<CampaignState>,
+    progress: CampaignProgress,
+    save: Option<CampaignSave>,
+    /// Slow / stop and rewind gauges for each player with time abilities
+    gauges: Vec<(PlayerId, Option<f32>, Option<f32>)>,
+}
+
+impl Widget for CampaignWidget {
+    fn update(&mut self, world: &mut World) {
+        let campaign = world.resource::<Campaign>();
+        self.state = Some(campaign.state);
+        self.progress = campaign.progress();
+        self.save = Some(world.resource::<CampaignSave>().clone());
+
+        self.gauges = world
+            .query::<(&PlayerId, &TimeAbilities)>()
+            .iter(world)
+            .filter(|(_, abilities)| abilities.any())
+            .map(|(player, abilities)| {
+                let scale = abilities.slow.or(abilities.stop).map(|_| abilities.scale_gauge);
+                let rewind = abilities
+                    .rewind
+                    .filter(|rewind| rewind.drain > 0.0)
+                    .map(|_| abilities.rewind_gauge);
+                (*player, scale, rewind)
+            })
+            .collect();
+
+        self.gauges.sort_by_key(|(player, _, _)| player.0);
+    }
+
+    fn title(&self) -> Cow<'static, str> {
+        Cow::Borrowed("Campaign")
+    }
+
+    fn ui(&mut self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            let unlocked = self.save.as_ref().map(|save| save.unlocked).unwrap_or(1);
+
+            if let Some(CampaignState::ShipSelect { selected }) = self.state {
+                ui.label("Ship select: Up / Down to choose, Fire to launch");
+
+                for (i, generation) in GENERATIONS.iter().enumerate().take(unlocked) {
+                    let _ = ui.selectable_label(
+                        i == selected,
+                        format!("Generation {} - {}", i + 1, generation.name),
+                    );
+                }
+
+                return;
+            }
+
+            let progress = &self.progress;
+            ui.label(format!(
+                "Generation {} - {}",
+                progress.generation + 1,
+                progress.generation_desc().name
+            ));
+            ui.label(format!(
+                "Stage {} of {}",
+                (progress.stage + 1).min(CAMPAIGN_STAGES.len()),
+                CAMPAIGN_STAGES.len()
+            ));
+            ui.label(format!("Successive clears: {}", progress.clears));
+
+            if let Some(ending) = progress.ending {
+                ui.label(format!("Ending: {ending:?}"));
+            }
+
+            for (player, scale, rewind) in self.gauges.iter() {
+                ui.label(format!("Player {}", player.0 + 1));
+
+                if let Some(scale) = scale {
+                    ui.add(ProgressBar::new(*scale).text(format!("TIME {:.0}%", scale * 100.0)));
+                }
+
+                if let Some(rewind) = rewind {
+                    ui.add(ProgressBar::new(*rewind).text(format!("RWD {:.0}%", rewind * 100.0)));
+                }
+            }
+
+            if let Some(save) = &self.save {
+                ui.separator();
+                ui.label(format!(
+                    "Unlocked generations: {} of {}",
+                    save.unlocked,
+                    GENERATIONS.len()
+                ));
+                ui.label(format!("Best successive clears: {}", save.best_clears));
+            }
+        })
+        .response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(starting_generation: usize, events: &[CampaignEvent]) -> CampaignProgress {
+        Campaign {
+            starting_generation,
+            log: events
+                .iter()
+                .map(|event| CampaignEntry {
+                    timeline: Entity::from_raw(0),
+                    t: 0.0,
+                    event: *event,
+                    stage: None,
+                })
+                .collect(),
+            ..default()
+        }
+        .progress()
+    }
+
+    #[test]
+    fn emitters_are_centered() {
+        for generation in GENERATIONS.iter() {
+            let emitters = generation.emitters();
+            assert_eq!(emitters.len(), generation.vulcan_emitters);
+
+            let sum = emitters.iter().map(|emitter| emitter.translation.y).sum::<f32>();
+            assert!(sum.abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn game_over_advances_generation() {
+        let progress = progress(0, &[CampaignEvent::GameOver]);
+        assert_eq!(progress.generation, 1);
+        assert_eq!(progress.clears, 0);
+        assert_eq!(progress.ending, None);
+    }
+
+    #[test]
+    fn clearing_every_stage_advances_generation() {
+        let events = vec![CampaignEvent::StageCleared; CAMPAIGN_STAGES.len()];
+        let progress = progress(0, &events);
+        assert_eq!(progress.generation, 1);
+        assert_eq!(progress.stage, 0);
+        assert_eq!(progress.clears, 1);
+        assert_eq!(progress.first_clear, Some(0));
+    }
+
+    #[test]
+    fn clearing_with_every_generation_is_the_true_ending() {
+        let events = vec![CampaignEvent::StageCleared; CAMPAIGN_STAGES.len() * GENERATIONS.len()];
+        assert_eq!(progress(0, &events).ending, Some(Ending::True));
+    }
+
+    #[test]
+    fn endings_follow_the_first_clear() {
+        let last = GENERATIONS.len() - 1;
+        assert_eq!(
+            progress(last, &[CampaignEvent::GameOver]).ending,
+            Some(Ending::Defeat)
+        );
+
+        let mut events = vec![CampaignEvent::GameOver];
+        events.extend(vec![CampaignEvent::StageCleared; CAMPAIGN_STAGES.len()]);
+        events.push(CampaignEvent::GameOver);
+        assert_eq!(
+            progress(last - 2, &events).ending,
+            Some(Ending::Generation(last - 1))
+        );
+    }
+
+    #[test]
+    fn ending_stops_progress() {
+        let last = GENERATIONS.len() - 1;
+        let ended = progress(last, &[CampaignEvent::GameOver]);
+        let after = progress(last, &[CampaignEvent::GameOver, CampaignEvent::StageCleared]);
+        assert_eq!(ended, after);
+    }
+
+    #[test]
+    fn save_round_trips() {
+        let path = std::env::temp_dir().join(format!("campaign-{}.ron", std::process::id()));
+        let save = CampaignSave {
+            unlocked: 3,
+            best_clears: 2,
+            endings: vec![Ending::Generation(1), Ending::True],
+        };
+
+        save.save(&path).unwrap();
+        let loaded = CampaignSave::load(&path);
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded, Some(save));
+    }
+}
@@ -4,8 +4,8 @@ use bevy::{
     ecs::{reflect::ReflectComponent, system::Command},
     prelude::{
         default, info, BuildWorldChildren, Bundle, Component, CoreStage, Entity, EventReader,
-        EventWriter, Input, Name, ParallelSystemDescriptorCoercion, Parent, Plugin, Quat, Query,
-        Res, ResMut, SystemSet, Transform, Vec3, With, Without, World,
+        EventWriter, Name, ParallelSystemDescriptorCoercion, Parent, Plugin, Quat, Query, Res,
+        ResMut, SystemSet, Transform, Vec3, With, Without, World,
     },
     reflect::Reflect,
 };
@@ -22,19 +22,21 @@ use crate::{
         shapecast_depenetration::InsertShapecastDepenetration,
     },
     hierarchy::HierarchyBundle,
-    scene::{InsertScene, InsertSceneArchive},
     prelude::{
-        contact_depenetration, default_entity, linear_move_integrate, linear_move_to, lives_death,
-        player_input_press, player_input_release, shapecast_depenetration, ship_alive,
-        timeline_damage, Alive, AnimateComponentFieldsTrait, AnimationTagTrait, ArchiveBundle,
-        AspectRatio, BuildAnimation, CameraPivotSource, CollisionGroup, CollisionGroupFlags,
+        contact_depenetration, default_entity, evaluate_tagged, linear_move_to, lives_death,
+        player_input_update, shapecast_depenetration, ship_alive, timeline_damage, Alive,
+        AngularVelocity, AngularVelocityTrait, AnimateComponentFieldsTrait, AnimationTagTrait,
+        ArchiveBundle, AspectRatio, BuildAnimation, CameraPivotSource, CollisionGroupNames,
         ComponentBundle, ContactDepenetration, DeathEvent, Discrete, DiscreteStopsTrait,
-        EvaluateTrait, HitboxBundle, InsertEntityPool, InterpolateTrait, LinearMoveTo,
-        LinearMoveToPlugin, PlayerInput, Playfield, PlayfieldRotationTargetBundle, RespawnEvent,
-        SensorBundle, ShapecastDepenetration, SpawnBullet, TimeSourceTrait, Timeline,
-        TimelineDamage, TimelineTime, TryAnimateComponentsTrait, TryReplaceComponentsTrait, Update,
-        Vulcan, VulcanTimer, SCENE_PLAYER_BULLET,
+        EntityPool, EvaluateTrait, HitboxBundle, InsertEntityPool, InsertTimelineLinearMove,
+        LinearMove, LinearMoveTo, LinearMoveToPlugin, MeshRotation, MeshRotationPivot, PlayerId,
+        PlayerInput, PlayerInputs, Playfield, PlayfieldLayer, PlayfieldRotationTargetBundle,
+        RegisterArchiveBundle, RespawnEvent, SensorBundle, ShapecastDepenetration, SpawnBullet,
+        TimeSourceTrait, Timeline, TimelineDamage, TimelineTime, TryAnimateComponentsTrait,
+        TryReplaceComponentsTrait, Update, Vulcan, VulcanTimer, LINEAR_MOVE_EPSILON,
+        SCENE_PLAYER_BULLET,
     },
+    scene::{InsertScene, InsertSceneArchive},
 };
 pub const SCENE_FORCE: &str = "meshes/Force.gltf#Scene0";
 
@@ -44,54 +46,53 @@ impl Plugin for ForcePlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.register_type::<Force>().register_type::<ForceState>();
 
+        app.register_archive_bundle::<ComponentBundle<Collider>>();
+
         app.add_plugin(LinearMoveToPlugin);
 
         app.add_event::<ForceStateEvent>()
             .add_event::<ForceInputEvent>()
             .add_event::<ForceVulcanEvent>();
 
-        app.add_system_to_stage(
-            CoreStage::PreUpdate,
-            force_input
-                .after(player_input_press)
-                .after(player_input_release),
-        )
-        .add_system(
-            force_dock_transform
-                .after(linear_move_integrate)
-                .after(ship_alive),
-        )
-        .add_system(force_target.before(linear_move_to))
-        .add_system_set_to_stage(
-            PhysicsStages::StepSimulation,
-            SystemSet::default()
-                .with_system(force_contact_depenetration.before(contact_depenetration))
-                .with_system(force_ship_contact_depenetration.before(contact_depenetration))
-                .with_system(force_shapecast_depenetration.before(shapecast_depenetration))
-                .with_system(force_ship_shapecast_depenetration.before(shapecast_depenetration)),
-        )
-        .add_system_to_stage(
-            CoreStage::PostUpdate,
-            force_ship_death.after(timeline_damage),
-        )
-        .add_system_to_stage(CoreStage::PostUpdate, force_ship_respawn.after(lives_death))
-        .add_system_to_stage(CoreStage::PostUpdate, force_body_collision)
-        .add_system_to_stage(CoreStage::PostUpdate, force_sensor_collision)
-        .add_system_to_stage(
-            CoreStage::PostUpdate,
-            force_state_event
-                .after(force_ship_death)
-                .after(force_ship_respawn)
-                .after(force_body_collision),
-        )
-        .add_system_to_stage(
-            CoreStage::PostUpdate,
-            force_input_event.after(force_state_event),
-        )
-        .add_system_to_stage(
-            CoreStage::PostUpdate,
-            force_vulcan_event.after(force_state_event),
-        );
+        app.add_system_to_stage(CoreStage::PreUpdate, force_input.after(player_input_update))
+            .add_system(
+                force_dock_transform
+                    .after(evaluate_tagged::<Update>)
+                    .after(ship_alive),
+            )
+            .add_system(force_target.before(linear_move_to))
+            .add_system_set_to_stage(
+                PhysicsStages::StepSimulation,
+                SystemSet::default()
+                    .with_system(force_contact_depenetration.before(contact_depenetration))
+                    .with_system(force_ship_contact_depenetration.before(contact_depenetration))
+                    .with_system(force_shapecast_depenetration.before(shapecast_depenetration))
+                    .with_system(
+                        force_ship_shapecast_depenetration.before(shapecast_depenetration),
+                    ),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                force_ship_death.after(timeline_damage),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, force_ship_respawn.after(lives_death))
+            .add_system_to_stage(CoreStage::PostUpdate, force_body_collision)
+            .add_system_to_stage(CoreStage::PostUpdate, force_sensor_collision)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                force_state_event
+                    .after(force_ship_death)
+                    .after(force_ship_respawn)
+                    .after(force_body_collision),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                force_input_event.after(force_state_event),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                force_vulcan_event.after(force_state_event),
+            );
     }
 }
 
@@ -103,7 +104,9 @@ pub struct ForceBundle {
     pub hierarchy: HierarchyBundle,
     #[bundle]
     pub body: KinematicBodyBundle,
+    pub collision_groups: CollisionGroupNames,
     pub collider: Collider,
+    pub linear_move: LinearMove,
     pub linear_move_to: LinearMoveTo,
     pub vulcan: Vulcan,
 }
@@ -121,13 +124,14 @@ impl Default for ForceBundle {
             },
             body: KinematicBodyBundle {
                 active_collision_types: ActiveCollisionTypes::KINEMATIC_STATIC,
-                collision_groups: CollisionGroupFlags {
-                    memberships: CollisionGroup::FORCE_BODY,
-                    filters: CollisionGroup::STATIC | CollisionGroup::SHIP_BODY,
-                },
                 ..default()
             },
+            collision_groups: CollisionGroupNames::new(["FORCE_BODY"]),
             collider: Collider::ball(0.75),
+            linear_move: LinearMove {
+                active: false,
+                ..default()
+            },
             linear_move_to: default(),
             vulcan: Vulcan {
                 timeline: default_entity(),
@@ -149,6 +153,7 @@ pub struct ForceHitboxBundle {
     pub hierarchy_bundle: HierarchyBundle,
     #[bundle]
     pub hitbox_bundle: HitboxBundle,
+    pub collision_groups: CollisionGroupNames,
     #[bundle]
     pub collider_bundle: ArchiveBundle<ComponentBundle<Collider>>,
 }
@@ -158,16 +163,8 @@ impl Default for ForceHitboxBundle {
         Self {
             name: Name::new("Force Hitbox"),
             hierarchy_bundle: default(),
-            hitbox_bundle: HitboxBundle {
-                sensor_bundle: SensorBundle {
-                    collision_groups: CollisionGroupFlags {
-                        memberships: CollisionGroup::FORCE_HITBOX,
-                        filters: CollisionGroup::ENEMY_HURTBOX,
-                    },
-                    ..default()
-                },
-                ..default()
-            },
+            hitbox_bundle: default(),
+            collision_groups: CollisionGroupNames::new(["FORCE_HITBOX"]),
             collider_bundle: ArchiveBundle {
                 bundle: ComponentBundle {
                     component: Collider::ball(0.75),
@@ -185,6 +182,7 @@ pub struct ForceSensorBundle {
     pub hierarchy_bundle: HierarchyBundle,
     #[bundle]
     pub sensor_bundle: SensorBundle,
+    pub collision_groups: CollisionGroupNames,
     #[bundle]
     pub collider_bundle: ArchiveBundle<ComponentBundle<Collider>>,
 }
@@ -194,13 +192,8 @@ impl Default for ForceSensorBundle {
         Self {
             name: Name::new("Force Sensor"),
             hierarchy_bundle: default(),
-            sensor_bundle: SensorBundle {
-                collision_groups: CollisionGroupFlags {
-                    memberships: CollisionGroup::FORCE_SENSOR,
-                    filters: CollisionGroup::SHIP_SENSOR,
-                },
-                ..default()
-            },
+            sensor_bundle: default(),
+            collision_groups: CollisionGroupNames::new(["FORCE_SENSOR"]),
             collider_bundle: ArchiveBundle {
                 bundle: ComponentBundle {
                     component: Collider::ball(0.75),
@@ -267,6 +260,7 @@ impl Default for AssembleForce<fn(&mut World, Entity, Entity)> {
                             path: SCENE_PLAYER_BULLET.into(),
                             ..default()
                         },
+                        playfield_layer: PlayfieldLayer::PlayerBullets,
                         ..default()
                     }
                     .write(world)
@@ -295,19 +289,7 @@ where
         // Rotation animation
         let rotation_animation = world
             .build_animation()
-            .from_discrete_stops([
-                DiscreteStop {
-                    t: 0.0,
-                    value: Quat::default(),
-                    ..default()
-                },
-                DiscreteStop {
-                    t: 2.0,
-                    value: Quat::from_scaled_axis(Vec3::Z * -std::f32::consts::PI),
-                    ..default()
-                },
-            ])
-            .interpolate()
+            .from_angular_velocity(AngularVelocity::new(Vec3::Z, -FRAC_PI_2 as f64))
             .animating_component_field::<Transform>(
                 force_visuals,
                 |transform| &transform.rotation,
@@ -325,6 +307,7 @@ where
             .entity_mut(force_visuals)
             .insert_bundle(self.force_visuals_bundle)
             .insert(Name::new("Force Visuals"))
+            .insert(MeshRotationPivot)
             .push_children(&[force_mesh, rotation_animation]);
 
         // Playfield rotation
@@ -407,9 +390,19 @@ where
         force_bundle.force.refire_stops = refire_stops;
         force_bundle.vulcan.timeline = self.timeline;
 
+        // Force is controlled by the same player as its ship
+        let player = *world
+            .get::<PlayerId>(self.ship)
+            .expect("Force ship has no PlayerId");
+
         world
             .entity_mut(self.force)
             .insert_bundle(force_bundle)
+            .insert(player)
+            .insert(PlayfieldLayer::Player)
+            .insert(MeshRotation {
+                pivot: force_visuals,
+            })
             .push_children(&[
                 force_visuals_rotation,
                 force_hitbox,
@@ -419,6 +412,12 @@ where
                 refire_animation,
             ]);
 
+        InsertTimelineLinearMove {
+            timeline: self.timeline,
+            entity: self.force,
+        }
+        .write(world);
+
         InsertContactDepenetration { entity: self.force }.write(world);
 
         InsertShapecastDepenetration { entity: self.force }.write(world);
@@ -438,6 +437,17 @@ where
         insert_bullet_pool.entity = self.force;
         insert_bullet_pool.timeline = self.timeline;
         insert_bullet_pool.write(world);
+
+        // Attribute bullets to the owning player
+        let bullets = world
+            .get::<EntityPool>(self.force)
+            .unwrap()
+            .entities()
+            .collect::<Vec<_>>();
+
+        for bullet in bullets {
+            world.entity_mut(bullet).insert(player);
+        }
     }
 }
 
@@ -525,7 +535,7 @@ pub fn force_target(
     let playfield_half_size = playfield.half_size(**aspect, camera_pivot.translation.z);
     let playfield_quarter_size = playfield.quarter_size(**aspect, camera_pivot.translation.z);
 
-    for (transform_force, force, force_state, mut linear_move_to) in query_force.iter_mut().next() {
+    for (transform_force, force, force_state, mut linear_move_to) in query_force.iter_mut() {
         let transform_ship = query_ship.get(force.ship).ok();
 
         // Configure movement
@@ -558,7 +568,9 @@ pub fn force_target(
                     _ => linear_move_to.target.x = playfield_quarter_size.x,
                 }
 
-                if transform_force.translation.x == linear_move_to.target.x {
+                if (transform_force.translation.x - linear_move_to.target.x).abs()
+                    < LINEAR_MOVE_EPSILON
+                {
                     linear_move_to.factor = Vec3::Y;
                     if let Some(transform_ship) = transform_ship {
                         linear_move_to.target.y = transform_ship.translation.y;
@@ -825,42 +837,40 @@ pub fn force_ship_shapecast_depenetration(
 }
 
 pub fn force_input(
-    input_ship: Res<Input<PlayerInput>>,
-    query_force: Query<(Entity, &Force, &ForceState), With<ForceInput>>,
+    inputs: Res<PlayerInputs>,
+    query_force: Query<(Entity, &PlayerId, &Force, &ForceState), With<ForceInput>>,
     query_timeline: Query<&Timeline>,
     mut state_events: EventWriter<ForceStateEvent>,
 ) {
-    for (force_entity, force, force_state) in query_force.iter() {
+    for (force_entity, player, force, force_state) in query_force.iter() {
         let timeline = query_timeline.get(force.timeline).unwrap();
         let t = timeline.t;
 
+        if !inputs.just_pressed(*player, PlayerInput::Force) {
+            continue;
+        }
+
         match *force_state {
             ForceState::Free => {
-                if input_ship.just_pressed(PlayerInput::Force) {
-                    state_events.send(ForceStateEvent {
-                        target: force_entity,
-                        state: ForceState::Recall,
-                        t,
-                    });
-                }
+                state_events.send(ForceStateEvent {
+                    target: force_entity,
+                    state: ForceState::Recall,
+                    t,
+                });
             }
             ForceState::DockedFront => {
-                if input_ship.just_pressed(PlayerInput::Force) {
-                    state_events.send(ForceStateEvent {
-                        target: force_entity,
-                        state: ForceState::LaunchForward,
-                        t,
-                    });
-                }
+                state_events.send(ForceStateEvent {
+                    target: force_entity,
+                    state: ForceState::LaunchForward,
+                    t,
+                });
             }
             ForceState::DockedRear => {
-                if input_ship.just_pressed(PlayerInput::Force) {
-                    state_events.send(ForceStateEvent {
-                        target: force_entity,
-                        state: ForceState::LaunchBackward,
-                        t,
-                    });
-                }
+                state_events.send(ForceStateEvent {
+                    target: force_entity,
+                    state: ForceState::LaunchBackward,
+                    t,
+                });
             }
             _ => (),
         }
@@ -911,33 +921,33 @@ pub fn force_ship_death(
 
 pub fn force_ship_respawn(
     mut respawn_events: EventReader<RespawnEvent>,
-    query_ship: Query<(Entity, &ShipForce)>,
+    query_ship: Query<&ShipForce>,
     mut input_events: EventWriter<ForceInputEvent>,
     mut vulcan_events: EventWriter<ForceVulcanEvent>,
 ) {
-    for (ship_entity, ship_force) in query_ship.iter() {
-        for event in respawn_events.iter() {
-            if event.entity != ship_entity {
-                continue;
-            }
+    for event in respawn_events.iter() {
+        let ship_force = if let Ok(components) = query_ship.get(event.entity) {
+            components
+        } else {
+            continue;
+        };
 
-            let t = event.t;
-
-            // Restore force's input and refire capability
-            input_events.send(ForceInputEvent {
-                target: ship_force.force,
-                enabled: true,
-                t,
-                determinism: event.determinism,
-            });
-
-            vulcan_events.send(ForceVulcanEvent {
-                target: ship_force.force,
-                enabled: true,
-                t,
-                determinism: event.determinism,
-            });
-        }
+        let t = event.t;
+
+        // Restore force's input and refire capability
+        input_events.send(ForceInputEvent {
+            target: ship_force.force,
+            enabled: true,
+            t,
+            determinism: event.determinism,
+        });
+
+        vulcan_events.send(ForceVulcanEvent {
+            target: ship_force.force,
+            enabled: true,
+            t,
+            determinism: event.determinism,
+        });
     }
 }
 
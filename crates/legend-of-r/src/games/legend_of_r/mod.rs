@@ -1,50 +1,45 @@
+pub mod audio;
+pub mod campaign;
 pub mod force;
+pub mod stage;
 
 use bevy_rapier2d::na::{Point3, Unit, Vector3};
 
 use bevy::{
-    ecs::system::Command,
     prelude::{
         default,
         shape::{Capsule, Cube, Icosphere, Plane},
         AmbientLight, AssetServer, Assets, BuildChildren, Color, Commands, DirectionalLightBundle,
         Entity, EulerRot, MaterialMeshBundle, Mesh, Name, Plugin, Quat, Res, ResMut, Transform,
-        Vec3, World,
+        Vec3,
     },
-    render::{mesh::Indices, render_resource::PrimitiveTopology, view::RenderLayers},
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
 };
 use parry3d::shape::{Ball, ConvexPolyhedron, Cuboid, HalfSpace, Segment, SharedShape, Triangle};
 
 use crate::{
-    animation::{
-        adapters::{
-            animation::{Animation, AnimationTrait},
-            discretize::DiscretizeTrait,
-            sequence::SequenceTrait,
-            try_animate_component_fields::TryAnimateComponentFieldsTrait,
-        },
-        animations::discrete::{Discrete, DiscreteStop},
-        Animate, AnimationTime, PreUpdate,
-    },
-    scene::InsertSceneArchive,
     prelude::{
-        interval, AfterTrait, Alive, AnimateComponentsTrait, AnimationTagTrait, AssembleBackground,
-        AssembleCameraRig, AssembleForce, AssemblePlaneCollider, AssemblePlayfield, AssembleShip,
-        AxialFunctionTrait, BeforeTrait, BuildAnimation, BulletAnimation, CurveTrait, DilateTrait,
-        DiscreteStopsTrait, EntityComponentTrait, EvaluateTrait, FlattenTrait, ForcePlugin,
-        HitPoints, InsertEnemy, InsertEntityPool, InsertTimelineAlive, InterpolateTrait,
-        MultiplyTrait, OffsetIterator, OffsetTrait, PartitionIterator, SpawnBoundary, SpawnBullet,
-        TimeSourceTrait, Timeline, TimelineAlive, TimelineDamage, TimelineTime,
-        TryAnimateComponentsTrait, UnpoolEntity, Update, SCENE_ENEMY, SCENE_ENEMY_BULLET,
+        AnimateComponentsTrait, AssembleBackground, AssembleCameraRig, AssembleForce,
+        AssemblePlaneCollider, AssemblePlayfield, AssembleShip, AxialFunctionTrait,
+        BuildAnimation, Campaign, CampaignPlugin, CampaignStages, EntityComponentTrait,
+        EvaluateTrait, ForcePlugin, InsertStage, InsertTimelineAudio, LegendOfRAudioPlugin,
+        MultiplyTrait, PlayerInputMaps, PlayfieldLayer, ShipBundle, SimulationTimeline,
+        SpawnBoundary, StagePlugin, TimeAbilities, TimeControlled, TimeSourceTrait, Timeline,
+        TimelineMusic, TimelineTime, MUSIC_STAGE_1,
     },
     util::GameMaterial,
 };
 
+pub const STAGE_1: &str = "stages/stage_1.stage.ron";
+
 pub struct LegendOfRPlugin;
 
 impl Plugin for LegendOfRPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_plugin(ForcePlugin);
+        app.add_plugin(ForcePlugin)
+            .add_plugin(StagePlugin)
+            .add_plugin(LegendOfRAudioPlugin)
+            .add_plugin(CampaignPlugin::default());
 
         app.add_startup_system(setup);
     }
@@ -52,6 +47,8 @@ impl Plugin for LegendOfRPlugin {
 
 pub fn setup<'w, 's>(
     asset_server: Res<AssetServer>,
+    player_input_maps: Res<PlayerInputMaps>,
+    mut campaign: ResMut<Campaign>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<GameMaterial>>,
     mut commands: Commands<'w, 's>,
@@ -105,7 +102,7 @@ pub fn setup<'w, 's>(
                     material: background_material.clone(),
                     ..default()
                 })
-                .insert(RenderLayers::layer(1))
+                .insert(PlayfieldLayer::Background)
                 .id()
         })
         .collect::<Vec<_>>();
@@ -119,11 +116,35 @@ pub fn setup<'w, 's>(
         .spawn()
         .insert(Name::new("Timeline"))
         .insert(Timeline::default())
+        .insert(TimeControlled::default())
+        .insert(SimulationTimeline)
         .id();
 
+    // Ships and forces keep playing while time abilities act on everything else
+    let player_timeline = commands
+        .spawn()
+        .insert(Name::new("Player Timeline"))
+        .insert(Timeline::default())
+        .id();
+
+    // Audio, with ship cues such as deaths and respawns scheduled on the player timeline
+    for timeline in [timeline_entity, player_timeline] {
+        let timeline_audio = commands.spawn().id();
+        commands.add(InsertTimelineAudio {
+            timeline,
+            entity: timeline_audio,
+        });
+    }
+
+    commands
+        .spawn()
+        .insert(Name::new("Music"))
+        .insert(TimelineMusic::new(
+            timeline_entity,
+            asset_server.load(MUSIC_STAGE_1),
+        ));
+
     let playfield = commands.spawn().id();
-    let ship = commands.spawn().id();
-    let force = commands.spawn().id();
 
     commands.add(AssemblePlayfield {
         entity: playfield,
@@ -131,262 +152,52 @@ pub fn setup<'w, 's>(
         ..default()
     });
     commands.add(SpawnBoundary { playfield });
-    commands.add(AssembleShip {
-        playfield,
-        timeline: timeline_entity,
-        entity: ship,
-        ..default()
-    });
-    commands.add(AssembleForce {
-        playfield,
-        timeline: timeline_entity,
-        ship,
-        force,
-        ..default()
-    });
-
-    // Timeline
-    let timeline = TimelineTime::new(timeline_entity);
 
-    let bullet_pool = commands.spawn().id();
-    commands.entity(playfield).add_child(bullet_pool);
+    // One ship and force per local player
+    let mut forces = vec![];
+    for player in player_input_maps.players() {
+        let ship = commands.spawn().id();
+        let force = commands.spawn().id();
 
-    let instance_constructor = move |world: &mut World, entity: Entity, timeline: Entity| {
-        SpawnBullet {
-            timeline,
-            entity,
-            scene: InsertSceneArchive {
-                path: SCENE_ENEMY_BULLET.into(),
+        commands.add(AssembleShip {
+            playfield,
+            timeline: player_timeline,
+            entity: ship,
+            ship_bundle: ShipBundle::for_player(player),
+            time_abilities: Some(TimeAbilities {
+                target: timeline_entity,
                 ..default()
-            },
+            }),
             ..default()
-        }
-        .write(world)
-    };
+        });
+        commands.add(AssembleForce {
+            playfield,
+            timeline: player_timeline,
+            ship,
+            force,
+            ..default()
+        });
+
+        forces.push(force);
+    }
 
-    commands.add(InsertEntityPool {
-        entity: bullet_pool,
+    // Stage
+    let stage = commands.spawn().id();
+    commands.add(InsertStage {
+        playfield,
         timeline: timeline_entity,
+        entity: stage,
+        stage: asset_server.load(STAGE_1),
+        targets: forces.clone(),
         ..default()
     });
 
-    // Spawn enemies
-    let iter = interval(0.5).offset(1.0);
-
-    const WAVE_1_COUNT: usize = 4;
-    const WAVE_1_SALVO: usize = 10;
-
-    let (wave_1, _iter) = iter.partition_iter(WAVE_1_COUNT);
-    wave_1.enumerate().for_each(move |(i, t)| {
-        let enemy = commands.spawn().id();
-
-        let animation_transform = commands
-            .build_animation()
-            .from_discrete_stops([
-                DiscreteStop {
-                    t: 0.0,
-                    value: Transform::from_xyz(17.0, 5.0 - i as f32 * 2.0, 0.0)
-                        .with_rotation(Quat::from_scaled_axis(Vec3::Z * std::f32::consts::PI)),
-                    ..default()
-                },
-                DiscreteStop {
-                    t: 1.0,
-                    value: Transform::from_xyz(-17.0, 5.0 - i as f32 * 2.0, 0.0)
-                        .with_rotation(Quat::from_scaled_axis(Vec3::Z * std::f32::consts::PI)),
-                    ..default()
-                },
-            ])
-            .interpolate()
-            .with_curve(4.0)
-            .with_dilation(0.5)
-            .with_offset(-t)
-            .after(t)
-            .before(t + 2.0)
-            .flatten()
-            .as_animation();
-
-        let enemy_animation = animation_transform.id();
-
-        let animation_transform = animation_transform
-            .with_time_source(timeline)
-            .try_animating_component(enemy)
-            .evaluate()
-            .tagged::<Update>()
-            .insert(Name::new(format!("Enemy {enemy:?} Transform")))
-            .id();
-
-        commands.entity(enemy).add_child(animation_transform);
-
-        commands.add(InsertEnemy {
-            playfield,
-            timeline: timeline_entity,
-            entity: enemy,
-            scene: InsertSceneArchive {
-                path: SCENE_ENEMY.into(),
-                ..default()
-            },
-            timeline_alive: InsertTimelineAlive {
-                alive_at: Some(t),
-                dead_at: Some(t + 2.0),
-                ..default()
-            },
-            ..default()
-        });
-
-        for t in interval(0.1).offset(t).offset(1.0).take(WAVE_1_SALVO) {
-            commands.add(UnpoolEntity {
-                source: enemy,
-                entity_pool: bullet_pool,
-                t,
-                unpool: move |world: &mut World, entity: Entity, transform: Transform, t: f64| {
-                    let bullet = world.get::<BulletAnimation>(entity).unwrap();
-
-                    let from = t;
-                    let to = t + 1.0;
-                    let speed = 0.6;
-
-                    // Transform animation
-                    let from_stops_entity = bullet.from_stops;
-                    let to_stops_entity = bullet.to_stops;
-
-                    let value = Animation::<Option<Transform>>::animate(
-                        world,
-                        enemy_animation,
-                        AnimationTime { t, ..default() },
-                    )
-                    .unwrap()
-                        * transform;
-
-                    let mut from_stops = world
-                        .get_mut::<Discrete<DiscreteStop<Transform>>>(from_stops_entity)
-                        .unwrap();
-
-                    from_stops.insert_stop(DiscreteStop {
-                        t: from,
-                        value: DiscreteStop {
-                            t: from,
-                            value,
-                            ..default()
-                        },
-                        ..default()
-                    });
-
-                    let mut to_stops = world
-                        .get_mut::<Discrete<DiscreteStop<Transform>>>(to_stops_entity)
-                        .unwrap();
-
-                    to_stops.insert_stop(DiscreteStop {
-                        t,
-                        value: DiscreteStop {
-                            t: t + speed,
-                            value: default(),
-                            ..default()
-                        },
-                        ..default()
-                    });
-
-                    // Lerp target animation
-                    world
-                        .build_animation()
-                        .from_component(Transform::default())
-                        .sequenced_with(|animation| animation.from_entity_component(force), 0.01)
-                        .discretized_to([0.0, from])
-                        .try_animating_component_field::<Discrete<DiscreteStop<Transform>>, _, _>(
-                            to_stops_entity,
-                            move |stops| &stops.stop_at(to).unwrap().value.value,
-                            move |stops| &mut stops.stop_at_mut(to).unwrap().value.value,
-                        )
-                        .with_time_source(timeline)
-                        .evaluate()
-                        .tagged::<PreUpdate>()
-                        .insert(Name::new("Bullet Lerp Target"));
-
-                    // Hit points animation
-                    let timeline_hit_points = world.get::<TimelineDamage>(entity).unwrap();
-                    let hit_points_stops_entity = timeline_hit_points.hit_points_stops;
-
-                    let mut hit_points_stops = world
-                        .get_mut::<Discrete<HitPoints>>(hit_points_stops_entity)
-                        .unwrap();
-
-                    hit_points_stops.insert_stop(DiscreteStop {
-                        t: from,
-                        value: HitPoints(1),
-                        ..default()
-                    });
-
-                    // Alive animation
-                    let timeline_alive = world.get::<TimelineAlive>(entity).unwrap();
-                    let alive_stops_entity = timeline_alive.alive_stops;
-
-                    let mut alive_stops = world
-                        .get_mut::<Discrete<Alive>>(alive_stops_entity)
-                        .unwrap();
-
-                    alive_stops.insert_stop(DiscreteStop {
-                        t: from,
-                        value: Alive(true),
-                        ..default()
-                    });
-
-                    alive_stops.insert_stop(DiscreteStop {
-                        t: to,
-                        value: Alive(false),
-                        ..default()
-                    });
-
-                    // Alive disabled animations
-                    let alive_from_disabled =
-                        world.build_animation().from_discrete_stops([DiscreteStop {
-                            t: 0.0,
-                            value: false,
-                            ..default()
-                        }]);
-
-                    let alive_from_enabled_entity = alive_from_disabled.id();
-
-                    alive_from_disabled
-                        .try_animating_component_field::<Discrete<Alive>, _, _>(
-                            alive_stops_entity,
-                            move |stops| &stops.stop_at(from).unwrap().disabled,
-                            move |stops| &mut stops.stop_at_mut(from).unwrap().disabled,
-                        )
-                        .with_time_source(timeline)
-                        .evaluate()
-                        .tagged::<Update>()
-                        .insert(Name::new("Bullet Alive From Enabled"));
-
-                    let alive_to_disabled =
-                        world.build_animation().from_discrete_stops([DiscreteStop {
-                            t: 0.0,
-                            value: false,
-                            ..default()
-                        }]);
-
-                    let alive_to_enabled_entity = alive_to_disabled.id();
-
-                    alive_to_disabled
-                        .try_animating_component_field::<Discrete<Alive>, _, _>(
-                            alive_stops_entity,
-                            move |stops| &stops.stop_at(to).unwrap().disabled,
-                            move |stops| &mut stops.stop_at_mut(to).unwrap().disabled,
-                        )
-                        .with_time_source(timeline)
-                        .evaluate()
-                        .tagged::<Update>()
-                        .insert(Name::new("Bullet Alive To Enabled"));
-
-                    world
-                        .entity_mut(enemy)
-                        .get_mut::<TimelineAlive>()
-                        .unwrap()
-                        .descendants_enabled_stops
-                        .push((from, alive_from_enabled_entity, alive_to_enabled_entity))
-                },
-                instance_constructor: Some((timeline_entity, instance_constructor.clone())),
-                emitters: vec![Transform::default()],
-            });
-        }
+    // Later stages are spawned by the campaign as earlier ones are cleared
+    campaign.stages = Some(CampaignStages {
+        playfield,
+        timeline: timeline_entity,
+        targets: forces,
+        first: stage,
     });
 
     /*
@@ -476,7 +287,7 @@ pub fn setup_animated_primitives<'w, 's>(
             material: materials.add(Color::GREEN.into()),
             ..default()
         })
-        .insert(RenderLayers::layer(1))
+        .insert(PlayfieldLayer::Background)
         .id();
     let segment_2d = commands.spawn().id();
 
@@ -523,7 +334,7 @@ pub fn setup_animated_primitives<'w, 's>(
             material: materials.add(Color::GREEN.into()),
             ..default()
         })
-        .insert(RenderLayers::layer(1))
+        .insert(PlayfieldLayer::Background)
         .id();
     let triangle_2d = commands.spawn().id();
 
@@ -563,7 +374,7 @@ pub fn setup_animated_primitives<'w, 's>(
             material: materials.add(Color::GREEN.into()),
             ..default()
         })
-        .insert(RenderLayers::layer(1))
+        .insert(PlayfieldLayer::Background)
         .id();
 
     let halfspace_2d = commands.spawn().id();
@@ -608,7 +419,7 @@ pub fn setup_animated_primitives<'w, 's>(
             material: materials.add(Color::GREEN.into()),
             ..default()
         })
-        .insert(RenderLayers::layer(1))
+        .insert(PlayfieldLayer::Background)
         .id();
 
     let sphere_2d = commands.spawn().id();
@@ -639,7 +450,7 @@ pub fn setup_animated_primitives<'w, 's>(
             material: materials.add(Color::GREEN.into()),
             ..default()
         })
-        .insert(RenderLayers::layer(1))
+        .insert(PlayfieldLayer::Background)
         .id();
 
     let box_2d = commands.spawn().id();
@@ -688,7 +499,7 @@ pub fn setup_animated_primitives<'w, 's>(
             material: materials.add(Color::GREEN.into()),
             ..default()
         })
-        .insert(RenderLayers::layer(1))
+        .insert(PlayfieldLayer::Background)
         .id();
 
     let convex_2d = commands.spawn().id();
@@ -0,0 +1,660 @@
+//! Data-driven stages
+//!
+//! Enemy types and the waves that spawn them are described in a `.stage.ron` asset.
+//! Edits to a loaded stage despawn and rebuild its waves in place,
+//! leaving the timeline untouched so play resumes from the current `t`.
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    ecs::system::Command,
+    prelude::{
+        default, info, warn, AddAsset, Added, AssetEvent, Assets, BuildWorldChildren, Commands,
+        Component, DespawnRecursiveExt, Entity, EventReader, Handle, Name, Plugin, Quat, Query,
+        Res, Transform, Vec3, World,
+    },
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::{
+        adapters::animation::{Animation, AnimationTrait},
+        animations::discrete::{Discrete, DiscreteStop},
+        Animate, AnimationTime, PreUpdate,
+    },
+    prelude::{
+        construct_enemy_bullet, default_entity, AfterTrait, Alive, AnimationTagTrait,
+        BeforeTrait, BossDesc, BuildAnimation, BulletAnimation, CurveTrait, DilateTrait,
+        DiscreteStopsTrait, DiscretizeTrait, EntityComponentTrait, EvaluateTrait, FlattenTrait,
+        HierarchyBundle, HitPoints, InsertBoss, InsertEnemy, InsertEntityPool, InsertMeshRotation,
+        InsertTimelineAlive, InsertTimelineDamage, InterpolateTrait, MeshRotationDesc, OffsetTrait,
+        SequenceTrait, TimeSourceTrait, TimelineAlive, TimelineDamage, TimelineTime,
+        TryAnimateComponentFieldsTrait, TryAnimateComponentsTrait, UnpoolEntity, Update,
+        SCENE_ENEMY,
+    },
+    scene::InsertSceneArchive,
+};
+
+pub struct StagePlugin;
+
+impl Plugin for StagePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_asset::<Stage>()
+            .init_asset_loader::<StageLoader>()
+            .add_system(stage_rebuild);
+    }
+}
+
+/// A set of enemy types and the waves that spawn them
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TypeUuid)]
+#[uuid = "8f3b9d62-2c1e-4a55-9a7e-51c0f0d8e6a4"]
+pub struct Stage {
+    pub enemies: Vec<StageEnemy>,
+    pub waves: Vec<StageWave>,
+    #[serde(default)]
+    pub bosses: Vec<StageBoss>,
+}
+
+impl Stage {
+    pub fn enemy(&self, name: &str) -> Option<&StageEnemy> {
+        self.enemies.iter().find(|enemy| enemy.name == name)
+    }
+}
+
+/// Enemy type, referenced by name from [`StageWave`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageEnemy {
+    pub name: String,
+    #[serde(default = "StageEnemy::default_scene")]
+    pub scene: String,
+    #[serde(default = "StageEnemy::default_hit_points")]
+    pub hit_points: usize,
+    pub path: StagePath,
+    #[serde(default)]
+    pub bullets: Option<BulletPattern>,
+    /// Spin or banking applied to the mesh independently of the path's rotation
+    #[serde(default)]
+    pub mesh_rotation: Option<MeshRotationDesc>,
+}
+
+impl StageEnemy {
+    fn default_scene() -> String {
+        SCENE_ENEMY.into()
+    }
+
+    fn default_hit_points() -> usize {
+        1
+    }
+}
+
+/// Spline followed by an enemy over its lifetime, relative to its wave origin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StagePath {
+    /// Control points, spread evenly over `duration`
+    pub points: Vec<(f32, f32)>,
+    pub duration: f64,
+    /// Easing exponent applied over the whole path
+    #[serde(default = "StagePath::default_curve")]
+    pub curve: f64,
+    /// Rotation about the Z axis, in radians
+    #[serde(default)]
+    pub rotation: f32,
+}
+
+impl StagePath {
+    fn default_curve() -> f64 {
+        1.0
+    }
+
+    fn stops(&self, origin: Vec3) -> Vec<DiscreteStop<Transform>> {
+        let rotation = Quat::from_rotation_z(self.rotation);
+        let divisor = self.points.len().saturating_sub(1).max(1) as f64;
+
+        self.points
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y))| DiscreteStop {
+                t: i as f64 / divisor,
+                value: Transform::from_translation(origin + Vec3::new(*x, *y, 0.0))
+                    .with_rotation(rotation),
+                ..default()
+            })
+            .collect()
+    }
+}
+
+/// Salvo of aimed bullets fired over an enemy's lifetime
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulletPattern {
+    /// Time after spawning before the first shot
+    pub delay: f64,
+    pub interval: f64,
+    pub count: usize,
+    /// Time taken to reach the target
+    pub speed: f64,
+    pub lifetime: f64,
+}
+
+impl Default for BulletPattern {
+    fn default() -> Self {
+        Self {
+            delay: 1.1,
+            interval: 0.1,
+            count: 10,
+            speed: 0.6,
+            lifetime: 1.0,
+        }
+    }
+}
+
+/// A group of enemies of the same type, spawned at regular intervals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageWave {
+    pub enemy: String,
+    pub spawn_at: f64,
+    #[serde(default = "StageWave::default_count")]
+    pub count: usize,
+    /// Time between consecutive spawns
+    #[serde(default)]
+    pub spacing: f64,
+    #[serde(default)]
+    pub origin: (f32, f32),
+    /// Offset applied to the origin of each consecutive spawn
+    #[serde(default)]
+    pub step: (f32, f32),
+}
+
+impl StageWave {
+    fn default_count() -> usize {
+        1
+    }
+}
+
+/// A boss appearing at a fixed point in the stage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageBoss {
+    pub spawn_at: f64,
+    #[serde(default)]
+    pub origin: (f32, f32),
+    pub boss: BossDesc,
+}
+
+#[derive(Default)]
+pub struct StageLoader;
+
+impl AssetLoader for StageLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let stage: Stage = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(stage));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["stage.ron"]
+    }
+}
+
+/// Root of a spawned stage, rebuilt whenever its asset changes
+#[derive(Debug, Clone, Component)]
+pub struct StageInstance {
+    pub stage: Handle<Stage>,
+    pub timeline: Entity,
+    /// Entities that enemy bullets home in on, assigned round-robin
+    pub targets: Vec<Entity>,
+    /// Timeline time that the stage's spawn times are relative to
+    pub offset: f64,
+}
+
+pub struct InsertStage {
+    pub playfield: Entity,
+    pub timeline: Entity,
+    pub entity: Entity,
+    pub stage: Handle<Stage>,
+    pub targets: Vec<Entity>,
+    pub offset: f64,
+}
+
+impl Default for InsertStage {
+    fn default() -> Self {
+        Self {
+            playfield: default_entity(),
+            timeline: default_entity(),
+            entity: default_entity(),
+            stage: default(),
+            targets: default(),
+            offset: default(),
+        }
+    }
+}
+
+impl Command for InsertStage {
+    fn write(self, world: &mut World) {
+        world
+            .entity_mut(self.entity)
+            .insert(Name::new("Stage"))
+            .insert_bundle(HierarchyBundle::default())
+            .insert(StageInstance {
+                stage: self.stage,
+                timeline: self.timeline,
+                targets: self.targets,
+                offset: self.offset,
+            });
+
+        world
+            .entity_mut(self.playfield)
+            .push_children(&[self.entity]);
+    }
+}
+
+/// Spawn the waves of a stage as children of its [`StageInstance`]
+pub struct BuildStage {
+    pub entity: Entity,
+    pub stage: Stage,
+}
+
+impl Command for BuildStage {
+    fn write(self, world: &mut World) {
+        let instance = world.get::<StageInstance>(self.entity).unwrap().clone();
+        let timeline_entity = instance.timeline;
+        let timeline = TimelineTime::new(timeline_entity);
+
+        let bullet_pool = world.spawn().insert(Name::new("Bullet Pool")).id();
+        world.entity_mut(self.entity).push_children(&[bullet_pool]);
+
+        InsertEntityPool {
+            entity: bullet_pool,
+            timeline: timeline_entity,
+            ..default()
+        }
+        .write(world);
+
+        let mut targets = instance.targets.iter().copied().cycle();
+
+        for wave in self.stage.waves.iter() {
+            let enemy_desc = if let Some(enemy_desc) = self.stage.enemy(&wave.enemy) {
+                enemy_desc
+            } else {
+                warn!("Stage wave references unknown enemy {}", wave.enemy);
+                continue;
+            };
+
+            for i in 0..wave.count {
+                let t = instance.offset + wave.spawn_at + wave.spacing * i as f64;
+                let dead_at = t + enemy_desc.path.duration;
+
+                let origin = Vec3::new(
+                    wave.origin.0 + wave.step.0 * i as f32,
+                    wave.origin.1 + wave.step.1 * i as f32,
+                    0.0,
+                );
+
+                let enemy = world.spawn().id();
+
+                let animation_transform = world
+                    .build_animation()
+                    .from_discrete_stops(enemy_desc.path.stops(origin))
+                    .interpolate()
+                    .with_curve(enemy_desc.path.curve)
+                    .with_dilation(1.0 / enemy_desc.path.duration)
+                    .with_offset(-t)
+                    .after(t)
+                    .before(dead_at)
+                    .flatten()
+                    .as_animation();
+
+                let enemy_animation = animation_transform.id();
+
+                let animation_transform = animation_transform
+                    .with_time_source(timeline)
+                    .try_animating_component(enemy)
+                    .evaluate()
+                    .tagged::<Update>()
+                    .insert(Name::new(format!("Enemy {enemy:?} Transform")))
+                    .id();
+
+                world.entity_mut(enemy).push_children(&[animation_transform]);
+
+                InsertEnemy {
+                    playfield: self.entity,
+                    timeline: timeline_entity,
+                    entity: enemy,
+                    scene: InsertSceneArchive {
+                        path: enemy_desc.scene.clone(),
+                        ..default()
+                    },
+                    timeline_damage: InsertTimelineDamage {
+                        hit_points: enemy_desc.hit_points,
+                        ..default()
+                    },
+                    timeline_alive: InsertTimelineAlive {
+                        alive_at: Some(t),
+                        dead_at: Some(dead_at),
+                        ..default()
+                    },
+                    ..default()
+                }
+                .write(world);
+
+                world
+                    .entity_mut(enemy)
+                    .insert(Name::new(format!("{} {i}", enemy_desc.name)));
+
+                if let Some(mesh_rotation) = &enemy_desc.mesh_rotation {
+                    InsertMeshRotation {
+                        entity: enemy,
+                        timeline: timeline_entity,
+                        t,
+                        desc: mesh_rotation.clone(),
+                    }
+                    .write(world);
+                }
+
+                let pattern = if let Some(pattern) = enemy_desc.bullets {
+                    pattern
+                } else {
+                    continue;
+                };
+
+                let target = if let Some(target) = targets.next() {
+                    target
+                } else {
+                    continue;
+                };
+
+                for shot in 0..pattern.count {
+                    let t = t + pattern.delay + pattern.interval * shot as f64;
+
+                    UnpoolEntity {
+                        source: enemy,
+                        entity_pool: bullet_pool,
+                        t,
+                        unpool: move |world: &mut World,
+                                      entity: Entity,
+                                      transform: Transform,
+                                      t: f64| {
+                            unpool_enemy_bullet(
+                                world,
+                                entity,
+                                transform,
+                                t,
+                                EnemyBullet {
+                                    enemy,
+                                    enemy_animation,
+                                    target,
+                                    timeline,
+                                    pattern,
+                                },
+                            )
+                        },
+                        instance_constructor: Some((
+                            timeline_entity,
+                            construct_enemy_bullet as fn(&mut World, Entity, Entity),
+                        )),
+                        emitters: vec![Transform::default()],
+                    }
+                    .write(world);
+                }
+            }
+        }
+
+        for stage_boss in self.stage.bosses.iter() {
+            let boss = world.spawn().id();
+
+            InsertBoss {
+                playfield: self.entity,
+                timeline: timeline_entity,
+                entity: boss,
+                bullet_pool,
+                spawn_at: instance.offset + stage_boss.spawn_at,
+                transform: Transform::from_xyz(stage_boss.origin.0, stage_boss.origin.1, 0.0),
+                desc: stage_boss.boss.clone(),
+            }
+            .write(world);
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct EnemyBullet {
+    enemy: Entity,
+    enemy_animation: Entity,
+    target: Entity,
+    timeline: TimelineTime,
+    pattern: BulletPattern,
+}
+
+/// Schedule a pooled bullet to fire from an enemy at `t` and home in on its target
+fn unpool_enemy_bullet(
+    world: &mut World,
+    entity: Entity,
+    transform: Transform,
+    t: f64,
+    bullet: EnemyBullet,
+) {
+    let EnemyBullet {
+        enemy,
+        enemy_animation,
+        target,
+        timeline,
+        pattern,
+    } = bullet;
+
+    let bullet = world.get::<BulletAnimation>(entity).unwrap();
+
+    let from = t;
+    let to = t + pattern.lifetime;
+
+    // Transform animation
+    let from_stops_entity = bullet.from_stops;
+    let to_stops_entity = bullet.to_stops;
+
+    let value = Animation::<Option<Transform>>::animate(
+        world,
+        enemy_animation,
+        AnimationTime { t, ..default() },
+    )
+    .unwrap()
+        * transform;
+
+    let mut from_stops = world
+        .get_mut::<Discrete<DiscreteStop<Transform>>>(from_stops_entity)
+        .unwrap();
+
+    from_stops.insert_stop(DiscreteStop {
+        t: from,
+        value: DiscreteStop {
+            t: from,
+            value,
+            ..default()
+        },
+        ..default()
+    });
+
+    let mut to_stops = world
+        .get_mut::<Discrete<DiscreteStop<Transform>>>(to_stops_entity)
+        .unwrap();
+
+    to_stops.insert_stop(DiscreteStop {
+        t,
+        value: DiscreteStop {
+            t: t + pattern.speed,
+            value: default(),
+            ..default()
+        },
+        ..default()
+    });
+
+    // Lerp target animation
+    let lerp_target = world
+        .build_animation()
+        .from_component(Transform::default())
+        .sequenced_with(|animation| animation.from_entity_component(target), 0.01)
+        .discretized_to([0.0, from])
+        .try_animating_component_field::<Discrete<DiscreteStop<Transform>>, _, _>(
+            to_stops_entity,
+            move |stops| &stops.stop_at(to).unwrap().value.value,
+            move |stops| &mut stops.stop_at_mut(to).unwrap().value.value,
+        )
+        .with_time_source(timeline)
+        .evaluate()
+        .tagged::<PreUpdate>()
+        .insert(Name::new("Bullet Lerp Target"))
+        .id();
+
+    // Parented to the bullet so it's cleaned up alongside its stage
+    world.entity_mut(entity).push_children(&[lerp_target]);
+
+    // Hit points animation
+    let timeline_hit_points = world.get::<TimelineDamage>(entity).unwrap();
+    let hit_points_stops_entity = timeline_hit_points.hit_points_stops;
+
+    let mut hit_points_stops = world
+        .get_mut::<Discrete<HitPoints>>(hit_points_stops_entity)
+        .unwrap();
+
+    hit_points_stops.insert_stop(DiscreteStop {
+        t: from,
+        value: HitPoints(1),
+        ..default()
+    });
+
+    // Alive animation
+    let timeline_alive = world.get::<TimelineAlive>(entity).unwrap();
+    let alive_stops_entity = timeline_alive.alive_stops;
+
+    let mut alive_stops = world.get_mut::<Discrete<Alive>>(alive_stops_entity).unwrap();
+
+    alive_stops.insert_stop(DiscreteStop {
+        t: from,
+        value: Alive(true),
+        ..default()
+    });
+
+    alive_stops.insert_stop(DiscreteStop {
+        t: to,
+        value: Alive(false),
+        ..default()
+    });
+
+    // Alive disabled animations
+    let alive_from_disabled = world.build_animation().from_discrete_stops([DiscreteStop {
+        t: 0.0,
+        value: false,
+        ..default()
+    }]);
+
+    let alive_from_enabled_entity = alive_from_disabled.id();
+
+    let alive_from_disabled = alive_from_disabled
+        .try_animating_component_field::<Discrete<Alive>, _, _>(
+            alive_stops_entity,
+            move |stops| &stops.stop_at(from).unwrap().disabled,
+            move |stops| &mut stops.stop_at_mut(from).unwrap().disabled,
+        )
+        .with_time_source(timeline)
+        .evaluate()
+        .tagged::<Update>()
+        .insert(Name::new("Bullet Alive From Enabled"))
+        .id();
+
+    let alive_to_disabled = world.build_animation().from_discrete_stops([DiscreteStop {
+        t: 0.0,
+        value: false,
+        ..default()
+    }]);
+
+    let alive_to_enabled_entity = alive_to_disabled.id();
+
+    let alive_to_disabled = alive_to_disabled
+        .try_animating_component_field::<Discrete<Alive>, _, _>(
+            alive_stops_entity,
+            move |stops| &stops.stop_at(to).unwrap().disabled,
+            move |stops| &mut stops.stop_at_mut(to).unwrap().disabled,
+        )
+        .with_time_source(timeline)
+        .evaluate()
+        .tagged::<Update>()
+        .insert(Name::new("Bullet Alive To Enabled"))
+        .id();
+
+    // Parented to the bullet so stage rebuilds despawn them along with it
+    world
+        .entity_mut(entity)
+        .push_children(&[alive_from_disabled, alive_to_disabled]);
+
+    world
+        .entity_mut(enemy)
+        .get_mut::<TimelineAlive>()
+        .unwrap()
+        .descendants_enabled_stops
+        .push((from, alive_from_enabled_entity, alive_to_enabled_entity))
+}
+
+/// Build stage instances once their asset is loaded, and rebuild them when it's modified
+pub fn stage_rebuild(
+    mut stage_events: EventReader<AssetEvent<Stage>>,
+    stages: Res<Assets<Stage>>,
+    query: Query<(Entity, &StageInstance)>,
+    query_added: Query<Entity, Added<StageInstance>>,
+    mut commands: Commands,
+) {
+    let mut built = vec![];
+
+    // Instances added after their asset has loaded won't receive an asset event
+    for entity in query_added.iter() {
+        let (_, instance) = query.get(entity).unwrap();
+
+        let stage = if let Some(stage) = stages.get(&instance.stage) {
+            stage
+        } else {
+            continue;
+        };
+
+        info!("Building stage {:?}", entity);
+
+        commands.add(BuildStage {
+            entity,
+            stage: stage.clone(),
+        });
+        built.push(entity);
+    }
+
+    for event in stage_events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+
+        let stage = if let Some(stage) = stages.get(handle) {
+            stage
+        } else {
+            continue;
+        };
+
+        for (entity, instance) in query.iter() {
+            if instance.stage != *handle {
+                continue;
+            }
+
+            // Already built from the current asset this frame
+            if built.contains(&entity) {
+                continue;
+            }
+
+            info!("Building stage {:?}", entity);
+
+            commands.entity(entity).despawn_descendants();
+            commands.add(BuildStage {
+                entity,
+                stage: stage.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Lift-based glider flight model
+//!
+//! The Shrike has no engine. Swooping horizontally generates lift, which is converted into
+//! altitude, while pitching up or down trades between climbing and holding height.
+
+use bevy::{
+    ecs::reflect::ReflectComponent,
+    prelude::{Component, Query, ReflectDefault, Transform, Vec3},
+    reflect::Reflect,
+};
+
+use crate::prelude::{LinearMove, Oxygen, TimelineMeter, OXYGEN_BLACKOUT_SINK};
+
+/// Altitude at which the ship starts, in the middle of the bottom row
+pub const ALTITUDE_START: f32 = 0.5;
+
+/// Altitude of the cloud layer, below which there's little sunlight
+pub const CLOUD_LAYER: f32 = 1.0;
+
+/// Altitude of the upper atmosphere, past which life support starts to fail
+pub const ATMOSPHERE_TOP: f32 = 5.0;
+
+/// Highest reachable altitude
+pub const ALTITUDE_MAX: f32 = 6.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct GliderFlight {
+    /// Altitude gained per unit of horizontal distance travelled
+    pub lift: f32,
+    /// Altitude lost per second regardless of movement
+    pub sink: f32,
+    /// How strongly flying up or down scales the lift generated
+    pub pitch: f32,
+}
+
+impl Default for GliderFlight {
+    fn default() -> Self {
+        Self {
+            lift: 0.005,
+            sink: 0.005,
+            pitch: 0.5,
+        }
+    }
+}
+
+/// Height above the terrain, measured in rows of the wave graph
+#[derive(Debug, Copy, Clone, PartialEq, Component, Reflect)]
+#[reflect(Component)]
+pub struct Altitude {
+    pub altitude: f32,
+    /// Altitude change per timeline second
+    pub rate: f32,
+}
+
+impl Default for Altitude {
+    fn default() -> Self {
+        Self {
+            altitude: ALTITUDE_START,
+            rate: 0.0,
+        }
+    }
+}
+
+impl Altitude {
+    /// Index of the wave graph row containing this altitude
+    pub fn row(&self) -> usize {
+        self.altitude.max(0.0) as usize
+    }
+}
+
+impl TimelineMeter for Altitude {
+    fn value(&self) -> &f32 {
+        &self.altitude
+    }
+
+    fn value_mut(&mut self) -> &mut f32 {
+        &mut self.altitude
+    }
+
+    fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    fn bounds(&self) -> (f32, f32) {
+        (0.0, ALTITUDE_MAX)
+    }
+}
+
+/// Convert a glider's movement into a rate of climb
+///
+/// A glider whose oxygen is depleted generates no lift, and sinks until it crashes.
+pub fn glider_flight(
+    mut query: Query<(
+        &GliderFlight,
+        &LinearMove,
+        &Transform,
+        Option<&Oxygen>,
+        &mut Altitude,
+    )>,
+) {
+    for (glider, linear_move, transform, oxygen, mut altitude) in query.iter_mut() {
+        if oxygen.map(Oxygen::depleted).unwrap_or_default() {
+            if altitude.rate != -OXYGEN_BLACKOUT_SINK {
+                altitude.rate = -OXYGEN_BLACKOUT_SINK;
+            }
+            continue;
+        }
+
+        let mut velocity = if linear_move.active {
+            linear_move.delta
+        } else {
+            Vec3::ZERO
+        };
+
+        // Pushing against the edge of the playfield doesn't generate lift
+        for axis in 0..3 {
+            if (transform.translation[axis] <= linear_move.min[axis] && velocity[axis] < 0.0)
+                || (transform.translation[axis] >= linear_move.max[axis] && velocity[axis] > 0.0)
+            {
+                velocity[axis] = 0.0;
+            }
+        }
+
+        let speed = velocity.length();
+        let pitch = if speed > 0.0 { velocity.y / speed } else { 0.0 };
+
+        let rate = glider.lift * velocity.x.abs() * (1.0 + glider.pitch * pitch) - glider.sink;
+
+        if altitude.rate != rate {
+            altitude.rate = rate;
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Scalar gauges recorded onto a timeline
+//!
+//! Like LinearMove, a meter's value is evaluated from dynamic animation segments
+//! of constant rate rather than integrated each frame,
+//! so rewinding restores earlier readings exactly.
+
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::system::Command,
+    prelude::{default, Component, Entity, Query, ResMut},
+};
+
+use crate::prelude::{
+    default_entity, Determinisms, Discrete, DiscreteStop, DynamicAnimation,
+    DynamicSegmentPlayback, InsertDynamicAnimation, Timeline,
+};
+
+/// Maximum length in seconds of a single recorded meter segment
+pub const METER_HORIZON: f64 = 1.0;
+
+/// Smallest change in rate that is recorded as a new segment
+pub const METER_EPSILON: f32 = 0.0001;
+
+/// Fraction of a segment's rate that a new rate must differ by to be recorded
+///
+/// Keeps meters whose rate follows a continuous value from recording a segment every frame.
+pub const METER_RATE_TOLERANCE: f32 = 0.05;
+
+/// A component holding a scalar that changes at a rate set by gameplay systems
+pub trait TimelineMeter: 'static + Send + Sync + Component {
+    fn value(&self) -> &f32;
+    fn value_mut(&mut self) -> &mut f32;
+
+    /// Change in value per timeline second
+    fn rate(&self) -> f32;
+
+    /// Range that the value is clamped to
+    fn bounds(&self) -> (f32, f32);
+}
+
+/// Drive a meter's value from a dynamic animation recorded by timeline_meter
+pub struct InsertTimelineMeter<C> {
+    pub timeline: Entity,
+    pub entity: Entity,
+    pub _phantom: PhantomData<C>,
+}
+
+impl<C> Default for InsertTimelineMeter<C> {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            entity: default_entity(),
+            _phantom: default(),
+        }
+    }
+}
+
+impl<C> InsertTimelineMeter<C> {
+    pub fn new(timeline: Entity, entity: Entity) -> Self {
+        InsertTimelineMeter {
+            timeline,
+            entity,
+            ..default()
+        }
+    }
+}
+
+impl<C> Command for InsertTimelineMeter<C>
+where
+    C: TimelineMeter,
+{
+    fn write(self, world: &mut bevy::prelude::World) {
+        InsertDynamicAnimation::<C, f32> {
+            timeline: self.timeline,
+            entity: self.entity,
+            target: self.entity,
+            accessor: C::value,
+            mutator: C::value_mut,
+        }
+        .write(world);
+
+        // Start from the meter's initial value rather than zero
+        let value = *world.get::<C>(self.entity).unwrap().value();
+        let dynamic = *world.get::<DynamicAnimation<C, f32>>(self.entity).unwrap();
+
+        for stops in [dynamic.from_stops, dynamic.to_stops] {
+            let mut stops = world.get_mut::<Discrete<DiscreteStop<f32>>>(stops).unwrap();
+
+            for stop in stops.stops_mut() {
+                stop.value.value = value;
+            }
+        }
+    }
+}
+
+/// Record meter rate changes as dynamic animation stops on the meter's timeline
+///
+/// Segments end where they meet the meter's bounds, or at the horizon.
+pub fn timeline_meter<C>(
+    mut determinisms: ResMut<Determinisms>,
+    query_meter: Query<(&C, &DynamicAnimation<C, f32>)>,
+    query_timeline: Query<&Timeline>,
+    mut query_stops: Query<&mut Discrete<DiscreteStop<f32>>>,
+) where
+    C: TimelineMeter,
+{
+    for (meter, dynamic) in query_meter.iter() {
+        let DynamicSegmentPlayback { t, segment, .. } =
+            if let Some(playback) = dynamic.recordable_segment(&query_timeline, &query_stops) {
+                playback
+            } else {
+                continue;
+            };
+
+        let (min, max) = meter.bounds();
+        let origin = segment.value_at(t).clamp(min, max);
+
+        // Don't push into bounds that have already been reached
+        let mut rate = meter.rate();
+        if (origin <= min && rate < 0.0) || (origin >= max && rate > 0.0) {
+            rate = 0.0;
+        }
+
+        let segment_rate = segment.rate_at(t);
+        let tolerance = METER_EPSILON.max(segment_rate.abs() * METER_RATE_TOLERANCE);
+
+        let ended = t >= segment.to.t && rate != 0.0;
+        let changed = (rate - segment_rate).abs() > tolerance;
+
+        if !ended && !changed {
+            continue;
+        }
+
+        // Change until the bound being approached is met, or the horizon is reached
+        let duration = if rate > 0.0 {
+            ((max - origin) / rate) as f64
+        } else if rate < 0.0 {
+            ((min - origin) / rate) as f64
+        } else {
+            METER_HORIZON
+        }
+        .min(METER_HORIZON)
+        .max(f32::EPSILON as f64);
+
+        let target = (origin + rate * duration as f32).clamp(min, max);
+
+        dynamic.record_segment(
+            &mut determinisms,
+            &mut query_stops,
+            t,
+            origin,
+            target,
+            duration,
+        );
+    }
+}
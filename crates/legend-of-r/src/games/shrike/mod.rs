@@ -95,3 +95,130 @@
 // - Bomb dumps a large orb of energy from the core
 //   - Initial flash wipes all on-screen bullets
 //   - Flies straight for a short time, then detonates
+
+pub mod flight;
+pub mod meter;
+pub mod reactor;
+pub mod route;
+pub mod widget;
+
+use bevy::prelude::{
+    default, AmbientLight, Color, Commands, DirectionalLightBundle, Name,
+    ParallelSystemDescriptorCoercion, Plugin, Res, Transform, Vec3,
+};
+
+use crate::prelude::{
+    evaluate_tagged, glider_flight, reactor_rates, setup_shrike_widget, shrike_route,
+    timeline_meter, AddHudGauges, Altitude, AssembleBackground, AssembleCameraRig,
+    AssemblePlayfield, AssembleShip, GliderFlight, InsertShrikeRoute, InsertTimelineMeter,
+    MomentumMove, Oxygen, PlayerInputMaps, SecondaryCharge, ShipBundle, ShrikeWaveGraph,
+    SimulationTimeline, SpawnBoundary, StagePlugin, Timeline, Update,
+};
+
+/// Game mode built around the glider flight model and altitude-driven wave route
+pub struct ShrikePlugin;
+
+impl Plugin for ShrikePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_plugin(StagePlugin);
+
+        app.register_type::<GliderFlight>()
+            .register_type::<Altitude>()
+            .register_type::<SecondaryCharge>()
+            .register_type::<Oxygen>();
+
+        app.init_resource::<ShrikeWaveGraph>();
+
+        app.add_hud_gauges::<SecondaryCharge>()
+            .add_hud_gauges::<Oxygen>();
+
+        app.add_system(glider_flight.before(timeline_meter::<Altitude>))
+            .add_system(
+                reactor_rates
+                    .before(timeline_meter::<SecondaryCharge>)
+                    .before(timeline_meter::<Oxygen>),
+            )
+            .add_system(timeline_meter::<Altitude>.before(evaluate_tagged::<Update>))
+            .add_system(timeline_meter::<SecondaryCharge>.before(evaluate_tagged::<Update>))
+            .add_system(timeline_meter::<Oxygen>.before(evaluate_tagged::<Update>))
+            .add_system(shrike_route.after(evaluate_tagged::<Update>));
+
+        app.add_startup_system(setup)
+            .add_startup_system(setup_shrike_widget);
+    }
+}
+
+pub fn setup(player_input_maps: Res<PlayerInputMaps>, mut commands: Commands) {
+    // Lighting
+    commands.insert_resource(AmbientLight {
+        color: Color::WHITE,
+        brightness: 0.1,
+    });
+
+    commands.spawn_bundle(DirectionalLightBundle {
+        transform: Transform::default()
+            .looking_at(Vec3::new(-1.0, -1.0, -1.0).normalize(), Vec3::Y),
+        ..default()
+    });
+
+    let camera_rig = commands.spawn().id();
+    commands.add(AssembleCameraRig { entity: camera_rig });
+
+    // Background
+    let background = commands.spawn().id();
+    commands.add(AssembleBackground { entity: background });
+
+    // Playfield
+    let timeline = commands
+        .spawn()
+        .insert(Name::new("Timeline"))
+        .insert(Timeline::default())
+        .insert(SimulationTimeline)
+        .id();
+
+    let playfield = commands.spawn().id();
+
+    commands.add(AssemblePlayfield {
+        entity: playfield,
+        playfield_bundle: default(),
+        ..default()
+    });
+    commands.add(SpawnBoundary { playfield });
+
+    // One glider per local player
+    let mut ships = vec![];
+    for player in player_input_maps.players() {
+        let ship = commands.spawn().id();
+
+        commands.add(AssembleShip {
+            playfield,
+            timeline,
+            entity: ship,
+            ship_bundle: ShipBundle::for_player(player),
+            momentum_move: Some(MomentumMove::default()),
+            ..default()
+        });
+
+        commands
+            .entity(ship)
+            .insert(GliderFlight::default())
+            .insert(Altitude::default())
+            .insert(SecondaryCharge::default())
+            .insert(Oxygen::default());
+
+        commands.add(InsertTimelineMeter::<Altitude>::new(timeline, ship));
+        commands.add(InsertTimelineMeter::<SecondaryCharge>::new(timeline, ship));
+        commands.add(InsertTimelineMeter::<Oxygen>::new(timeline, ship));
+
+        ships.push(ship);
+    }
+
+    // Route through the wave grid, starting in the bottom-left
+    let route = commands.spawn().id();
+    commands.add(InsertShrikeRoute {
+        timeline,
+        playfield,
+        entity: route,
+        targets: ships,
+    });
+}
@@ -0,0 +1,206 @@
+//! Sunlight-powered reactor and life support
+//!
+//! Secondary weapon charge builds faster the higher the ship flies above the clouds,
+//! while oxygen runs down once it climbs past the upper atmosphere.
+//! Life support doesn't recover once oxygen is depleted, and the glider falls out of the sky.
+
+use bevy::{
+    ecs::reflect::ReflectComponent,
+    prelude::{Component, Query},
+    reflect::Reflect,
+};
+
+use crate::prelude::{
+    Altitude, HudGauges, TimelineMeter, ALTITUDE_MAX, ATMOSPHERE_TOP, CLOUD_LAYER,
+};
+
+/// Charge gained per second just above the cloud layer, doubling with each row climbed
+pub const CHARGE_RATE: f32 = 0.02;
+
+/// Altitude beyond which charge rate stops increasing
+pub const CHARGE_MAX_ALTITUDE: f32 = ALTITUDE_MAX;
+
+/// Oxygen lost per second for each row above the upper atmosphere
+pub const OXYGEN_DEPLETION: f32 = 0.1;
+
+/// Oxygen regained per second below the upper atmosphere
+pub const OXYGEN_RECOVERY: f32 = 0.05;
+
+/// Fraction of the oxygen readout lost to thinning air on reaching the upper atmosphere
+pub const OXYGEN_PRESSURE_DROP: f32 = 0.3;
+
+/// Altitude lost per second once oxygen is depleted and the pilot has blacked out
+pub const OXYGEN_BLACKOUT_SINK: f32 = 0.5;
+
+/// Secondary weapon charge, from empty at 0 to full at 1
+#[derive(Debug, Default, Copy, Clone, PartialEq, Component, Reflect)]
+#[reflect(Component)]
+pub struct SecondaryCharge {
+    pub charge: f32,
+    pub rate: f32,
+}
+
+impl SecondaryCharge {
+    /// Charge gained per second at a given altitude
+    pub fn rate_at(altitude: f32) -> f32 {
+        if altitude < CLOUD_LAYER {
+            0.0
+        } else {
+            CHARGE_RATE * 2.0f32.powf(altitude.min(CHARGE_MAX_ALTITUDE) - CLOUD_LAYER)
+        }
+    }
+}
+
+impl TimelineMeter for SecondaryCharge {
+    fn value(&self) -> &f32 {
+        &self.charge
+    }
+
+    fn value_mut(&mut self) -> &mut f32 {
+        &mut self.charge
+    }
+
+    fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    fn bounds(&self) -> (f32, f32) {
+        (0.0, 1.0)
+    }
+}
+
+impl HudGauges for SecondaryCharge {
+    fn gauges(&self) -> Vec<(&'static str, f32)> {
+        vec![("CHRG", self.charge)]
+    }
+}
+
+/// Life support reserve, from depleted at 0 to full at 1
+#[derive(Debug, Copy, Clone, PartialEq, Component, Reflect)]
+#[reflect(Component)]
+pub struct Oxygen {
+    pub oxygen: f32,
+    pub rate: f32,
+}
+
+impl Default for Oxygen {
+    fn default() -> Self {
+        Self {
+            oxygen: 1.0,
+            rate: 0.0,
+        }
+    }
+}
+
+impl Oxygen {
+    /// Oxygen change per second at a given altitude
+    pub fn rate_at(altitude: f32) -> f32 {
+        if altitude > ATMOSPHERE_TOP {
+            -OXYGEN_DEPLETION * (altitude - ATMOSPHERE_TOP)
+        } else {
+            OXYGEN_RECOVERY
+        }
+    }
+
+    /// Value shown on the O2 readout, which sags as the ship nears the upper atmosphere
+    pub fn readout(&self, altitude: f32) -> f32 {
+        let thinning = (altitude / ATMOSPHERE_TOP).clamp(0.0, 1.0).powi(2);
+        self.oxygen * (1.0 - OXYGEN_PRESSURE_DROP * thinning)
+    }
+
+    pub fn depleted(&self) -> bool {
+        self.oxygen <= 0.0
+    }
+}
+
+impl TimelineMeter for Oxygen {
+    fn value(&self) -> &f32 {
+        &self.oxygen
+    }
+
+    fn value_mut(&mut self) -> &mut f32 {
+        &mut self.oxygen
+    }
+
+    fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    fn bounds(&self) -> (f32, f32) {
+        (0.0, 1.0)
+    }
+}
+
+impl HudGauges for Oxygen {
+    fn gauges(&self) -> Vec<(&'static str, f32)> {
+        vec![("O2", self.oxygen)]
+    }
+}
+
+/// Set charge and oxygen rates from the ship's current altitude
+pub fn reactor_rates(
+    mut query: Query<(&Altitude, Option<&mut SecondaryCharge>, Option<&mut Oxygen>)>,
+) {
+    for (altitude, charge, oxygen) in query.iter_mut() {
+        if let Some(mut charge) = charge {
+            let rate = SecondaryCharge::rate_at(altitude.altitude);
+            if charge.rate != rate {
+                charge.rate = rate;
+            }
+        }
+
+        if let Some(mut oxygen) = oxygen {
+            // Life support doesn't recover once depleted
+            let rate = if oxygen.depleted() {
+                0.0
+            } else {
+                Oxygen::rate_at(altitude.altitude)
+            };
+
+            if oxygen.rate != rate {
+                oxygen.rate = rate;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charge_doubles_per_row_above_clouds() {
+        assert_eq!(SecondaryCharge::rate_at(CLOUD_LAYER - 0.5), 0.0);
+        assert_eq!(SecondaryCharge::rate_at(CLOUD_LAYER), CHARGE_RATE);
+        assert_eq!(SecondaryCharge::rate_at(CLOUD_LAYER + 1.0), CHARGE_RATE * 2.0);
+        assert_eq!(
+            SecondaryCharge::rate_at(CHARGE_MAX_ALTITUDE + 1.0),
+            SecondaryCharge::rate_at(CHARGE_MAX_ALTITUDE)
+        );
+    }
+
+    #[test]
+    fn oxygen_drains_above_atmosphere() {
+        assert_eq!(Oxygen::rate_at(ATMOSPHERE_TOP), OXYGEN_RECOVERY);
+        assert!(Oxygen::rate_at(ATMOSPHERE_TOP + 1.0) < 0.0);
+        assert!(Oxygen::rate_at(ATMOSPHERE_TOP + 1.0) > Oxygen::rate_at(ATMOSPHERE_TOP + 2.0));
+    }
+
+    #[test]
+    fn oxygen_readout_sags_with_altitude() {
+        let oxygen = Oxygen::default();
+        assert_eq!(oxygen.readout(0.0), 1.0);
+        assert_eq!(oxygen.readout(ATMOSPHERE_TOP), 1.0 - OXYGEN_PRESSURE_DROP);
+        assert_eq!(oxygen.readout(ALTITUDE_MAX), oxygen.readout(ATMOSPHERE_TOP));
+    }
+
+    #[test]
+    fn oxygen_depletes_at_zero() {
+        assert!(!Oxygen::default().depleted());
+        assert!(Oxygen {
+            oxygen: 0.0,
+            ..Oxygen::default()
+        }
+        .depleted());
+    }
+}
@@ -0,0 +1,368 @@
+//! Altitude-driven route through a 5x5 grid of waves
+//!
+//! Each column of the grid is one wave. At the end of a wave, the ship's altitude within its
+//! current row decides whether the next wave is up-right, right, or down-right.
+//! Waves are picked during regular playback; rewinding past the start of a wave
+//! despawns it, so it's picked again from however the ship flies the second time.
+
+use bevy::{
+    ecs::system::Command,
+    prelude::{
+        default, info, AssetServer, Commands, Component, DespawnRecursiveExt, Entity, EventWriter,
+        Name, Query, Res, World,
+    },
+};
+
+use crate::prelude::{
+    default_entity, Altitude, DamageEvent, HitPoints, InsertStage, Oxygen, Timeline, ATMOSPHERE_TOP,
+};
+
+pub const WAVE_ROWS: usize = 5;
+pub const WAVE_COLUMNS: usize = 5;
+
+/// Length of each wave in timeline seconds
+pub const WAVE_DURATION: f64 = 60.0;
+
+/// Fraction of a row above or below which the route climbs or descends
+pub const WAVE_TRAVERSE_THRESHOLD: f32 = 1.0 / 3.0;
+
+/// Stage assets for each cell of the wave grid, indexed by row then column
+///
+/// The route starts in the bottom-left and climbs at most one row per column,
+/// so only cells on or below the diagonal are reachable by default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShrikeWaveGraph {
+    pub cells: Vec<Vec<Option<String>>>,
+}
+
+impl Default for ShrikeWaveGraph {
+    fn default() -> Self {
+        Self {
+            cells: (0..WAVE_ROWS)
+                .map(|row| {
+                    (0..WAVE_COLUMNS)
+                        .map(|column| {
+                            (row <= column)
+                                .then(|| format!("stages/shrike/wave_{row}_{column}.stage.ron"))
+                        })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ShrikeWaveGraph {
+    pub fn stage(&self, row: usize, column: usize) -> Option<&str> {
+        self.cells
+            .get(row)
+            .and_then(|row| row.get(column))
+            .and_then(Option::as_deref)
+    }
+}
+
+/// Direction taken through the wave grid at the end of a wave
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Traverse {
+    Up,
+    Right,
+    Down,
+}
+
+impl Traverse {
+    pub fn from_altitude(row: usize, altitude: f32) -> Self {
+        let local = altitude - row as f32;
+
+        if local > 1.0 - WAVE_TRAVERSE_THRESHOLD {
+            Traverse::Up
+        } else if local < WAVE_TRAVERSE_THRESHOLD {
+            Traverse::Down
+        } else {
+            Traverse::Right
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RouteOutcome {
+    /// Descended from the bottom row into the terrain
+    Crashed,
+    /// Life support failed above the upper atmosphere, and the ship fell into the terrain
+    Asphyxiated,
+    /// Finished the final column
+    Cleared,
+    /// Climbed every wave and left the atmosphere
+    Ascended,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RouteWave {
+    /// Timeline time at which the wave starts
+    pub t: f64,
+    pub row: usize,
+    pub column: usize,
+    pub stage: Entity,
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct ShrikeRoute {
+    pub timeline: Entity,
+    pub playfield: Entity,
+    /// Ships whose altitude steers the route, and that enemy bullets home in on
+    pub targets: Vec<Entity>,
+    pub waves: Vec<RouteWave>,
+    pub outcome: Option<(f64, RouteOutcome)>,
+}
+
+impl Default for ShrikeRoute {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            playfield: default_entity(),
+            targets: default(),
+            waves: default(),
+            outcome: default(),
+        }
+    }
+}
+
+impl ShrikeRoute {
+    pub fn current(&self) -> Option<&RouteWave> {
+        self.waves.last()
+    }
+
+    /// Whether every wave so far has been reached by climbing
+    pub fn ascending(&self) -> bool {
+        self.waves.iter().all(|wave| wave.row == wave.column)
+    }
+
+    fn insert_wave(
+        &mut self,
+        graph: &ShrikeWaveGraph,
+        asset_server: &AssetServer,
+        stage: Entity,
+        t: f64,
+        row: usize,
+        column: usize,
+    ) -> Option<InsertStage> {
+        info!("Starting wave at row {row}, column {column}");
+
+        self.waves.push(RouteWave {
+            t,
+            row,
+            column,
+            stage,
+        });
+
+        // Cells without a stage are left empty
+        let path = graph.stage(row, column)?;
+
+        Some(InsertStage {
+            playfield: self.playfield,
+            timeline: self.timeline,
+            entity: stage,
+            stage: asset_server.load(path),
+            targets: self.targets.clone(),
+            offset: t,
+        })
+    }
+}
+
+pub struct InsertShrikeRoute {
+    pub timeline: Entity,
+    pub playfield: Entity,
+    pub entity: Entity,
+    pub targets: Vec<Entity>,
+}
+
+impl Default for InsertShrikeRoute {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            playfield: default_entity(),
+            entity: default_entity(),
+            targets: default(),
+        }
+    }
+}
+
+impl Command for InsertShrikeRoute {
+    fn write(self, world: &mut World) {
+        let mut route = ShrikeRoute {
+            timeline: self.timeline,
+            playfield: self.playfield,
+            targets: self.targets,
+            ..default()
+        };
+
+        // Start in the bottom-left
+        let stage = world.spawn().id();
+        let insert_stage = route.insert_wave(
+            &world.resource::<ShrikeWaveGraph>().clone(),
+            world.resource::<AssetServer>(),
+            stage,
+            0.0,
+            0,
+            0,
+        );
+
+        if let Some(insert_stage) = insert_stage {
+            insert_stage.write(world);
+        }
+
+        world
+            .entity_mut(self.entity)
+            .insert(Name::new("Shrike Route"))
+            .insert(route);
+    }
+}
+
+pub fn shrike_route(
+    graph: Res<ShrikeWaveGraph>,
+    asset_server: Res<AssetServer>,
+    query_timeline: Query<&Timeline>,
+    query_altitude: Query<&Altitude>,
+    query_oxygen: Query<(&Oxygen, &Altitude, Option<&HitPoints>)>,
+    mut query_route: Query<&mut ShrikeRoute>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut commands: Commands,
+) {
+    for mut route in query_route.iter_mut() {
+        let timeline = if let Ok(timeline) = query_timeline.get(route.timeline) {
+            timeline
+        } else {
+            continue;
+        };
+
+        let t = timeline.t;
+
+        // Forget waves and outcomes that haven't happened yet at the rewound time
+        while route.waves.len() > 1 && route.current().unwrap().t > t {
+            let wave = route.waves.pop().unwrap();
+            commands.entity(wave.stage).despawn_recursive();
+        }
+
+        if matches!(route.outcome, Some((outcome_t, _)) if outcome_t > t) {
+            route.outcome = None;
+        }
+
+        // Only advance during regular playback; pausing and scrubbing replay existing waves
+        if timeline.paused || timeline.scrub_rate != 0.0 || route.outcome.is_some() {
+            continue;
+        }
+
+        // A ship that blacked out destroys itself on reaching the ground
+        let crashed = route.targets.iter().copied().find(|target| {
+            query_oxygen
+                .get(*target)
+                .map(|(oxygen, altitude, _)| oxygen.depleted() && altitude.altitude <= 0.0)
+                .unwrap_or_default()
+        });
+
+        if let Some(target) = crashed {
+            info!("Route ended by oxygen depletion");
+
+            let (_, _, hit_points) = query_oxygen.get(target).unwrap();
+            damage_events.send(DamageEvent {
+                source: target,
+                target,
+                damage: hit_points.map(|hit_points| hit_points.0).unwrap_or(1) as isize,
+            });
+
+            route.outcome = Some((t, RouteOutcome::Asphyxiated));
+            continue;
+        }
+
+        let wave = *route.current().unwrap();
+        let end = wave.t + WAVE_DURATION;
+        if t < end {
+            continue;
+        }
+
+        // The highest ship leads the route
+        let altitude = route
+            .targets
+            .iter()
+            .filter_map(|target| query_altitude.get(*target).ok())
+            .map(|altitude| altitude.altitude)
+            .fold(0.0, f32::max);
+
+        let row = match Traverse::from_altitude(wave.row, altitude) {
+            Traverse::Up => (wave.row + 1).min(WAVE_ROWS - 1),
+            Traverse::Right => wave.row,
+            Traverse::Down if wave.row == 0 => {
+                info!("Route ended by crashing into the terrain");
+                route.outcome = Some((end, RouteOutcome::Crashed));
+                continue;
+            }
+            Traverse::Down => wave.row - 1,
+        };
+
+        let column = wave.column + 1;
+        if column >= WAVE_COLUMNS {
+            let outcome = if route.ascending() && altitude > ATMOSPHERE_TOP {
+                RouteOutcome::Ascended
+            } else {
+                RouteOutcome::Cleared
+            };
+
+            info!("Route ended with outcome {outcome:?}");
+            route.outcome = Some((end, outcome));
+            continue;
+        }
+
+        let stage = commands.spawn().id();
+        if let Some(insert_stage) =
+            route.insert_wave(&graph, &asset_server, stage, end, row, column)
+        {
+            commands.add(insert_stage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Stage;
+
+    #[test]
+    fn traverse_from_altitude() {
+        assert_eq!(Traverse::from_altitude(2, 2.9), Traverse::Up);
+        assert_eq!(Traverse::from_altitude(2, 2.5), Traverse::Right);
+        assert_eq!(Traverse::from_altitude(2, 2.1), Traverse::Down);
+        assert_eq!(Traverse::from_altitude(0, -0.5), Traverse::Down);
+    }
+
+    #[test]
+    fn default_graph_covers_reachable_cells() {
+        let graph = ShrikeWaveGraph::default();
+
+        for row in 0..WAVE_ROWS {
+            for column in 0..WAVE_COLUMNS {
+                assert_eq!(graph.stage(row, column).is_some(), row <= column);
+            }
+        }
+
+        assert_eq!(graph.stage(WAVE_ROWS, 0), None);
+    }
+
+    #[test]
+    fn default_graph_stages_parse() {
+        let graph = ShrikeWaveGraph::default();
+
+        for path in graph.cells.iter().flatten().flatten() {
+            let path = format!("{}/assets/{path}", env!("CARGO_MANIFEST_DIR"));
+            let source = std::fs::read_to_string(&path).unwrap();
+            let stage: Stage = ron::de::from_str(&source)
+                .unwrap_or_else(|e| panic!("Failed to parse {path}: {e}"));
+
+            for wave in stage.waves.iter() {
+                assert!(
+                    stage.enemy(&wave.enemy).is_some(),
+                    "Unknown enemy {} in {path}",
+                    wave.enemy
+                );
+            }
+        }
+    }
+}
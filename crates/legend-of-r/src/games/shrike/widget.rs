@@ -0,0 +1,115 @@
+//! Flight instrument readout for altitude, reactor charge and oxygen
+
+use std::borrow::Cow;
+
+use bevy::prelude::{Res, World};
+use bevy_inspector_egui::egui::{Color32, ProgressBar, Response, Ui};
+
+use crate::prelude::{
+    Altitude, Oxygen, PlayerId, RouteOutcome, SecondaryCharge, ShrikeRoute, Widget, Widgets,
+    ALTITUDE_MAX, ATMOSPHERE_TOP, WAVE_COLUMNS,
+};
+
+pub fn setup_shrike_widget(widgets: Option<Res<Widgets>>) {
+    if let Some(widgets) = widgets {
+        widgets.panel_outer_max.add_widget(ShrikeWidget::default());
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+struct Instruments {
+    player: PlayerId,
+    altitude: f32,
+    charge: f32,
+    oxygen: f32,
+}
+
+/// UI widget displaying each ship's instruments and the current position in the wave grid
+#[derive(Debug, Default, Clone)]
+pub struct ShrikeWidget {
+    instruments: Vec<Instruments>,
+    wave: Option<(usize, usize)>,
+    outcome: Option<RouteOutcome>,
+}
+
+impl Widget for ShrikeWidget {
+    fn update(&mut self, world: &mut World) {
+        self.instruments = world
+            .query::<(&PlayerId, &Altitude, &SecondaryCharge, &Oxygen)>()
+            .iter(world)
+            .map(|(player, altitude, charge, oxygen)| Instruments {
+                player: *player,
+                altitude: altitude.altitude,
+                charge: charge.charge,
+                oxygen: oxygen.readout(altitude.altitude),
+            })
+            .collect();
+
+        self.instruments.sort_by_key(|instruments| instruments.player.0);
+
+        let route = world.query::<&ShrikeRoute>().iter(world).next().cloned();
+
+        self.wave = route
+            .as_ref()
+            .and_then(ShrikeRoute::current)
+            .map(|wave| (wave.row, wave.column));
+
+        self.outcome = route
+            .and_then(|route| route.outcome)
+            .map(|(_, outcome)| outcome);
+    }
+
+    fn title(&self) -> Cow<'static, str> {
+        Cow::Borrowed("Shrike")
+    }
+
+    fn ui(&mut self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            match self.wave {
+                Some((row, column)) => ui.label(format!(
+                    "Wave {} of {WAVE_COLUMNS}, row {}",
+                    column + 1,
+                    row + 1
+                )),
+                None => ui.label("No route"),
+            };
+
+            if let Some(outcome) = self.outcome {
+                ui.label(format!("{outcome:?}"));
+            }
+
+            for instruments in self.instruments.iter() {
+                ui.separator();
+                ui.label(format!("Player {}", instruments.player.0 + 1));
+
+                ui.add(
+                    ProgressBar::new(instruments.altitude / ALTITUDE_MAX)
+                        .text(format!("ALT {:.2}", instruments.altitude)),
+                );
+
+                ui.add(
+                    ProgressBar::new(instruments.charge)
+                        .text(format!("CHG {:.0}%", instruments.charge * 100.0)),
+                );
+
+                // Comfortably green in normal play, only dropping off past the atmosphere
+                let color = if instruments.altitude > ATMOSPHERE_TOP || instruments.oxygen < 0.5 {
+                    if instruments.oxygen < 0.25 {
+                        Color32::RED
+                    } else {
+                        Color32::YELLOW
+                    }
+                } else {
+                    Color32::DARK_GREEN
+                };
+
+                ui.add(
+                    ProgressBar::new(instruments.oxygen)
+                        .fill(color)
+                        .text(format!("O2 {:.0}%", instruments.oxygen * 100.0)),
+                );
+            }
+        })
+        .response
+    }
+}
@@ -19,7 +19,11 @@ use serde_json::{Map, Value};
 
 use crate::{
     gltf_json::gltf_json_remove,
-    integration::ReflectEnum,
+    integration::{
+        migration::IntegrationMigrations,
+        validate::{json_kind, ExtrasError, ExtrasErrorKind},
+        EnumVariantFields, IntegrationMetadata, ReflectEnum,
+    },
     prelude::{
         gltf_json_insert, reflect_bundle::ReflectBundle, GltfExtrasJson, ReflectBoolean,
         ReflectOption,
@@ -44,6 +48,8 @@ fn deserialize_gltf_components(
     gltf_assets: ResMut<Assets<Gltf>>,
     mut scene_assets: ResMut<Assets<Scene>>,
     type_registry: Res<TypeRegistry>,
+    metadata: Option<Res<IntegrationMetadata>>,
+    migrations: Option<Res<IntegrationMigrations>>,
 ) {
     // Iterate over changed handles
     for handle in gltf_events.iter().filter_map(|event| match event {
@@ -61,90 +67,252 @@ fn deserialize_gltf_components(
 
         // Iterate over each scene in the gltf asset
         for handle in gltf.scenes.iter() {
-            let scene = scene_assets.get_mut(handle).unwrap();
+            let scene = if let Some(scene) = scene_assets.get_mut(handle) {
+                scene
+            } else {
+                continue;
+            };
             debug!("Scene: {scene:#?}");
-            deserialize_gltf_scene(&type_registry, scene);
+            deserialize_gltf_scene(
+                &type_registry,
+                metadata.as_deref(),
+                migrations.as_deref(),
+                scene,
+            );
         }
     }
 }
 
-fn deserialize_gltf_scene(type_registry: &TypeRegistry, scene: &mut Scene) {
+fn deserialize_gltf_scene(
+    type_registry: &TypeRegistry,
+    metadata: Option<&IntegrationMetadata>,
+    migrations: Option<&IntegrationMigrations>,
+    scene: &mut Scene,
+) {
     // Iterate over scene and collect JSON extra entity entries
-    let mut query = scene.world.query::<(Entity, &mut GltfExtrasJson)>();
+    let mut query = scene
+        .world
+        .query::<(Entity, Option<&Name>, &mut GltfExtrasJson)>();
     let extra_entries = query
         .iter_mut(&mut scene.world)
-        .flat_map(|(entity, mut extras)| match &mut extras.0 {
-            Value::Object(object) => {
-                if let Some(entity_object) = object.remove_entry("entity") {
-                    Some((entity, entity_object))
-                } else {
-                    None
-                }
-            }
-            _ => panic!("Extras entry is not an object"),
+        .flat_map(|(entity, name, mut extras)| {
+            let node = name
+                .map(ToString::to_string)
+                .unwrap_or_else(|| format!("{entity:?}"));
+
+            let object = if let Value::Object(object) = &mut extras.0 {
+                object
+            } else {
+                report_extras_errors(&[ExtrasError {
+                    node,
+                    path: default(),
+                    kind: ExtrasErrorKind::ExtrasNotObject,
+                }]);
+                return None;
+            };
+
+            object
+                .remove("entity")
+                .map(|entity_object| (entity, (node, entity_object)))
         })
         .collect::<BTreeMap<_, _>>();
 
     // Iterate over entity entries
-    for (entity, (_, mut value)) in extra_entries {
+    for (entity, (node, mut value)) in extra_entries {
         let object = if let Value::Object(ref mut object) = value {
             object
         } else {
-            panic!("Entity entry is not an object");
+            report_extras_errors(&[ExtrasError {
+                node,
+                path: "entity".into(),
+                kind: ExtrasErrorKind::EntityNotObject,
+            }]);
+            continue;
         };
 
-        deserialize_gltf_entity(entity, scene, type_registry, object);
+        deserialize_gltf_entity(
+            entity,
+            &node,
+            scene,
+            type_registry,
+            metadata,
+            migrations,
+            object,
+        );
+    }
+}
+
+fn report_extras_errors(errors: &[ExtrasError]) {
+    for error in errors {
+        error!("Invalid GLTF extras: {error:}");
     }
 }
 
 fn deserialize_gltf_entity(
     entity: Entity,
+    node: &str,
     scene: &mut Scene,
     type_registry: &TypeRegistry,
+    metadata: Option<&IntegrationMetadata>,
+    migrations: Option<&IntegrationMigrations>,
     object: &mut Map<String, Value>,
 ) {
-    // Iterate over entity components
-    for (_, component) in object.iter_mut() {
-        deserialize_gltf_component(scene, entity, type_registry, component);
+    // Bring components authored against an older schema up to date
+    if let Some(migrations) = migrations {
+        for (_, component) in object.iter_mut() {
+            migrations.migrate(component);
+        }
+    }
+
+    // Skip components whose extras don't match the integration metadata, keeping the valid ones
+    if let Some(metadata) = metadata {
+        object.retain(|key, component| {
+            let errors = metadata.validate_component(node, key, component);
+            report_extras_errors(&errors);
+            errors.is_empty()
+        });
+    }
+
+    // Iterate over entity components, skipping any that fail to deserialize
+    for (key, component) in object.iter_mut() {
+        let path = format!("entity.{key:}");
+        if let Err(error) =
+            deserialize_gltf_component(scene, entity, node, &path, type_registry, component)
+        {
+            report_extras_errors(&[error]);
+        }
+    }
+}
+
+fn extras_error(node: &str, path: &str, kind: ExtrasErrorKind) -> ExtrasError {
+    ExtrasError {
+        node: node.to_string(),
+        path: path.to_string(),
+        kind,
+    }
+}
+
+fn expect_object<'a>(
+    node: &str,
+    path: &str,
+    value: &'a mut Value,
+) -> Result<&'a mut Map<String, Value>, ExtrasError> {
+    match value {
+        Value::Object(object) => Ok(object),
+        other => Err(extras_error(
+            node,
+            path,
+            ExtrasErrorKind::InvalidValue {
+                expected: "object".to_string(),
+                found: json_kind(other),
+            },
+        )),
+    }
+}
+
+/// Read the type entry of a typed JSON value
+fn type_entry(node: &str, path: &str, value: &mut Value) -> Result<String, ExtrasError> {
+    match expect_object(node, path, value)?.get("type") {
+        Some(Value::String(type_name)) => Ok(type_name.clone()),
+        Some(other) => Err(extras_error(
+            node,
+            path,
+            ExtrasErrorKind::InvalidValue {
+                expected: "string".to_string(),
+                found: json_kind(other),
+            },
+        )),
+        None => Err(extras_error(node, path, ExtrasErrorKind::MissingType)),
+    }
+}
+
+/// Remove an object-valued payload entry such as "struct" or "map"
+fn take_object(
+    node: &str,
+    path: &str,
+    component: &mut Map<String, Value>,
+    key: &str,
+) -> Result<Map<String, Value>, ExtrasError> {
+    let path = format!("{path:}.{key:}");
+    match component.remove(key) {
+        Some(Value::Object(object)) => Ok(object),
+        Some(other) => Err(extras_error(
+            node,
+            &path,
+            ExtrasErrorKind::InvalidValue {
+                expected: "object".to_string(),
+                found: json_kind(&other),
+            },
+        )),
+        None => Err(extras_error(
+            node,
+            &path,
+            ExtrasErrorKind::MissingKey(key.to_string()),
+        )),
+    }
+}
+
+/// Remove an index-keyed payload entry such as "tuple" or "list", in index order
+fn take_indexed(
+    node: &str,
+    path: &str,
+    component: &mut Map<String, Value>,
+    key: &str,
+) -> Result<Vec<Value>, ExtrasError> {
+    match component.remove(key) {
+        Some(items @ (Value::Array(_) | Value::Object(_))) => Ok(indexed_json(&items)),
+        Some(other) => Err(extras_error(
+            node,
+            &format!("{path:}.{key:}"),
+            ExtrasErrorKind::InvalidValue {
+                expected: "object".to_string(),
+                found: json_kind(&other),
+            },
+        )),
+        None => Err(extras_error(
+            node,
+            path,
+            ExtrasErrorKind::MissingKey(key.to_string()),
+        )),
     }
 }
 
 fn deserialize_gltf_component(
     scene: &mut Scene,
     entity: Entity,
+    node: &str,
+    path: &str,
     type_registry: &TypeRegistry,
     component: &mut Value,
-) {
-    let type_name = component
-        .get("type")
-        .expect("Component {short_name:} has no type entry")
-        .as_str()
-        .expect("Component type entry is not a string")
-        .to_string();
+) -> Result<(), ExtrasError> {
+    let type_name = type_entry(node, path, component)?;
 
     info!("{type_name:}");
 
     // Perform fixups to ensure JSON data is compliant with serde format
-    walk_json(component, &mut |value| {
-        fixup_bool(type_registry, value);
-        fixup_enum(type_registry, value);
-        fixup_option(type_registry, value);
-        fixup_tuple_types(type_registry, value);
-        fixup_map(type_registry, value);
-        fixup_entity(&mut scene.world, value);
+    walk_json(component, path, &mut |path, value| {
+        fixup_bool(type_registry, node, path, value)?;
+        fixup_enum(type_registry, node, path, value)?;
+        fixup_option(type_registry, node, path, value)?;
+        fixup_tuple_types(type_registry, node, path, value)?;
+        fixup_map(type_registry, node, path, value)?;
+        fixup_entity(&mut scene.world, node, path, value)
+    })?;
+
+    walk_json_post(component, &mut |value| {
+        fixup_enum_payload(type_registry, value);
     });
 
-    info!("{:}", serde_json::to_string_pretty(component).unwrap());
+    info!("{component:#}");
 
     // Take a read lock on the type registry
     let type_registry = type_registry.read();
 
-    let result = deserialize_gltf_field(&type_registry, component)
-        .unwrap_or_else(|| panic!("Failed to desreialize component {type_name:}"));
+    let result = deserialize_gltf_field(&type_registry, node, path, component)?;
 
     let registration = type_registry
         .get_with_name(&type_name)
-        .unwrap_or_else(|| panic!("No type registration for {type_name:}"));
+        .ok_or_else(|| extras_error(node, path, ExtrasErrorKind::UnknownType(type_name.clone())))?;
 
     if let Some(reflect_component) = registration.data::<ReflectComponent>() {
         debug!("Reflect component OK");
@@ -155,29 +323,32 @@ fn deserialize_gltf_component(
         reflect_bundle.insert(&mut scene.world, entity, &*result);
         debug!("Insert bundle OK");
     } else {
-        error!("Unrecognized GLTF entity datatype {type_name:}")
+        return Err(extras_error(
+            node,
+            path,
+            ExtrasErrorKind::NotAComponent(type_name),
+        ));
     }
+
+    Ok(())
 }
 
 fn deserialize_gltf_field(
     type_registry: &TypeRegistryInternal,
+    node: &str,
+    path: &str,
     value: &mut Value,
-) -> Option<Box<dyn Reflect>> {
-    let type_name = value
-        .get("type")
-        .expect("Field {short_name:} has no type entry")
-        .as_str()
-        .expect("Field type entry is not a string")
-        .to_string();
+) -> Result<Box<dyn Reflect>, ExtrasError> {
+    let type_name = type_entry(node, path, value)?;
 
     info!("{type_name:}");
 
     let registration = type_registry
         .get_with_name(&type_name)
-        .unwrap_or_else(|| panic!("No type registration for {type_name:}"));
+        .ok_or_else(|| extras_error(node, path, ExtrasErrorKind::UnknownType(type_name.clone())))?;
 
     // Try to deserialize
-    let deserializer = ReflectDeserializer::new(&type_registry);
+    let deserializer = ReflectDeserializer::new(type_registry);
     let result = deserializer.deserialize(value.clone());
 
     info!("Deserialize result: {result:#?}");
@@ -185,7 +356,7 @@ fn deserialize_gltf_field(
     match result {
         Ok(reflect) => {
             info!("Deserialized: {reflect:#?}");
-            Some(reflect)
+            Ok(reflect)
         }
         Err(e) => {
             info!("Deserialization failed: {e:#?}");
@@ -196,32 +367,26 @@ fn deserialize_gltf_field(
                 if let Some(reflect_default) = registration.data::<ReflectDefault>() {
                     reflect_default
                 } else {
-                    return None;
+                    return Err(extras_error(
+                        node,
+                        path,
+                        ExtrasErrorKind::Deserialize(e.to_string()),
+                    ));
                 };
 
             let mut default = reflect_default.default();
 
-            let component = if let Value::Object(object) = value {
-                object
-            } else {
-                panic!("Component is not an object");
-            };
+            let component = expect_object(node, path, value)?;
 
             match registration.type_info() {
                 TypeInfo::Struct(_) => {
                     let mut dynamic = DynamicStruct::default();
                     dynamic.set_name(type_name);
 
-                    let object = if let Some(Value::Object(object)) = component.remove("struct") {
-                        object
-                    } else {
-                        panic!("struct field is not an object");
-                    };
-
-                    for (key, mut value) in object.into_iter() {
-                        if let Some(field) = deserialize_gltf_field(&type_registry, &mut value) {
-                            dynamic.insert_boxed(&key, field);
-                        }
+                    for (key, mut value) in take_object(node, path, component, "struct")? {
+                        let path = format!("{path:}.struct.{key:}");
+                        let field = deserialize_gltf_field(type_registry, node, &path, &mut value)?;
+                        dynamic.insert_boxed(&key, field);
                     }
 
                     default.apply(dynamic.as_reflect());
@@ -230,17 +395,11 @@ fn deserialize_gltf_field(
                     let mut dynamic = DynamicTupleStruct::default();
                     dynamic.set_name(type_name);
 
-                    let object =
-                        if let Some(Value::Object(object)) = component.remove("tuple_struct") {
-                            object
-                        } else {
-                            panic!("tuple_struct field is not an object");
-                        };
-
-                    for (_, mut value) in object.into_iter() {
-                        if let Some(field) = deserialize_gltf_field(&type_registry, &mut value) {
-                            dynamic.insert_boxed(field);
-                        }
+                    let items = take_indexed(node, path, component, "tuple_struct")?;
+                    for (index, mut value) in items.into_iter().enumerate() {
+                        let path = format!("{path:}.tuple_struct.{index:}");
+                        let field = deserialize_gltf_field(type_registry, node, &path, &mut value)?;
+                        dynamic.insert_boxed(field);
                     }
 
                     default.apply(dynamic.as_reflect());
@@ -249,16 +408,11 @@ fn deserialize_gltf_field(
                     let mut dynamic = DynamicTuple::default();
                     dynamic.set_name(type_name);
 
-                    let object = if let Some(Value::Object(object)) = component.remove("tuple") {
-                        object
-                    } else {
-                        panic!("tuple field is not an object");
-                    };
-
-                    for (_, mut value) in object.into_iter() {
-                        if let Some(field) = deserialize_gltf_field(&type_registry, &mut value) {
-                            dynamic.insert_boxed(field);
-                        }
+                    let items = take_indexed(node, path, component, "tuple")?;
+                    for (index, mut value) in items.into_iter().enumerate() {
+                        let path = format!("{path:}.tuple.{index:}");
+                        let field = deserialize_gltf_field(type_registry, node, &path, &mut value)?;
+                        dynamic.insert_boxed(field);
                     }
 
                     default.apply(dynamic.as_reflect());
@@ -267,16 +421,11 @@ fn deserialize_gltf_field(
                     let mut dynamic = DynamicList::default();
                     dynamic.set_name(type_name);
 
-                    let object = if let Some(Value::Object(object)) = component.remove("list") {
-                        object
-                    } else {
-                        panic!("list field is not an object");
-                    };
-
-                    for (_, mut value) in object.into_iter() {
-                        if let Some(field) = deserialize_gltf_field(&type_registry, &mut value) {
-                            dynamic.push_box(field);
-                        }
+                    let items = take_indexed(node, path, component, "list")?;
+                    for (index, mut value) in items.into_iter().enumerate() {
+                        let path = format!("{path:}.list.{index:}");
+                        let field = deserialize_gltf_field(type_registry, node, &path, &mut value)?;
+                        dynamic.push_box(field);
                     }
 
                     default.apply(dynamic.as_reflect());
@@ -285,16 +434,10 @@ fn deserialize_gltf_field(
                     let mut dynamic = DynamicMap::default();
                     dynamic.set_name(type_name);
 
-                    let object = if let Some(Value::Object(object)) = component.remove("map") {
-                        object
-                    } else {
-                        panic!("map field is not an object");
-                    };
-
-                    for (key, mut value) in object.into_iter() {
-                        if let Some(field) = deserialize_gltf_field(&type_registry, &mut value) {
-                            dynamic.insert_boxed(Box::new(key), field);
-                        }
+                    for (key, mut value) in take_object(node, path, component, "map")? {
+                        let path = format!("{path:}.map.{key:}");
+                        let field = deserialize_gltf_field(type_registry, node, &path, &mut value)?;
+                        dynamic.insert_boxed(Box::new(key), field);
                     }
 
                     default.apply(dynamic.as_reflect());
@@ -303,88 +446,136 @@ fn deserialize_gltf_field(
                 _ => (),
             };
 
-            Some(default)
+            Ok(default)
         }
     }
 }
 
 /// Recursively traverse a JSON Value, invoking the supplied callback at each field
-fn walk_json<F>(value: &mut Value, callback: &mut F)
+/// after its children have been visited
+fn walk_json_post<F>(value: &mut Value, callback: &mut F)
 where
     F: FnMut(&mut Value),
 {
+    match value {
+        Value::Array(array) => {
+            for value in array.iter_mut() {
+                walk_json_post(value, callback);
+            }
+        }
+        Value::Object(object) => {
+            for (_, value) in object.iter_mut() {
+                walk_json_post(value, callback)
+            }
+        }
+        _ => (),
+    }
+
     callback(value);
+}
+
+/// Recursively traverse a JSON Value, invoking the supplied callback at each field
+/// with its path, and stopping at the first error
+fn walk_json<F>(value: &mut Value, path: &str, callback: &mut F) -> Result<(), ExtrasError>
+where
+    F: FnMut(&str, &mut Value) -> Result<(), ExtrasError>,
+{
+    callback(path, value)?;
 
     match value {
         Value::Array(array) => {
-            for value in array {
-                walk_json(value, callback);
+            for (index, value) in array.iter_mut().enumerate() {
+                walk_json(value, &format!("{path:}.{index:}"), callback)?;
             }
         }
         Value::Object(object) => {
-            for (_, value) in object {
-                walk_json(value, callback)
+            for (key, value) in object.iter_mut() {
+                walk_json(value, &format!("{path:}.{key:}"), callback)?;
             }
         }
         _ => (),
     }
+
+    Ok(())
 }
 
-fn fixup_bool(type_registry: &TypeRegistry, value: &mut Value) {
+fn fixup_bool(
+    type_registry: &TypeRegistry,
+    node: &str,
+    path: &str,
+    value: &mut Value,
+) -> Result<(), ExtrasError> {
     let object = if let Value::Object(object) = value {
         object
     } else {
-        return;
+        return Ok(());
     };
 
     let type_name = if let Some(Value::String(type_name)) = object.get("type") {
         type_name
     } else {
-        return;
+        return Ok(());
     };
 
     let type_registry = type_registry.read();
     let registration = if let Some(registration) = type_registry.get_with_name(type_name) {
         registration
     } else {
-        return;
+        return Ok(());
     };
 
     if registration.data::<ReflectBoolean>().is_none() {
-        return;
+        return Ok(());
     }
 
-    if let Some(Value::Number(number)) = object.remove("value") {
-        object.insert(
-            "value".to_string(),
-            Value::Bool(number.as_u64().expect("Bool representation is non-integer") > 0),
-        );
-    }
+    let flag = if let Some(Value::Number(number)) = object.get("value") {
+        number.as_u64().ok_or_else(|| {
+            extras_error(
+                node,
+                path,
+                ExtrasErrorKind::InvalidValue {
+                    expected: "integer".to_string(),
+                    found: number.to_string(),
+                },
+            )
+        })? > 0
+    } else {
+        return Ok(());
+    };
+
+    object.insert("value".to_string(), Value::Bool(flag));
+
+    Ok(())
 }
 
-fn fixup_entity(world: &mut World, value: &mut Value) {
+fn fixup_entity(
+    world: &mut World,
+    node: &str,
+    path: &str,
+    value: &mut Value,
+) -> Result<(), ExtrasError> {
     let object = if let Value::Object(object) = value {
         object
     } else {
-        return;
+        return Ok(());
     };
 
     if let Some(Value::String(type_name)) = object.get("type") {
         if type_name != std::any::type_name::<Entity>() {
-            return;
+            return Ok(());
         }
     }
 
     let mut entity_value = if let Some(Value::Object(object)) = object.remove("value") {
         object
     } else {
-        return;
+        return Ok(());
     };
 
     let entity_name = if let Some(Value::String(name)) = entity_value.remove("name") {
         name
     } else {
-        return;
+        return Ok(());
     };
 
     info!("Fixing up entity for object {object:#?}");
@@ -401,53 +592,71 @@ fn fixup_entity(world: &mut World, value: &mut Value) {
                 None
             }
         })
-        .unwrap_or_else(|| panic!("No entity with name {entity_name:}"));
+        .ok_or_else(|| extras_error(node, path, ExtrasErrorKind::UnknownEntity(entity_name)))?;
 
-    let serialized = serde_json::to_value(&entity).unwrap();
+    let serialized = serde_json::to_value(&entity)
+        .map_err(|e| extras_error(node, path, ExtrasErrorKind::Deserialize(e.to_string())))?;
     object.insert("value".to_string(), serialized);
+
+    Ok(())
 }
 
-fn fixup_enum(type_registry: &TypeRegistry, value: &mut Value) {
+fn fixup_enum(
+    type_registry: &TypeRegistry,
+    node: &str,
+    path: &str,
+    value: &mut Value,
+) -> Result<(), ExtrasError> {
     let object = if let Value::Object(object) = value {
         object
     } else {
-        return;
+        return Ok(());
     };
 
     let type_name = if let Some(Value::String(type_name)) = object.get("type") {
         type_name
     } else {
-        return;
+        return Ok(());
     };
 
     let type_registry = type_registry.read();
     let registration = if let Some(registration) = type_registry.get_with_name(type_name) {
         registration
     } else {
-        return;
+        return Ok(());
     };
 
     let enumeration = if let Some(enumeration) = registration.data::<ReflectEnum>() {
         enumeration
     } else {
-        return;
+        return Ok(());
     };
 
-    if let Some((key, Value::Number(number))) = object.remove_entry("value") {
-        object.insert(
-            key,
-            Value::String(
-                enumeration
-                    .variants()
-                    .nth(number.as_u64().unwrap() as usize)
-                    .unwrap()
-                    .to_string(),
-            ),
-        );
+    // Convert variant indices to names, leaving variants authored by name untouched
+    if let Some(Value::Number(number)) = object.get("value") {
+        let variant = number
+            .as_u64()
+            .and_then(|index| enumeration.variants().nth(index as usize))
+            .ok_or_else(|| {
+                extras_error(
+                    node,
+                    path,
+                    ExtrasErrorKind::UnknownVariant(number.to_string()),
+                )
+            })?
+            .to_string();
+
+        object.insert("value".to_string(), Value::String(variant));
     }
+
+    Ok(())
 }
 
-fn fixup_option(type_registry: &TypeRegistry, value: &mut Value) {
+/// Fold the tuple or struct payload of a data-carrying enum variant
+/// into the externally-tagged serde representation expected by its deserializer
+///
+/// Runs after the other fixups, so payload fields are already in their final typed form
+fn fixup_enum_payload(type_registry: &TypeRegistry, value: &mut Value) {
     let object = if let Value::Object(object) = value {
         object
     } else {
@@ -467,136 +676,412 @@ fn fixup_option(type_registry: &TypeRegistry, value: &mut Value) {
         return;
     };
 
-    if registration.data::<ReflectOption>().is_none() {
+    let enumeration = if let Some(enumeration) = registration.data::<ReflectEnum>() {
+        enumeration
+    } else {
+        return;
+    };
+
+    let variant = if let Some(Value::String(variant)) = object.get("value") {
+        variant.clone()
+    } else {
         return;
+    };
+
+    let payload = match enumeration.variant_fields(&variant) {
+        Some(EnumVariantFields::Tuple(fields)) => {
+            let items = object
+                .remove("tuple")
+                .map(|tuple| indexed_json(&tuple))
+                .unwrap_or_default()
+                .iter()
+                .map(typed_to_serde)
+                .collect::<Vec<_>>();
+
+            // Newtype variants serialize as their inner value
+            if fields.len() == 1 {
+                items.into_iter().next().unwrap_or(Value::Null)
+            } else {
+                Value::Array(items)
+            }
+        }
+        Some(EnumVariantFields::Struct(_)) => match object.remove("struct") {
+            Some(Value::Object(fields)) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), typed_to_serde(value)))
+                    .collect(),
+            ),
+            _ => Value::Object(default()),
+        },
+        None => return,
+    };
+
+    object.insert(
+        "value".to_string(),
+        Value::Object([(variant, payload)].into_iter().collect()),
+    );
+}
+
+/// Collect the values of an index-keyed JSON object (or array) in index order
+fn indexed_json(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Array(array) => array.clone(),
+        Value::Object(object) => object
+            .iter()
+            .filter_map(|(key, value)| Some((key.parse::<usize>().ok()?, value.clone())))
+            .collect::<BTreeMap<_, _>>()
+            .into_values()
+            .collect(),
+        _ => vec![],
     }
+}
 
-    if let Some(Value::Object(mut obj)) = object.remove("value") {
-        let val = obj.remove("value").unwrap();
-        object.insert("value".to_string(), val);
+/// Strip the type annotations from a fixed-up field, producing its plain serde representation
+fn typed_to_serde(value: &Value) -> Value {
+    let object = if let Value::Object(object) = value {
+        object
+    } else {
+        return value.clone();
+    };
+
+    if let Some(value) = object.get("value") {
+        return value.clone();
     }
+
+    if let Some(Value::Object(fields)) = object.get("struct") {
+        return Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), typed_to_serde(value)))
+                .collect(),
+        );
+    }
+
+    if let Some(Value::Object(map)) = object.get("map") {
+        return Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), typed_to_serde(value)))
+                .collect(),
+        );
+    }
+
+    for key in ["tuple_struct", "tuple", "list", "array"] {
+        let items = if let Some(items) = object.get(key) {
+            indexed_json(items)
+        } else {
+            continue;
+        };
+
+        let mut items = items.iter().map(typed_to_serde).collect::<Vec<_>>();
+
+        // Newtype structs serialize as their inner value
+        if key == "tuple_struct" && items.len() == 1 {
+            return items.remove(0);
+        }
+
+        return Value::Array(items);
+    }
+
+    Value::Null
 }
 
-fn fixup_tuple_types(type_registry: &TypeRegistry, value: &mut Value) {
+fn fixup_option(
+    type_registry: &TypeRegistry,
+    node: &str,
+    path: &str,
+    value: &mut Value,
+) -> Result<(), ExtrasError> {
     let object = if let Value::Object(object) = value {
         object
     } else {
-        return;
+        return Ok(());
     };
 
     let type_name = if let Some(Value::String(type_name)) = object.get("type") {
         type_name
     } else {
-        return;
+        return Ok(());
     };
 
     let type_registry = type_registry.read();
     let registration = if let Some(registration) = type_registry.get_with_name(type_name) {
         registration
     } else {
-        return;
+        return Ok(());
     };
 
-    if let TypeInfo::Tuple(_) = registration.type_info() {
-        let mut tuple = object.remove("tuple").unwrap();
-        fixup_tuple(&mut tuple);
-        object.insert("tuple".to_string(), tuple);
+    if registration.data::<ReflectOption>().is_none() {
+        return Ok(());
     }
 
-    if let TypeInfo::TupleStruct(_) = registration.type_info() {
-        let mut tuple_struct = object.remove("tuple_struct").unwrap();
-        fixup_tuple(&mut tuple_struct);
-        object.insert("tuple_struct".to_string(), tuple_struct);
+    if let Some(Value::Object(mut obj)) = object.remove("value") {
+        let val = obj.remove("value").ok_or_else(|| {
+            extras_error(
+                node,
+                &format!("{path:}.value"),
+                ExtrasErrorKind::MissingKey("value".to_string()),
+            )
+        })?;
+        object.insert("value".to_string(), val);
     }
+
+    Ok(())
 }
 
-fn fixup_tuple(tuple: &mut Value) {
-    println!("Tuple value: {tuple:#?}");
-    let object = if let Value::Object(object) = tuple {
+fn fixup_tuple_types(
+    type_registry: &TypeRegistry,
+    node: &str,
+    path: &str,
+    value: &mut Value,
+) -> Result<(), ExtrasError> {
+    let object = if let Value::Object(object) = value {
         object
     } else {
-        panic!("tuple_struct field is not an object");
+        return Ok(());
     };
 
-    *tuple = Value::Array(
-        object
-            .iter()
-            .map(|(key, value)| {
-                let i: usize = key
-                    .parse()
-                    .unwrap_or_else(|_| panic!("tuple_struct key {key:?} is not numeric"));
-                let value = value.clone();
-                (i, value)
-            })
-            .collect::<BTreeMap<_, _>>()
-            .into_values()
-            .collect::<Vec<_>>(),
-    )
+    let type_name = if let Some(Value::String(type_name)) = object.get("type") {
+        type_name
+    } else {
+        return Ok(());
+    };
+
+    let type_registry = type_registry.read();
+    let registration = if let Some(registration) = type_registry.get_with_name(type_name) {
+        registration
+    } else {
+        return Ok(());
+    };
+
+    let key = match registration.type_info() {
+        TypeInfo::Tuple(_) => "tuple",
+        TypeInfo::TupleStruct(_) => "tuple_struct",
+        _ => return Ok(()),
+    };
+
+    let mut tuple = object
+        .remove(key)
+        .ok_or_else(|| extras_error(node, path, ExtrasErrorKind::MissingKey(key.to_string())))?;
+    fixup_tuple(node, &format!("{path:}.{key:}"), &mut tuple)?;
+    object.insert(key.to_string(), tuple);
+
+    Ok(())
+}
+
+fn fixup_tuple(node: &str, path: &str, tuple: &mut Value) -> Result<(), ExtrasError> {
+    let items = expect_object(node, path, tuple)?
+        .iter()
+        .map(|(key, value)| {
+            let i: usize = key.parse().map_err(|_| {
+                extras_error(node, path, ExtrasErrorKind::InvalidIndex(key.clone()))
+            })?;
+            Ok((i, value.clone()))
+        })
+        .collect::<Result<BTreeMap<_, _>, ExtrasError>>()?;
+
+    *tuple = Value::Array(items.into_values().collect());
+
+    Ok(())
 }
 
-fn fixup_map(type_registry: &TypeRegistry, value: &mut Value) {
+/// Unwrap the value of a typed map key or value entry
+fn map_entry(node: &str, path: &str, entry: Value) -> Result<Value, ExtrasError> {
+    match entry {
+        Value::Object(mut entry) => entry
+            .remove("value")
+            .ok_or_else(|| extras_error(node, path, ExtrasErrorKind::MissingKey("value".into()))),
+        other => Err(extras_error(
+            node,
+            path,
+            ExtrasErrorKind::InvalidValue {
+                expected: "object".to_string(),
+                found: json_kind(&other),
+            },
+        )),
+    }
+}
+
+fn fixup_map(
+    type_registry: &TypeRegistry,
+    node: &str,
+    path: &str,
+    value: &mut Value,
+) -> Result<(), ExtrasError> {
     let object = if let Value::Object(object) = value {
         object
     } else {
-        return;
+        return Ok(());
     };
 
     let type_name = if let Some(Value::String(type_name)) = object.get("type") {
         type_name
     } else {
-        return;
+        return Ok(());
     };
 
     let type_registry = type_registry.read();
     let registration = if let Some(registration) = type_registry.get_with_name(type_name) {
         registration
     } else {
-        return;
+        return Ok(());
     };
 
     // Fixup maps
     let info = if let TypeInfo::Map(info) = registration.type_info() {
         info
     } else {
-        return;
+        return Ok(());
     };
 
-    match (object.remove("keys"), object.remove("values")) {
-        (Some(Value::Array(keys)), Some(Value::Array(values))) => {
-            object.insert(
-                "map".to_string(),
-                Value::Object(
-                    keys.into_iter()
-                        .zip(values.into_iter())
-                        .map(|(key, value)| {
-                            let (key, value) = match (key, value) {
-                                (Value::Object(mut key), Value::Object(mut value)) => {
-                                    (key.remove("value").unwrap(), value.remove("value").unwrap())
-                                }
-                                _ => panic!("Unexpected map format"),
-                            };
-
-                            (
-                                key.as_str().expect("Key must be String").to_string(),
-                                Value::Object(
-                                    [
-                                        (
-                                            "type".to_string(),
-                                            Value::String(info.value_type_name().to_string()),
-                                        ),
-                                        ("value".to_string(), value),
-                                    ]
-                                    .into_iter()
-                                    .collect(),
-                                ),
-                            )
-                        })
-                        .collect(),
-                ),
-            );
-        }
+    let (keys, values) = match (object.remove("keys"), object.remove("values")) {
+        (Some(Value::Array(keys)), Some(Value::Array(values))) => (keys, values),
         _ => {
             object.insert("value".to_string(), Value::Object(default()));
+            return Ok(());
         }
+    };
+
+    let map = keys
+        .into_iter()
+        .zip(values.into_iter())
+        .enumerate()
+        .map(|(index, (key, value))| {
+            let key_path = format!("{path:}.keys.{index:}");
+            let key = match map_entry(node, &key_path, key)? {
+                Value::String(key) => key,
+                other => {
+                    return Err(extras_error(
+                        node,
+                        &key_path,
+                        ExtrasErrorKind::InvalidValue {
+                            expected: "string".to_string(),
+                            found: json_kind(&other),
+                        },
+                    ))
+                }
+            };
+
+            let value = map_entry(node, &format!("{path:}.values.{index:}"), value)?;
+
+            Ok((
+                key,
+                Value::Object(
+                    [
+                        (
+                            "type".to_string(),
+                            Value::String(info.value_type_name().to_string()),
+                        ),
+                        ("value".to_string(), value),
+                    ]
+                    .into_iter()
+                    .collect(),
+                ),
+            ))
+        })
+        .collect::<Result<Map<_, _>, ExtrasError>>()?;
+
+    object.insert("map".to_string(), Value::Object(map));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Component;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, Default, Clone, Component, Reflect)]
+    #[reflect(Component, Default)]
+    struct Flagged {
+        flag: bool,
+    }
+
+    fn deserialize(mut component: Value) -> (Scene, Entity, Result<(), ExtrasError>) {
+        let type_registry = TypeRegistry::default();
+        {
+            let mut type_registry = type_registry.write();
+            type_registry.register::<bool>();
+            type_registry.register_type_data::<bool, ReflectBoolean>();
+            type_registry.register::<Flagged>();
+        }
+
+        let mut scene = Scene::new(World::new());
+        let entity = scene.world.spawn().id();
+
+        let result = deserialize_gltf_component(
+            &mut scene,
+            entity,
+            "Node",
+            "entity.Flagged",
+            &type_registry,
+            &mut component,
+        );
+
+        (scene, entity, result)
+    }
+
+    #[test]
+    fn valid_component_is_inserted() {
+        let (scene, entity, result) = deserialize(json!({
+            "type": std::any::type_name::<Flagged>(),
+            "struct": { "flag": { "type": "bool", "value": 1 } },
+        }));
+
+        assert_eq!(result, Ok(()));
+        assert!(scene.world.get::<Flagged>(entity).unwrap().flag);
+    }
+
+    #[test]
+    fn missing_type_is_an_error() {
+        let (scene, entity, result) = deserialize(json!({ "struct": {} }));
+
+        assert_eq!(result.unwrap_err().kind, ExtrasErrorKind::MissingType);
+        assert!(scene.world.get::<Flagged>(entity).is_none());
+    }
+
+    #[test]
+    fn unknown_type_is_an_error() {
+        let (_, _, result) = deserialize(json!({ "type": "Missing", "struct": {} }));
+
+        assert_eq!(
+            result.unwrap_err().kind,
+            ExtrasErrorKind::UnknownType("Missing".to_string())
+        );
+    }
+
+    #[test]
+    fn malformed_field_reports_its_path() {
+        let (scene, entity, result) = deserialize(json!({
+            "type": std::any::type_name::<Flagged>(),
+            "struct": { "flag": { "type": "bool", "value": 0.5 } },
+        }));
+
+        let error = result.unwrap_err();
+        assert_eq!(error.node, "Node");
+        assert_eq!(error.path, "entity.Flagged.struct.flag");
+        assert_eq!(
+            error.kind,
+            ExtrasErrorKind::InvalidValue {
+                expected: "integer".to_string(),
+                found: "0.5".to_string(),
+            }
+        );
+        assert!(scene.world.get::<Flagged>(entity).is_none());
+    }
+
+    #[test]
+    fn unknown_entity_reference_is_an_error() {
+        let (_, _, result) = deserialize(json!({
+            "type": std::any::type_name::<Entity>(),
+            "value": { "name": "Missing" },
+        }));
+
+        assert_eq!(
+            result.unwrap_err().kind,
+            ExtrasErrorKind::UnknownEntity("Missing".to_string())
+        );
     }
 }
@@ -1,10 +1,15 @@
-use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::Path,
+};
 
 use bevy::{
+    asset::HandleId,
     gltf::{Gltf, GltfExtras},
     prelude::{
-        AssetEvent, Assets, Component, CoreStage, Deref, DerefMut, Entity, EventReader,
-        ParallelSystemDescriptorCoercion, Plugin, ResMut, With,
+        AssetEvent, AssetServer, Assets, Component, CoreStage, Deref, DerefMut, Entity,
+        EventReader, EventWriter, Handle, ParallelSystemDescriptorCoercion, Plugin, Res, ResMut,
+        With,
     },
     scene::Scene,
 };
@@ -13,11 +18,51 @@ pub struct GltfJsonPlugin;
 
 impl Plugin for GltfJsonPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<GltfScenesReady>()
+            .add_event::<GltfEntitiesReady>();
+
         app.add_system_to_stage(CoreStage::Last, gltf_json_insert)
-            .add_system_to_stage(CoreStage::Last, gltf_json_remove.after(gltf_json_insert));
+            .add_system_to_stage(CoreStage::Last, gltf_json_remove.after(gltf_json_insert))
+            .add_system_to_stage(CoreStage::Last, gltf_entities_ready.after(gltf_json_remove));
+    }
+}
+
+/// Sent once a Gltf asset's scenes have had their extras fully deserialized
+#[derive(Debug, Clone)]
+pub struct GltfEntitiesReady {
+    pub gltf: Handle<Gltf>,
+    pub scenes: Vec<Handle<Scene>>,
+}
+
+/// The set of Gltf scenes whose extras have been fully deserialized
+#[derive(Debug, Default, Clone)]
+pub struct GltfScenesReady {
+    pub scenes: HashSet<HandleId>,
+}
+
+impl GltfScenesReady {
+    /// Returns true if the given scene can be spawned with all of its components present
+    ///
+    /// Scenes that don't originate from a Gltf file have no extras to wait on
+    pub fn is_ready(&self, asset_server: &AssetServer, handle: &Handle<Scene>) -> bool {
+        if self.scenes.contains(&handle.id) {
+            return true;
+        }
+
+        asset_server
+            .get_handle_path(handle)
+            .map(|path| !is_gltf_path(path.path()))
+            .unwrap_or(true)
     }
 }
 
+fn is_gltf_path(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("gltf" | "glb")
+    )
+}
+
 #[derive(Debug, Default, Clone, Component, Deref, DerefMut)]
 pub struct GltfExtrasJson(pub serde_json::Value);
 
@@ -96,3 +141,39 @@ pub fn gltf_json_remove(
         }
     }
 }
+
+/// Once extras have been consumed and removed, mark a Gltf asset's scenes as ready for spawning
+pub fn gltf_entities_ready(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    gltf_assets: Res<Assets<Gltf>>,
+    mut scenes_ready: ResMut<GltfScenesReady>,
+    mut ready_events: EventWriter<GltfEntitiesReady>,
+) {
+    for event in gltf_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                let gltf = if let Some(gltf) = gltf_assets.get(handle) {
+                    gltf
+                } else {
+                    continue;
+                };
+
+                scenes_ready
+                    .scenes
+                    .extend(gltf.scenes.iter().map(|scene| scene.id));
+
+                ready_events.send(GltfEntitiesReady {
+                    gltf: handle.clone_weak(),
+                    scenes: gltf.scenes.iter().map(Handle::clone_weak).collect(),
+                });
+            }
+            AssetEvent::Removed { handle } => {
+                if let Some(gltf) = gltf_assets.get(handle) {
+                    for scene in gltf.scenes.iter() {
+                        scenes_ready.scenes.remove(&scene.id);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,593 @@
+//! Rebindable input mappings with RON persistence and an egui rebinding widget
+
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    hash::Hash,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    prelude::{
+        info, warn, Axis, Gamepad, GamepadAxis, GamepadAxisType, GamepadButton,
+        GamepadButtonType, Gamepads, Input, KeyCode, Plugin, Res, World,
+    },
+    utils::HashMap,
+};
+use bevy_inspector_egui::egui::{DragValue, Response, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    PlayerInput, PlayerInputMap, PlayerInputMaps, TimelineInput, TimelineInputMap, Widget, Widgets,
+};
+
+/// Default name of the bindings file, stored alongside the executable
+pub const INPUT_BINDINGS_FILE: &str = "bindings.ron";
+
+/// Axis magnitude beyond which a stick movement is captured as a binding
+const CAPTURE_THRESHOLD: f32 = 0.75;
+
+pub struct InputBindingsPlugin {
+    pub path: PathBuf,
+    /// Use the two-player co-op maps in place of any saved player bindings
+    pub coop: bool,
+}
+
+impl Default for InputBindingsPlugin {
+    fn default() -> Self {
+        Self {
+            path: input_bindings_path(),
+            coop: false,
+        }
+    }
+}
+
+impl Plugin for InputBindingsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        if let Some(bindings) = InputBindingsFile::load(&self.path) {
+            info!("Loaded input bindings from {:?}", self.path);
+            app.insert_resource(PlayerInputMaps {
+                maps: bindings.players,
+            })
+            .insert_resource(bindings.timeline);
+        }
+
+        if self.coop {
+            info!("Using co-op player bindings");
+            app.insert_resource(PlayerInputMaps::coop());
+        }
+
+        app.insert_resource(InputBindingsPath(self.path.clone()));
+
+        app.add_startup_system(setup_input_bindings_widget);
+    }
+}
+
+/// Location that bindings are loaded from and saved to
+#[derive(Debug, Clone)]
+pub struct InputBindingsPath(pub PathBuf);
+
+/// Path to the bindings file next to the running executable
+pub fn input_bindings_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+        .join(INPUT_BINDINGS_FILE)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+impl AxisDirection {
+    pub fn sign(&self) -> f32 {
+        match self {
+            AxisDirection::Positive => 1.0,
+            AxisDirection::Negative => -1.0,
+        }
+    }
+}
+
+/// A physical input that can trigger an action
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+    GamepadAxis {
+        axis: GamepadAxisType,
+        direction: AxisDirection,
+        deadzone: f32,
+    },
+}
+
+impl InputBinding {
+    pub fn axis(axis: GamepadAxisType, direction: AxisDirection) -> Self {
+        InputBinding::GamepadAxis {
+            axis,
+            direction,
+            deadzone: 0.5,
+        }
+    }
+
+    /// Whether this binding is currently held, optionally restricted to a single gamepad
+    pub fn active(&self, state: &InputState, gamepad: Option<Gamepad>) -> bool {
+        match self {
+            InputBinding::Key(key_code) => state.keys.pressed(*key_code),
+            InputBinding::GamepadButton(button_type) => state
+                .gamepads(gamepad)
+                .any(|gamepad| state.buttons.pressed(GamepadButton::new(gamepad, *button_type))),
+            InputBinding::GamepadAxis {
+                axis,
+                direction,
+                deadzone,
+            } => state.gamepads(gamepad).any(|gamepad| {
+                state
+                    .axes
+                    .get(GamepadAxis::new(gamepad, *axis))
+                    .map(|value| value * direction.sign() > *deadzone)
+                    .unwrap_or_default()
+            }),
+        }
+    }
+
+    /// Analog strength of this binding in the 0..1 range; keys and buttons are either 0 or 1
+    pub fn value(&self, state: &InputState, gamepad: Option<Gamepad>) -> f32 {
+        match self {
+            InputBinding::Key(_) | InputBinding::GamepadButton(_) => {
+                self.active(state, gamepad) as usize as f32
+            }
+            InputBinding::GamepadAxis {
+                axis,
+                direction,
+                deadzone,
+            } => state
+                .gamepads(gamepad)
+                .filter_map(|gamepad| state.axes.get(GamepadAxis::new(gamepad, *axis)))
+                .map(|value| InputBinding::axis_value(value, *direction, *deadzone))
+                .fold(0.0, f32::max),
+        }
+    }
+
+    /// Rescale a raw axis position along a direction so the deadzone edge reads 0 and full
+    /// deflection reads 1
+    pub fn axis_value(value: f32, direction: AxisDirection, deadzone: f32) -> f32 {
+        let deadzone = deadzone.clamp(0.0, 0.99);
+        ((value * direction.sign() - deadzone) / (1.0 - deadzone)).clamp(0.0, 1.0)
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            InputBinding::Key(key_code) => format!("{key_code:?}"),
+            InputBinding::GamepadButton(button_type) => format!("{button_type:?}"),
+            InputBinding::GamepadAxis {
+                axis, direction, ..
+            } => format!(
+                "{axis:?}{}",
+                match direction {
+                    AxisDirection::Positive => "+",
+                    AxisDirection::Negative => "-",
+                }
+            ),
+        }
+    }
+}
+
+/// Borrowed view over raw bevy input resources
+pub struct InputState<'a> {
+    pub keys: &'a Input<KeyCode>,
+    pub buttons: &'a Input<GamepadButton>,
+    pub axes: &'a Axis<GamepadAxis>,
+    pub gamepads: &'a Gamepads,
+}
+
+impl<'a> InputState<'a> {
+    fn gamepads(&self, gamepad: Option<Gamepad>) -> impl Iterator<Item = Gamepad> + '_ {
+        self.gamepads
+            .iter()
+            .copied()
+            .filter(move |candidate| gamepad.map(|gamepad| gamepad == *candidate).unwrap_or(true))
+    }
+
+    /// First input newly pressed this frame, if any
+    pub fn capture(&self, gamepad: Option<Gamepad>) -> Option<InputBinding> {
+        if let Some(key_code) = self.keys.get_just_pressed().next() {
+            return Some(InputBinding::Key(*key_code));
+        }
+
+        if let Some(button) = self
+            .buttons
+            .get_just_pressed()
+            .find(|button| gamepad.map(|gamepad| gamepad == button.gamepad).unwrap_or(true))
+        {
+            return Some(InputBinding::GamepadButton(button.button_type));
+        }
+
+        for gamepad in self.gamepads(gamepad) {
+            for axis in [
+                GamepadAxisType::LeftStickX,
+                GamepadAxisType::LeftStickY,
+                GamepadAxisType::LeftZ,
+                GamepadAxisType::RightStickX,
+                GamepadAxisType::RightStickY,
+                GamepadAxisType::RightZ,
+            ] {
+                let value = if let Some(value) = self.axes.get(GamepadAxis::new(gamepad, axis)) {
+                    value
+                } else {
+                    continue;
+                };
+
+                if value.abs() > CAPTURE_THRESHOLD {
+                    return Some(InputBinding::axis(
+                        axis,
+                        if value > 0.0 {
+                            AxisDirection::Positive
+                        } else {
+                            AxisDirection::Negative
+                        },
+                    ));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Set of bindings per action, any of which will trigger it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputBindings<T>
+where
+    T: Ord,
+{
+    pub bindings: BTreeMap<T, Vec<InputBinding>>,
+}
+
+impl<T> Default for InputBindings<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self {
+            bindings: BTreeMap::default(),
+        }
+    }
+}
+
+impl<T> FromIterator<(T, InputBinding)> for InputBindings<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = (T, InputBinding)>>(iter: I) -> Self {
+        let mut bindings = InputBindings::default();
+        for (action, binding) in iter {
+            bindings.bind(action, binding);
+        }
+        bindings
+    }
+}
+
+impl<T> InputBindings<T>
+where
+    T: Ord,
+{
+    pub fn bind(&mut self, action: T, binding: InputBinding) {
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn unbind(&mut self, action: T, index: usize) {
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            if index < bindings.len() {
+                bindings.remove(index);
+            }
+        }
+    }
+}
+
+impl<T> InputBindings<T>
+where
+    T: 'static + Send + Sync + Copy + Eq + Ord + Hash,
+{
+    /// Press or release actions to match the current state of their bindings
+    pub fn update(&self, state: &InputState, gamepad: Option<Gamepad>, input: &mut Input<T>) {
+        for (action, bindings) in self.bindings.iter() {
+            let active = bindings.iter().any(|binding| binding.active(state, gamepad));
+
+            if active && !input.pressed(*action) {
+                input.press(*action);
+            } else if !active && input.pressed(*action) {
+                input.release(*action);
+            }
+        }
+    }
+
+    /// Store the strongest analog value among each action's bindings
+    ///
+    /// Actions with no active binding are left without a value,
+    /// so inputs pressed by other means read as fully pressed.
+    pub fn update_values(
+        &self,
+        state: &InputState,
+        gamepad: Option<Gamepad>,
+        values: &mut HashMap<T, f32>,
+    ) {
+        for (action, bindings) in self.bindings.iter() {
+            let value = bindings
+                .iter()
+                .map(|binding| binding.value(state, gamepad))
+                .fold(0.0, f32::max);
+
+            if value > 0.0 {
+                values.insert(*action, value);
+            } else {
+                values.remove(action);
+            }
+        }
+    }
+}
+
+/// On-disk representation of all rebindable input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputBindingsFile {
+    pub players: Vec<PlayerInputMap>,
+    pub timeline: TimelineInputMap,
+}
+
+impl InputBindingsFile {
+    pub fn load(path: &Path) -> Option<Self> {
+        let bindings = std::fs::read_to_string(path).ok()?;
+        match ron::from_str(&bindings) {
+            Ok(bindings) => Some(bindings),
+            Err(e) => {
+                warn!("Failed to parse input bindings {path:?}: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let bindings = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, bindings)?;
+        Ok(())
+    }
+
+    pub fn from_world(world: &World) -> Self {
+        InputBindingsFile {
+            players: world.resource::<PlayerInputMaps>().maps.clone(),
+            timeline: world.resource::<TimelineInputMap>().clone(),
+        }
+    }
+
+    pub fn apply(self, world: &mut World) {
+        world.resource_mut::<PlayerInputMaps>().maps = self.players;
+        *world.resource_mut::<TimelineInputMap>() = self.timeline;
+    }
+}
+
+/// Action awaiting a new binding
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BindingTarget {
+    Player(usize, PlayerInput),
+    Timeline(TimelineInput),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BindingRequest {
+    Save,
+    Load,
+}
+
+/// Egui widget for inspecting and rebinding input
+#[derive(Default)]
+pub struct InputBindingsWidget {
+    bindings: Option<InputBindingsFile>,
+    listening: Option<BindingTarget>,
+    request: Option<BindingRequest>,
+    dirty: bool,
+    status: String,
+}
+
+impl Widget for InputBindingsWidget {
+    fn update(&mut self, world: &mut World) {
+        if let (Some(target), Some(bindings)) = (self.listening, self.bindings.as_mut()) {
+            let state = InputState {
+                keys: world.resource::<Input<KeyCode>>(),
+                buttons: world.resource::<Input<GamepadButton>>(),
+                axes: world.resource::<Axis<GamepadAxis>>(),
+                gamepads: world.resource::<Gamepads>(),
+            };
+
+            let gamepad = match target {
+                BindingTarget::Player(i, _) => bindings
+                    .players
+                    .get(i)
+                    .and_then(|player| player.gamepad_id)
+                    .map(|id| Gamepad { id }),
+                BindingTarget::Timeline(_) => None,
+            };
+
+            if let Some(binding) = state.capture(gamepad) {
+                match target {
+                    BindingTarget::Player(i, action) => {
+                        if let Some(player) = bindings.players.get_mut(i) {
+                            player.bindings.bind(action, binding);
+                        }
+                    }
+                    BindingTarget::Timeline(action) => {
+                        bindings.timeline.bindings.bind(action, binding)
+                    }
+                }
+
+                self.listening = None;
+                self.dirty = true;
+            }
+        }
+
+        if self.dirty {
+            if let Some(bindings) = self.bindings.take() {
+                bindings.apply(world);
+            }
+            self.dirty = false;
+        }
+
+        let path = world.resource::<InputBindingsPath>().0.clone();
+
+        match self.request.take() {
+            Some(BindingRequest::Save) => {
+                self.status = match InputBindingsFile::from_world(world).save(&path) {
+                    Ok(()) => format!("Saved to {}", path.display()),
+                    Err(e) => format!("Failed to save: {e}"),
+                };
+            }
+            Some(BindingRequest::Load) => {
+                self.status = match InputBindingsFile::load(&path) {
+                    Some(bindings) => {
+                        bindings.apply(world);
+                        format!("Loaded from {}", path.display())
+                    }
+                    None => format!("No bindings at {}", path.display()),
+                };
+            }
+            None => (),
+        }
+
+        self.bindings = Some(InputBindingsFile::from_world(world));
+    }
+
+    fn title(&self) -> Cow<'static, str> {
+        Cow::Borrowed("Input Bindings")
+    }
+
+    fn ui(&mut self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            let bindings = if let Some(bindings) = self.bindings.as_mut() {
+                bindings
+            } else {
+                return;
+            };
+
+            for (i, player) in bindings.players.iter_mut().enumerate() {
+                ui.collapsing(format!("Player {}", player.player.0 + 1), |ui| {
+                    self.dirty |= bindings_ui(
+                        ui,
+                        &mut player.bindings,
+                        &mut self.listening,
+                        |action| BindingTarget::Player(i, action),
+                    );
+                });
+            }
+
+            ui.collapsing("Timeline", |ui| {
+                self.dirty |= bindings_ui(
+                    ui,
+                    &mut bindings.timeline.bindings,
+                    &mut self.listening,
+                    BindingTarget::Timeline,
+                );
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    self.request = Some(BindingRequest::Save);
+                }
+
+                if ui.button("Load").clicked() {
+                    self.request = Some(BindingRequest::Load);
+                }
+
+                if self.listening.is_some() && ui.button("Cancel").clicked() {
+                    self.listening = None;
+                }
+            });
+
+            if self.listening.is_some() {
+                ui.label("Press a key, button or stick direction...");
+            } else if !self.status.is_empty() {
+                ui.label(self.status.as_str());
+            }
+        })
+        .response
+    }
+}
+
+/// Draws the bindings for a set of actions, returning true if any were modified
+fn bindings_ui<T>(
+    ui: &mut Ui,
+    bindings: &mut InputBindings<T>,
+    listening: &mut Option<BindingTarget>,
+    target: impl Fn(T) -> BindingTarget,
+) -> bool
+where
+    T: 'static + Send + Sync + Copy + Eq + Ord + Hash + std::fmt::Debug,
+{
+    let mut dirty = false;
+    let mut unbind = None;
+
+    for (action, action_bindings) in bindings.bindings.iter_mut() {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("{action:?}"));
+
+            for (i, binding) in action_bindings.iter_mut().enumerate() {
+                if ui
+                    .button(binding.label())
+                    .on_hover_text("Click to remove")
+                    .clicked()
+                {
+                    unbind = Some((*action, i));
+                }
+
+                if let InputBinding::GamepadAxis { deadzone, .. } = binding {
+                    dirty |= ui
+                        .add(DragValue::new(deadzone).speed(0.01).clamp_range(0.0..=1.0))
+                        .on_hover_text("Deadzone")
+                        .changed();
+                }
+            }
+
+            let this_target = target(*action);
+            if *listening == Some(this_target) {
+                ui.label("...");
+            } else if ui.button("+").clicked() {
+                *listening = Some(this_target);
+            }
+        });
+    }
+
+    if let Some((action, i)) = unbind {
+        bindings.unbind(action, i);
+        dirty = true;
+    }
+
+    dirty
+}
+
+pub fn setup_input_bindings_widget(widgets: Res<Widgets>) {
+    widgets
+        .panel_outer_max
+        .add_widget(InputBindingsWidget::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axis_value_uses_binding_deadzone() {
+        assert_eq!(InputBinding::axis_value(0.5, AxisDirection::Positive, 0.5), 0.0);
+        assert_eq!(InputBinding::axis_value(0.75, AxisDirection::Positive, 0.5), 0.5);
+        assert_eq!(InputBinding::axis_value(1.0, AxisDirection::Positive, 0.5), 1.0);
+        assert!((InputBinding::axis_value(0.6, AxisDirection::Positive, 0.2) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn axis_value_follows_direction() {
+        assert_eq!(InputBinding::axis_value(-1.0, AxisDirection::Negative, 0.5), 1.0);
+        assert_eq!(InputBinding::axis_value(1.0, AxisDirection::Negative, 0.5), 0.0);
+    }
+}
@@ -0,0 +1,200 @@
+//! Migrations for glTF extras authored against an older version of the integration schema
+//!
+//! Migrations are idempotent, and applied to the raw Blender-side representation
+//! before validation and reflection deserialization
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::App;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A single schema change to a reflected type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Migration {
+    /// A struct field was renamed
+    RenameField { from: String, to: String },
+    /// A struct field was added; fill it with a typed value when absent
+    DefaultField { field: String, value: Value },
+    /// An enum variant was renamed
+    RenameVariant { from: String, to: String },
+}
+
+impl Migration {
+    fn apply(&self, object: &mut Map<String, Value>) {
+        match self {
+            Migration::RenameField { from, to } => {
+                if let Some(Value::Object(fields)) = object.get_mut("struct") {
+                    if fields.contains_key(to) {
+                        return;
+                    }
+
+                    if let Some(value) = fields.remove(from) {
+                        fields.insert(to.clone(), value);
+                    }
+                }
+            }
+            Migration::DefaultField { field, value } => {
+                if let Some(Value::Object(fields)) = object.get_mut("struct") {
+                    if !fields.contains_key(field) {
+                        fields.insert(field.clone(), value.clone());
+                    }
+                }
+            }
+            Migration::RenameVariant { from, to } => {
+                if let Some(Value::String(variant)) = object.get_mut("value") {
+                    if variant == from {
+                        *variant = to.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Registry of migrations, keyed by the full type name they apply to
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrationMigrations {
+    pub migrations: BTreeMap<String, Vec<Migration>>,
+}
+
+impl IntegrationMigrations {
+    pub fn register<T>(&mut self, migration: Migration) {
+        self.migrations
+            .entry(std::any::type_name::<T>().to_string())
+            .or_default()
+            .push(migration);
+    }
+
+    pub fn contains(&self, type_name: &str) -> bool {
+        self.migrations.contains_key(type_name)
+    }
+
+    /// Recursively migrate a typed value and any typed values nested inside it
+    pub fn migrate(&self, value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                let migrations = object
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .and_then(|type_name| self.migrations.get(type_name));
+
+                if let Some(migrations) = migrations {
+                    for migration in migrations {
+                        migration.apply(object);
+                    }
+                }
+
+                for (_, value) in object.iter_mut() {
+                    self.migrate(value);
+                }
+            }
+            Value::Array(array) => {
+                for value in array {
+                    self.migrate(value);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Extension trait for registering migrations against a reflected type
+///
+/// ExportIntegrationPlugin snapshots the registered migrations when it's built,
+/// so any migrations must be registered before that plugin is added
+pub trait RegisterIntegrationMigration {
+    fn register_integration_migration<T>(&mut self, migration: Migration) -> &mut Self
+    where
+        T: 'static;
+}
+
+impl RegisterIntegrationMigration for App {
+    fn register_integration_migration<T>(&mut self, migration: Migration) -> &mut Self
+    where
+        T: 'static,
+    {
+        self.world
+            .get_resource_or_insert_with(IntegrationMigrations::default)
+            .register::<T>(migration);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    struct Renamed;
+
+    fn migrations() -> IntegrationMigrations {
+        let mut migrations = IntegrationMigrations::default();
+        migrations.register::<Renamed>(Migration::RenameField {
+            from: "old".to_string(),
+            to: "new".to_string(),
+        });
+        migrations.register::<Renamed>(Migration::DefaultField {
+            field: "added".to_string(),
+            value: json!({ "type": "f32", "value": 0.0 }),
+        });
+        migrations.register::<Renamed>(Migration::RenameVariant {
+            from: "Before".to_string(),
+            to: "After".to_string(),
+        });
+        migrations
+    }
+
+    fn type_name() -> &'static str {
+        std::any::type_name::<Renamed>()
+    }
+
+    #[test]
+    fn migrate_renames_and_defaults_fields() {
+        let mut value = json!({
+            "type": type_name(),
+            "struct": { "old": { "type": "f32", "value": 1.0 } },
+        });
+        migrations().migrate(&mut value);
+
+        assert_eq!(
+            value,
+            json!({
+                "type": type_name(),
+                "struct": {
+                    "new": { "type": "f32", "value": 1.0 },
+                    "added": { "type": "f32", "value": 0.0 },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn migrate_renames_nested_variants() {
+        let mut value = json!({
+            "type": "Outer",
+            "list": [{ "type": type_name(), "value": "Before" }],
+        });
+        migrations().migrate(&mut value);
+
+        assert_eq!(value["list"][0]["value"], json!("After"));
+    }
+
+    #[test]
+    fn migrate_is_idempotent() {
+        let mut value = json!({
+            "type": type_name(),
+            "struct": {
+                "old": { "type": "f32", "value": 1.0 },
+                "new": { "type": "f32", "value": 2.0 },
+            },
+        });
+        migrations().migrate(&mut value);
+        let migrated = value.clone();
+        migrations().migrate(&mut value);
+
+        assert_eq!(value, migrated);
+        assert_eq!(value["struct"]["new"]["value"], json!(2.0));
+    }
+}
@@ -1,24 +1,29 @@
 //! Serialization-friendly version of bevy's reflection data model
 //! Used to export type information to external editors
 
+pub mod migration;
 pub mod reflect_boolean;
 pub mod reflect_bundle;
 pub mod reflect_float;
 pub mod reflect_signed_integer;
 pub mod reflect_string;
 pub mod reflect_unsigned_integer;
+pub mod validate;
 
 use bevy::{
     ecs::{entity::MapEntities, reflect::ReflectMapEntities},
-    prelude::{info, App, Bundle, Color, Plugin, ReflectComponent, ReflectDefault},
+    prelude::{
+        info, warn, App, Bundle, Color, Commands, Plugin, ReflectComponent, ReflectDefault, Res,
+    },
     reflect::{
         impl_reflect_value, serde::ReflectSerializer, ArrayInfo, FromType, ListInfo, MapInfo,
-        NamedField, TypeRegistry, UnnamedField,
+        NamedField, TypeRegistry, TypeRegistryInternal, UnnamedField,
     },
     utils::HashMap,
 };
 use serde::{Deserialize, Serialize};
 use std::{
+    any::type_name,
     borrow::Cow,
     collections::{BTreeMap, BTreeSet},
 };
@@ -33,12 +38,13 @@ use crate::{
         reflect_boolean::ReflectBoolean, reflect_float::ReflectFloat, reflect_string::ReflectString,
     },
     prelude::reflect_bundle::ReflectBundle,
-    shmup::collision_group::CollisionGroup,
     util::default_entity,
 };
 
 use self::{
-    reflect_signed_integer::ReflectSignedInteger, reflect_unsigned_integer::ReflectUnsignedInteger,
+    migration::{IntegrationMigrations, Migration, RegisterIntegrationMigration},
+    reflect_signed_integer::ReflectSignedInteger,
+    reflect_unsigned_integer::ReflectUnsignedInteger,
 };
 
 pub struct ExportIntegrationPlugin {
@@ -54,157 +60,402 @@ impl Default for ExportIntegrationPlugin {
 impl Plugin for ExportIntegrationPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         info!("Exporting Integration...");
-        let type_registry = &mut *app.world.resource::<TypeRegistry>().write();
+        let type_registry = app.world.resource::<TypeRegistry>().clone();
+        let type_registry = &mut *type_registry.write();
+
+        register_integration_primitives(type_registry);
+
+        let migrations = app
+            .world
+            .get_resource::<IntegrationMigrations>()
+            .cloned()
+            .unwrap_or_default();
+
+        let mut metadata = IntegrationMetadata::from_registry(type_registry, migrations);
+
+        metadata.version = match IntegrationMetadata::load(self.path) {
+            Ok(previous) => {
+                let changed = metadata.changed_types(&previous);
+                for type_name in changed.iter() {
+                    if !metadata.migrations.contains(type_name) {
+                        warn!("{type_name:} changed since the last export without a migration");
+                    }
+                }
+
+                if changed.is_empty() {
+                    previous.version
+                } else {
+                    previous.version + 1
+                }
+            }
+            Err(_) => 1,
+        };
+
+        info!("Integration schema version {}", metadata.version);
+
+        let out = serde_json::to_string_pretty(&metadata).unwrap();
+
+        println!("Writing to {}", self.path);
+        std::fs::write(self.path, out).expect("Failed to write type manifest");
+
+        app.insert_resource(metadata);
+    }
+}
+
+/// Register the reflection data the integration needs for primitive and std types
+pub fn register_integration_primitives(type_registry: &mut TypeRegistryInternal) {
+    type_registry.register_type_data::<bool, ReflectDefault>();
+    type_registry.register_type_data::<bool, ReflectBoolean>();
+
+    type_registry.register_type_data::<u8, ReflectDefault>();
+    type_registry.register_type_data::<u8, ReflectUnsignedInteger>();
+
+    type_registry.register_type_data::<u16, ReflectDefault>();
+    type_registry.register_type_data::<u16, ReflectUnsignedInteger>();
+
+    type_registry.register_type_data::<u32, ReflectDefault>();
+    type_registry.register_type_data::<u32, ReflectUnsignedInteger>();
+
+    type_registry.register_type_data::<u64, ReflectDefault>();
+    type_registry.register_type_data::<u64, ReflectUnsignedInteger>();
+
+    type_registry.register_type_data::<u128, ReflectIntegrationBlacklist>();
+
+    type_registry.register_type_data::<usize, ReflectDefault>();
+    type_registry.register_type_data::<usize, ReflectUnsignedInteger>();
+
+    type_registry.register_type_data::<i8, ReflectDefault>();
+    type_registry.register_type_data::<i8, ReflectSignedInteger>();
+
+    type_registry.register_type_data::<i16, ReflectDefault>();
+    type_registry.register_type_data::<i16, ReflectSignedInteger>();
+
+    type_registry.register_type_data::<i32, ReflectDefault>();
+    type_registry.register_type_data::<i32, ReflectSignedInteger>();
+
+    type_registry.register_type_data::<i64, ReflectDefault>();
+    type_registry.register_type_data::<i64, ReflectSignedInteger>();
+
+    type_registry.register_type_data::<i128, ReflectIntegrationBlacklist>();
+
+    type_registry.register_type_data::<isize, ReflectDefault>();
+    type_registry.register_type_data::<isize, ReflectSignedInteger>();
+
+    type_registry.register_type_data::<f32, ReflectDefault>();
+    type_registry.register_type_data::<f32, ReflectFloat>();
+
+    type_registry.register_type_data::<f64, ReflectDefault>();
+    type_registry.register_type_data::<f64, ReflectFloat>();
+
+    type_registry.register::<String>();
+    type_registry.register_type_data::<String, ReflectDefault>();
+    type_registry.register_type_data::<String, ReflectString>();
+
+    type_registry.register::<Cow<'static, str>>();
+    type_registry.register_type_data::<Cow<'static, str>, ReflectDefault>();
+    type_registry.register_type_data::<Cow<'static, str>, ReflectSerialize>();
+    type_registry.register_type_data::<Cow<'static, str>, ReflectDeserialize>();
+    type_registry.register_type_data::<Cow<'static, str>, ReflectString>();
+
+    type_registry.register::<Color>();
+    type_registry.register_type_data::<Color, ReflectDefault>();
+
+    type_registry.register::<Entity>();
+    type_registry.register_type_data::<Entity, ReflectEntity>();
+
+    type_registry.register::<Option<bool>>();
+    type_registry.register_type_data::<Option<bool>, ReflectDefault>();
+    type_registry.register_type_data::<Option<bool>, ReflectOption>();
+    type_registry.register_type_data::<Option<bool>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<bool>, ReflectDeserialize>();
+
+    type_registry.register::<Option<u8>>();
+    type_registry.register_type_data::<Option<u8>, ReflectDefault>();
+    type_registry.register_type_data::<Option<u8>, ReflectOption>();
+    type_registry.register_type_data::<Option<u8>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<u8>, ReflectDeserialize>();
+
+    type_registry.register::<Option<u16>>();
+    type_registry.register_type_data::<Option<u16>, ReflectDefault>();
+    type_registry.register_type_data::<Option<u16>, ReflectOption>();
+    type_registry.register_type_data::<Option<u16>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<u16>, ReflectDeserialize>();
+
+    type_registry.register::<Option<u32>>();
+    type_registry.register_type_data::<Option<u32>, ReflectDefault>();
+    type_registry.register_type_data::<Option<u32>, ReflectOption>();
+    type_registry.register_type_data::<Option<u32>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<u32>, ReflectDeserialize>();
+
+    type_registry.register::<Option<u64>>();
+    type_registry.register_type_data::<Option<u64>, ReflectDefault>();
+    type_registry.register_type_data::<Option<u64>, ReflectOption>();
+    type_registry.register_type_data::<Option<u64>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<u64>, ReflectDeserialize>();
+
+    type_registry.register::<Option<usize>>();
+    type_registry.register_type_data::<Option<usize>, ReflectDefault>();
+    type_registry.register_type_data::<Option<usize>, ReflectOption>();
+    type_registry.register_type_data::<Option<usize>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<usize>, ReflectDeserialize>();
+
+    type_registry.register::<Option<i8>>();
+    type_registry.register_type_data::<Option<i8>, ReflectDefault>();
+    type_registry.register_type_data::<Option<i8>, ReflectOption>();
+    type_registry.register_type_data::<Option<i8>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<i8>, ReflectDeserialize>();
+
+    type_registry.register::<Option<i16>>();
+    type_registry.register_type_data::<Option<i16>, ReflectDefault>();
+    type_registry.register_type_data::<Option<i16>, ReflectOption>();
+    type_registry.register_type_data::<Option<i16>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<i16>, ReflectDeserialize>();
+
+    type_registry.register::<Option<i32>>();
+    type_registry.register_type_data::<Option<i32>, ReflectDefault>();
+    type_registry.register_type_data::<Option<i32>, ReflectOption>();
+    type_registry.register_type_data::<Option<i32>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<i32>, ReflectDeserialize>();
+
+    type_registry.register::<Option<i64>>();
+    type_registry.register_type_data::<Option<i64>, ReflectDefault>();
+    type_registry.register_type_data::<Option<i64>, ReflectOption>();
+    type_registry.register_type_data::<Option<i64>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<i64>, ReflectDeserialize>();
+
+    type_registry.register::<Option<isize>>();
+    type_registry.register_type_data::<Option<isize>, ReflectDefault>();
+    type_registry.register_type_data::<Option<isize>, ReflectOption>();
+    type_registry.register_type_data::<Option<isize>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<isize>, ReflectDeserialize>();
+
+    type_registry.register::<Option<f32>>();
+    type_registry.register_type_data::<Option<f32>, ReflectDefault>();
+    type_registry.register_type_data::<Option<f32>, ReflectOption>();
+    type_registry.register_type_data::<Option<f32>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<f32>, ReflectDeserialize>();
+
+    type_registry.register::<Option<f64>>();
+    type_registry.register_type_data::<Option<f64>, ReflectDefault>();
+    type_registry.register_type_data::<Option<f64>, ReflectOption>();
+    type_registry.register_type_data::<Option<f64>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<f64>, ReflectDeserialize>();
+
+    type_registry.register::<Option<String>>();
+    type_registry.register_type_data::<Option<String>, ReflectDefault>();
+    type_registry.register_type_data::<Option<String>, ReflectOption>();
+    type_registry.register_type_data::<Option<String>, ReflectSerialize>();
+    type_registry.register_type_data::<Option<String>, ReflectDeserialize>();
+
+    type_registry.register::<Vec<String>>();
+    type_registry.register_type_data::<Vec<String>, ReflectDefault>();
+    type_registry.register_type_data::<Vec<String>, ReflectSerialize>();
+    type_registry.register_type_data::<Vec<String>, ReflectDeserialize>();
+
+    info!("Registered primitive types");
+}
+
+/// Load previously-exported metadata so glTF extras are validated without exporting
+///
+/// Runs that do export replace the loaded metadata with the fresh export,
+/// provided ExportIntegrationPlugin is added after this plugin.
+/// If there is no exported file, metadata is built from the type registry at startup instead.
+pub struct IntegrationMetadataPlugin {
+    pub path: &'static str,
+}
+
+impl Default for IntegrationMetadataPlugin {
+    fn default() -> Self {
+        Self { path: "types.json" }
+    }
+}
+
+impl Plugin for IntegrationMetadataPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        match IntegrationMetadata::load(self.path) {
+            Ok(metadata) => {
+                info!("Loaded integration schema version {}", metadata.version);
+                app.insert_resource(metadata);
+            }
+            Err(e) => {
+                warn!("No integration metadata, building it from the type registry: {e:?}");
+                app.add_startup_system(generate_integration_metadata);
+            }
+        }
+    }
+}
+
+/// Build metadata from the registry once every plugin has registered its types,
+/// unless an export already provided it
+fn generate_integration_metadata(
+    mut commands: Commands,
+    type_registry: Res<TypeRegistry>,
+    metadata: Option<Res<IntegrationMetadata>>,
+    migrations: Option<Res<IntegrationMigrations>>,
+) {
+    if metadata.is_some() {
+        return;
+    }
+
+    let type_registry = &mut *type_registry.write();
+    register_integration_primitives(type_registry);
+
+    let migrations = migrations.as_deref().cloned().unwrap_or_default();
+    let metadata = IntegrationMetadata::from_registry(type_registry, migrations);
+
+    info!("Built integration metadata from the type registry");
+    commands.insert_resource(metadata);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrationNamedField {
+    pub r#type: String,
+}
+
+impl From<&NamedField> for IntegrationNamedField {
+    fn from(field: &NamedField) -> Self {
+        IntegrationNamedField {
+            r#type: field.type_name().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrationUnnamedField {
+    pub r#type: String,
+}
+
+impl From<&UnnamedField> for IntegrationUnnamedField {
+    fn from(field: &UnnamedField) -> Self {
+        IntegrationUnnamedField {
+            r#type: field.type_name().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrationListInfo {
+    pub item_type: String,
+}
+
+impl From<&ListInfo> for IntegrationListInfo {
+    fn from(info: &ListInfo) -> Self {
+        IntegrationListInfo {
+            item_type: info.item_type_name().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrationArrayInfo {
+    pub item_type: String,
+    pub capacity: usize,
+}
 
-        type_registry.register_type_data::<bool, ReflectDefault>();
-        type_registry.register_type_data::<bool, ReflectBoolean>();
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrationMapInfo {
+    pub key_type: String,
+    pub value_type: String,
+}
 
-        type_registry.register_type_data::<u8, ReflectDefault>();
-        type_registry.register_type_data::<u8, ReflectUnsignedInteger>();
+impl From<&MapInfo> for IntegrationMapInfo {
+    fn from(info: &MapInfo) -> Self {
+        IntegrationMapInfo {
+            key_type: info.key_type_name().to_string(),
+            value_type: info.value_type_name().to_string(),
+        }
+    }
+}
 
-        type_registry.register_type_data::<u16, ReflectDefault>();
-        type_registry.register_type_data::<u16, ReflectUnsignedInteger>();
+impl From<&ArrayInfo> for IntegrationArrayInfo {
+    fn from(info: &ArrayInfo) -> Self {
+        IntegrationArrayInfo {
+            item_type: info.item_type_name().to_string(),
+            capacity: info.capacity(),
+        }
+    }
+}
 
-        type_registry.register_type_data::<u32, ReflectDefault>();
-        type_registry.register_type_data::<u32, ReflectUnsignedInteger>();
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IntegrationTypeInfo {
+    #[serde(rename = "struct")]
+    Struct(indexmap::IndexMap<String, IntegrationNamedField>),
+    #[serde(rename = "tuple_struct")]
+    TupleStruct(Vec<IntegrationUnnamedField>),
+    #[serde(rename = "tuple")]
+    Tuple(Vec<IntegrationUnnamedField>),
+    #[serde(rename = "list")]
+    List(IntegrationListInfo),
+    #[serde(rename = "array")]
+    Array(IntegrationArrayInfo),
+    #[serde(rename = "map")]
+    Map(IntegrationMapInfo),
+    #[serde(rename = "value")]
+    Value(()),
+    #[serde(rename = "dynamic")]
+    Dynamic(()),
+}
 
-        type_registry.register_type_data::<u64, ReflectDefault>();
-        type_registry.register_type_data::<u64, ReflectUnsignedInteger>();
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct UIntMetadata {
+    min: u128,
+    max: u128,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct IntMetadata {
+    min: i128,
+    max: i128,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityMetadata {
+    default: Entity,
+}
 
-        type_registry.register_type_data::<u128, ReflectIntegrationBlacklist>();
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct IntegrationMetadata {
+    /// Incremented on export whenever any type's hash changes
+    #[serde(default)]
+    version: u32,
+    /// Structural hash of each exported type, used to detect schema changes between exports
+    #[serde(default)]
+    hashes: BTreeMap<String, u64>,
+    #[serde(default)]
+    migrations: IntegrationMigrations,
 
-        type_registry.register_type_data::<usize, ReflectDefault>();
-        type_registry.register_type_data::<usize, ReflectUnsignedInteger>();
+    types: BTreeMap<String, IntegrationTypeInfo>,
+    type_variant: BTreeMap<String, String>,
+    short_name: BTreeMap<String, String>,
 
-        type_registry.register_type_data::<i8, ReflectDefault>();
-        type_registry.register_type_data::<i8, ReflectSignedInteger>();
-
-        type_registry.register_type_data::<i16, ReflectDefault>();
-        type_registry.register_type_data::<i16, ReflectSignedInteger>();
-
-        type_registry.register_type_data::<i32, ReflectDefault>();
-        type_registry.register_type_data::<i32, ReflectSignedInteger>();
-
-        type_registry.register_type_data::<i64, ReflectDefault>();
-        type_registry.register_type_data::<i64, ReflectSignedInteger>();
-
-        type_registry.register_type_data::<i128, ReflectIntegrationBlacklist>();
-
-        type_registry.register_type_data::<isize, ReflectDefault>();
-        type_registry.register_type_data::<isize, ReflectSignedInteger>();
-
-        type_registry.register_type_data::<f32, ReflectDefault>();
-        type_registry.register_type_data::<f32, ReflectFloat>();
-
-        type_registry.register_type_data::<f64, ReflectDefault>();
-        type_registry.register_type_data::<f64, ReflectFloat>();
-
-        type_registry.register::<String>();
-        type_registry.register_type_data::<String, ReflectDefault>();
-        type_registry.register_type_data::<String, ReflectString>();
-
-        type_registry.register::<Cow<'static, str>>();
-        type_registry.register_type_data::<Cow<'static, str>, ReflectDefault>();
-        type_registry.register_type_data::<Cow<'static, str>, ReflectSerialize>();
-        type_registry.register_type_data::<Cow<'static, str>, ReflectDeserialize>();
-        type_registry.register_type_data::<Cow<'static, str>, ReflectString>();
-
-        type_registry.register::<Color>();
-        type_registry.register_type_data::<Color, ReflectDefault>();
-
-        type_registry.register::<Entity>();
-        type_registry.register_type_data::<Entity, ReflectEntity>();
-
-        type_registry.register::<Option<bool>>();
-        type_registry.register_type_data::<Option<bool>, ReflectDefault>();
-        type_registry.register_type_data::<Option<bool>, ReflectOption>();
-        type_registry.register_type_data::<Option<bool>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<bool>, ReflectDeserialize>();
-
-        type_registry.register::<Option<u8>>();
-        type_registry.register_type_data::<Option<u8>, ReflectDefault>();
-        type_registry.register_type_data::<Option<u8>, ReflectOption>();
-        type_registry.register_type_data::<Option<u8>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<u8>, ReflectDeserialize>();
-
-        type_registry.register::<Option<u16>>();
-        type_registry.register_type_data::<Option<u16>, ReflectDefault>();
-        type_registry.register_type_data::<Option<u16>, ReflectOption>();
-        type_registry.register_type_data::<Option<u16>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<u16>, ReflectDeserialize>();
-
-        type_registry.register::<Option<u32>>();
-        type_registry.register_type_data::<Option<u32>, ReflectDefault>();
-        type_registry.register_type_data::<Option<u32>, ReflectOption>();
-        type_registry.register_type_data::<Option<u32>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<u32>, ReflectDeserialize>();
-
-        type_registry.register::<Option<u64>>();
-        type_registry.register_type_data::<Option<u64>, ReflectDefault>();
-        type_registry.register_type_data::<Option<u64>, ReflectOption>();
-        type_registry.register_type_data::<Option<u64>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<u64>, ReflectDeserialize>();
-
-        type_registry.register::<Option<usize>>();
-        type_registry.register_type_data::<Option<usize>, ReflectDefault>();
-        type_registry.register_type_data::<Option<usize>, ReflectOption>();
-        type_registry.register_type_data::<Option<usize>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<usize>, ReflectDeserialize>();
-
-        type_registry.register::<Option<i8>>();
-        type_registry.register_type_data::<Option<i8>, ReflectDefault>();
-        type_registry.register_type_data::<Option<i8>, ReflectOption>();
-        type_registry.register_type_data::<Option<i8>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<i8>, ReflectDeserialize>();
-
-        type_registry.register::<Option<i16>>();
-        type_registry.register_type_data::<Option<i16>, ReflectDefault>();
-        type_registry.register_type_data::<Option<i16>, ReflectOption>();
-        type_registry.register_type_data::<Option<i16>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<i16>, ReflectDeserialize>();
-
-        type_registry.register::<Option<i32>>();
-        type_registry.register_type_data::<Option<i32>, ReflectDefault>();
-        type_registry.register_type_data::<Option<i32>, ReflectOption>();
-        type_registry.register_type_data::<Option<i32>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<i32>, ReflectDeserialize>();
-
-        type_registry.register::<Option<i64>>();
-        type_registry.register_type_data::<Option<i64>, ReflectDefault>();
-        type_registry.register_type_data::<Option<i64>, ReflectOption>();
-        type_registry.register_type_data::<Option<i64>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<i64>, ReflectDeserialize>();
-
-        type_registry.register::<Option<isize>>();
-        type_registry.register_type_data::<Option<isize>, ReflectDefault>();
-        type_registry.register_type_data::<Option<isize>, ReflectOption>();
-        type_registry.register_type_data::<Option<isize>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<isize>, ReflectDeserialize>();
-
-        type_registry.register::<Option<f32>>();
-        type_registry.register_type_data::<Option<f32>, ReflectDefault>();
-        type_registry.register_type_data::<Option<f32>, ReflectOption>();
-        type_registry.register_type_data::<Option<f32>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<f32>, ReflectDeserialize>();
-
-        type_registry.register::<Option<f64>>();
-        type_registry.register_type_data::<Option<f64>, ReflectDefault>();
-        type_registry.register_type_data::<Option<f64>, ReflectOption>();
-        type_registry.register_type_data::<Option<f64>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<f64>, ReflectDeserialize>();
-
-        type_registry.register::<Option<String>>();
-        type_registry.register_type_data::<Option<String>, ReflectDefault>();
-        type_registry.register_type_data::<Option<String>, ReflectOption>();
-        type_registry.register_type_data::<Option<String>, ReflectSerialize>();
-        type_registry.register_type_data::<Option<String>, ReflectDeserialize>();
-
-        type_registry.register::<Vec<String>>();
-        type_registry.register_type_data::<Vec<String>, ReflectDefault>();
-        type_registry.register_type_data::<Vec<String>, ReflectSerialize>();
-        type_registry.register_type_data::<Vec<String>, ReflectDeserialize>();
-
-        info!("Registered primitive types");
+    boolean: BTreeSet<String>,
+    integer_unsigned: BTreeMap<String, ReflectUnsignedInteger>,
+    integer_signed: BTreeMap<String, ReflectSignedInteger>,
+    float: BTreeSet<String>,
+    string: BTreeSet<String>,
+    option: BTreeMap<String, ReflectOption>,
+    enumeration: BTreeMap<String, Vec<String>>,
+    enumeration_fields: BTreeMap<String, BTreeMap<String, EnumVariantFields>>,
+    bitflags: BTreeMap<String, BTreeMap<usize, String>>,
+    list: BTreeMap<String, IntegrationListInfo>,
+    array: BTreeMap<String, IntegrationArrayInfo>,
+    map: BTreeMap<String, IntegrationMapInfo>,
+    r#struct: BTreeMap<String, indexmap::IndexMap<String, String>>,
+    tuple_struct: BTreeMap<String, Vec<String>>,
+    tuple: BTreeMap<String, Vec<String>>,
+    entity: BTreeSet<String>,
+
+    default: BTreeMap<String, serde_json::Value>,
+    component: BTreeSet<String>,
+    bundle: BTreeSet<String>,
+}
+
+impl IntegrationMetadata {
+    /// Build metadata describing every non-blacklisted type in the registry
+    pub fn from_registry(
+        type_registry: &TypeRegistryInternal,
+        migrations: IntegrationMigrations,
+    ) -> Self {
         let mut metadata = IntegrationMetadata::default();
 
         for ty in type_registry.iter() {
@@ -379,6 +630,12 @@ impl Plugin for ExportIntegrationPlugin {
                         .map(|variant| variant.to_string())
                         .collect(),
                 );
+
+                if !enumeration.variant_fields.is_empty() {
+                    metadata
+                        .enumeration_fields
+                        .insert(type_name.to_string(), enumeration.variant_fields.clone());
+                }
             }
 
             if let Some(bitflags) = ty.data::<ReflectBitflags>() {
@@ -392,146 +649,55 @@ impl Plugin for ExportIntegrationPlugin {
             }
         }
 
-        let out = serde_json::to_string_pretty(&metadata).unwrap();
+        metadata.migrations = migrations;
 
-        println!("Writing to {}", self.path);
-        std::fs::write(self.path, out).expect("Failed to write type manifest");
-    }
-}
+        metadata.hashes = metadata
+            .type_variant
+            .keys()
+            .map(|type_name| (type_name.clone(), metadata.type_hash(type_name)))
+            .collect();
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct IntegrationNamedField {
-    pub r#type: String,
-}
-
-impl From<&NamedField> for IntegrationNamedField {
-    fn from(field: &NamedField) -> Self {
-        IntegrationNamedField {
-            r#type: field.type_name().to_string(),
-        }
+        metadata
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct IntegrationUnnamedField {
-    pub r#type: String,
-}
-
-impl From<&UnnamedField> for IntegrationUnnamedField {
-    fn from(field: &UnnamedField) -> Self {
-        IntegrationUnnamedField {
-            r#type: field.type_name().to_string(),
-        }
+    pub fn version(&self) -> u32 {
+        self.version
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct IntegrationListInfo {
-    pub item_type: String,
-}
-
-impl From<&ListInfo> for IntegrationListInfo {
-    fn from(info: &ListInfo) -> Self {
-        IntegrationListInfo {
-            item_type: info.item_type_name().to_string(),
-        }
+    /// Stable FNV-1a hash over everything the metadata records about a type
+    pub fn type_hash(&self, type_name: &str) -> u64 {
+        let signature = serde_json::json!({
+            "variant": self.type_variant.get(type_name),
+            "struct": self.r#struct.get(type_name),
+            "tuple_struct": self.tuple_struct.get(type_name),
+            "tuple": self.tuple.get(type_name),
+            "list": self.list.get(type_name),
+            "array": self.array.get(type_name),
+            "map": self.map.get(type_name),
+            "option": self.option.get(type_name),
+            "enumeration": self.enumeration.get(type_name),
+            "enumeration_fields": self.enumeration_fields.get(type_name),
+            "bitflags": self.bitflags.get(type_name),
+        });
+
+        signature
+            .to_string()
+            .bytes()
+            .fold(0xcbf29ce484222325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            })
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct IntegrationArrayInfo {
-    pub item_type: String,
-    pub capacity: usize,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct IntegrationMapInfo {
-    pub key_type: String,
-    pub value_type: String,
-}
-
-impl From<&MapInfo> for IntegrationMapInfo {
-    fn from(info: &MapInfo) -> Self {
-        IntegrationMapInfo {
-            key_type: info.key_type_name().to_string(),
-            value_type: info.value_type_name().to_string(),
-        }
+    /// Types whose hashes differ from, or are absent in, a previous export
+    pub fn changed_types(&self, previous: &IntegrationMetadata) -> Vec<String> {
+        self.hashes
+            .iter()
+            .filter(|(type_name, hash)| previous.hashes.get(*type_name) != Some(*hash))
+            .map(|(type_name, _)| type_name.clone())
+            .collect()
     }
 }
 
-impl From<&ArrayInfo> for IntegrationArrayInfo {
-    fn from(info: &ArrayInfo) -> Self {
-        IntegrationArrayInfo {
-            item_type: info.item_type_name().to_string(),
-            capacity: info.capacity(),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum IntegrationTypeInfo {
-    #[serde(rename = "struct")]
-    Struct(indexmap::IndexMap<String, IntegrationNamedField>),
-    #[serde(rename = "tuple_struct")]
-    TupleStruct(Vec<IntegrationUnnamedField>),
-    #[serde(rename = "tuple")]
-    Tuple(Vec<IntegrationUnnamedField>),
-    #[serde(rename = "list")]
-    List(IntegrationListInfo),
-    #[serde(rename = "array")]
-    Array(IntegrationArrayInfo),
-    #[serde(rename = "map")]
-    Map(IntegrationMapInfo),
-    #[serde(rename = "value")]
-    Value(()),
-    #[serde(rename = "dynamic")]
-    Dynamic(()),
-}
-
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-pub struct UIntMetadata {
-    min: u128,
-    max: u128,
-}
-
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-pub struct IntMetadata {
-    min: i128,
-    max: i128,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EntityMetadata {
-    default: Entity,
-}
-
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-pub struct IntegrationMetadata {
-    types: BTreeMap<String, IntegrationTypeInfo>,
-    type_variant: BTreeMap<String, String>,
-    short_name: BTreeMap<String, String>,
-
-    boolean: BTreeSet<String>,
-    integer_unsigned: BTreeMap<String, ReflectUnsignedInteger>,
-    integer_signed: BTreeMap<String, ReflectSignedInteger>,
-    float: BTreeSet<String>,
-    string: BTreeSet<String>,
-    option: BTreeMap<String, ReflectOption>,
-    enumeration: BTreeMap<String, Vec<String>>,
-    bitflags: BTreeMap<String, BTreeMap<usize, String>>,
-    list: BTreeMap<String, IntegrationListInfo>,
-    array: BTreeMap<String, IntegrationArrayInfo>,
-    map: BTreeMap<String, IntegrationMapInfo>,
-    r#struct: BTreeMap<String, indexmap::IndexMap<String, String>>,
-    tuple_struct: BTreeMap<String, Vec<String>>,
-    tuple: BTreeMap<String, Vec<String>>,
-    entity: BTreeSet<String>,
-
-    default: BTreeMap<String, serde_json::Value>,
-    component: BTreeSet<String>,
-    bundle: BTreeSet<String>,
-}
-
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ReflectOption {
     pub inner_type: String,
@@ -545,15 +711,44 @@ impl<T> FromType<Option<T>> for ReflectOption {
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// Field schema for an enum variant that carries data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnumVariantFields {
+    #[serde(rename = "tuple")]
+    Tuple(Vec<String>),
+    #[serde(rename = "struct")]
+    Struct(indexmap::IndexMap<String, String>),
+}
+
+impl EnumVariantFields {
+    pub fn tuple<'a>(fields: impl IntoIterator<Item = &'a str>) -> Self {
+        EnumVariantFields::Tuple(fields.into_iter().map(ToString::to_string).collect())
+    }
+
+    pub fn structure<'a>(fields: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        EnumVariantFields::Struct(
+            fields
+                .into_iter()
+                .map(|(name, ty)| (name.to_string(), ty.to_string()))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct ReflectEnum {
     pub variants: Vec<String>,
+    pub variant_fields: BTreeMap<String, EnumVariantFields>,
 }
 
 impl ReflectEnum {
     pub fn variants(&self) -> impl Iterator<Item = &str> {
         self.variants.iter().map(|variant| variant.as_str())
     }
+
+    pub fn variant_fields(&self, variant: &str) -> Option<&EnumVariantFields> {
+        self.variant_fields.get(variant)
+    }
 }
 
 impl<T> FromType<T> for ReflectEnum
@@ -563,12 +758,20 @@ where
     fn from_type() -> Self {
         ReflectEnum {
             variants: T::variants().into_iter().map(ToString::to_string).collect(),
+            variant_fields: T::variant_fields(),
         }
     }
 }
 
 pub trait Enum {
     fn variants() -> &'static [&'static str];
+
+    /// Field schemas for variants that carry data, keyed by variant name
+    ///
+    /// Variants without an entry are treated as unit variants
+    fn variant_fields() -> BTreeMap<String, EnumVariantFields> {
+        default()
+    }
 }
 
 #[derive(
@@ -594,6 +797,46 @@ impl Enum for TestEnum {
     }
 }
 
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Default, Enum, Component, Serialize, Deserialize)]
+pub enum TestDataEnum {
+    Unit,
+    Tuple(usize, bool),
+    Struct { count: u32, flag: bool },
+}
+
+impl Default for TestDataEnum {
+    fn default() -> Self {
+        TestDataEnum::Unit
+    }
+}
+
+impl Enum for TestDataEnum {
+    fn variants() -> &'static [&'static str] {
+        &["Unit", "Tuple", "Struct"]
+    }
+
+    fn variant_fields() -> BTreeMap<String, EnumVariantFields> {
+        [
+            (
+                "Tuple".to_string(),
+                EnumVariantFields::tuple([type_name::<usize>(), type_name::<bool>()]),
+            ),
+            (
+                "Struct".to_string(),
+                EnumVariantFields::structure([
+                    ("count", type_name::<u32>()),
+                    ("flag", type_name::<bool>()),
+                ]),
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ReflectBitflags {
     pub flags: BTreeMap<String, usize>,
@@ -626,27 +869,6 @@ pub trait Bitflags {
     }
 }
 
-impl Bitflags for CollisionGroup {
-    fn flags() -> BTreeMap<String, usize> {
-        [
-            ("STATIC", CollisionGroup::STATIC),
-            ("SHIP_BODY", CollisionGroup::SHIP_BODY),
-            ("SHIP_HURTBOX", CollisionGroup::SHIP_HURTBOX),
-            ("SHIP_HITBOX", CollisionGroup::SHIP_HITBOX),
-            ("SHIP_SENSOR", CollisionGroup::SHIP_SENSOR),
-            ("FORCE_BODY", CollisionGroup::FORCE_BODY),
-            ("FORCE_HITBOX", CollisionGroup::FORCE_HITBOX),
-            ("FORCE_SENSOR", CollisionGroup::FORCE_SENSOR),
-            ("ENEMY_BODY", CollisionGroup::ENEMY_BODY),
-            ("ENEMY_HURTBOX", CollisionGroup::ENEMY_HURTBOX),
-            ("ENEMY_HITBOX", CollisionGroup::ENEMY_HITBOX),
-        ]
-        .map(|(key, value)| (key.to_string(), value.bits() as usize))
-        .into_iter()
-        .collect()
-    }
-}
-
 /// Prevents a type from being exported to integration format
 #[derive(Debug, Default, Copy, Clone)]
 pub struct ReflectIntegrationBlacklist;
@@ -795,6 +1017,7 @@ impl_reflect_value!(TestMapPrivate);
 
 pub fn register_test_types(app: &mut App) {
     app.register_type::<TestEnum>();
+    app.register_type::<TestDataEnum>();
     app.register_type::<TestOption>();
     app.register_type::<TestList>();
     app.register_type::<TestArray>();
@@ -804,6 +1027,11 @@ pub fn register_test_types(app: &mut App) {
     app.register_type::<TestTuple>();
     app.register_type::<TestBundle>();
 
+    app.register_integration_migration::<TestEnum>(Migration::RenameVariant {
+        from: "Deadbeef".to_string(),
+        to: "Decafisbad".to_string(),
+    });
+
     app.register_type::<TestOptionPrivate>();
     app.register_type_data::<TestOptionPrivate, ReflectDefault>();
     app.register_type_data::<TestOptionPrivate, ReflectComponent>();
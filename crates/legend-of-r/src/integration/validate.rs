@@ -0,0 +1,801 @@
+//! Validation of glTF extras against exported IntegrationMetadata
+//!
+//! Checks the raw Blender-side representation of entity components,
+//! reporting every mismatch instead of stopping at the first
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{EnumVariantFields, IntegrationMetadata};
+
+/// A single mismatch between a node's extras and the integration metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtrasError {
+    pub node: String,
+    pub path: String,
+    pub kind: ExtrasErrorKind,
+}
+
+impl Display for ExtrasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.node, self.path, self.kind)
+    }
+}
+
+impl std::error::Error for ExtrasError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExtrasErrorKind {
+    ExtrasNotObject,
+    EntityNotObject,
+    ComponentNotObject,
+    MissingType,
+    UnknownType(String),
+    NotAComponent(String),
+    TypeMismatch { expected: String, found: String },
+    MissingKey(String),
+    InvalidValue { expected: String, found: String },
+    MissingField(String),
+    UnknownField(String),
+    InvalidIndex(String),
+    WrongLength { expected: usize, found: usize },
+    OutOfRange { value: String, min: String, max: String },
+    UnknownVariant(String),
+    UnknownFlags(u64),
+    UnknownEntity(String),
+    Deserialize(String),
+}
+
+impl Display for ExtrasErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtrasErrorKind::ExtrasNotObject => write!(f, "extras entry is not an object"),
+            ExtrasErrorKind::EntityNotObject => write!(f, "entity entry is not an object"),
+            ExtrasErrorKind::ComponentNotObject => write!(f, "component is not an object"),
+            ExtrasErrorKind::MissingType => write!(f, "missing type entry"),
+            ExtrasErrorKind::UnknownType(ty) => write!(f, "unknown type {ty:}"),
+            ExtrasErrorKind::NotAComponent(ty) => {
+                write!(f, "{ty:} is neither a component nor a bundle")
+            }
+            ExtrasErrorKind::TypeMismatch { expected, found } => {
+                write!(f, "expected type {expected:}, found {found:}")
+            }
+            ExtrasErrorKind::MissingKey(key) => write!(f, "missing {key:} entry"),
+            ExtrasErrorKind::InvalidValue { expected, found } => {
+                write!(f, "expected {expected:}, found {found:}")
+            }
+            ExtrasErrorKind::MissingField(field) => write!(f, "missing field {field:}"),
+            ExtrasErrorKind::UnknownField(field) => write!(f, "unknown field {field:}"),
+            ExtrasErrorKind::InvalidIndex(key) => write!(f, "{key:?} is not a valid index"),
+            ExtrasErrorKind::WrongLength { expected, found } => {
+                write!(f, "expected {expected:} elements, found {found:}")
+            }
+            ExtrasErrorKind::OutOfRange { value, min, max } => {
+                write!(f, "{value:} is outside the range {min:}..={max:}")
+            }
+            ExtrasErrorKind::UnknownVariant(variant) => write!(f, "unknown variant {variant:}"),
+            ExtrasErrorKind::UnknownFlags(bits) => write!(f, "unknown flag bits {bits:#b}"),
+            ExtrasErrorKind::UnknownEntity(name) => write!(f, "no entity named {name:}"),
+            ExtrasErrorKind::Deserialize(error) => write!(f, "failed to deserialize: {error:}"),
+        }
+    }
+}
+
+pub(crate) fn json_kind(value: &Value) -> String {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+    .to_string()
+}
+
+/// Accumulates errors for a single node while walking its extras
+struct Validator<'a> {
+    metadata: &'a IntegrationMetadata,
+    node: &'a str,
+    errors: Vec<ExtrasError>,
+}
+
+impl<'a> Validator<'a> {
+    fn error(&mut self, path: &str, kind: ExtrasErrorKind) {
+        self.errors.push(ExtrasError {
+            node: self.node.to_string(),
+            path: path.to_string(),
+            kind,
+        });
+    }
+
+    fn invalid(&mut self, path: &str, expected: &str, found: &Value) {
+        self.error(
+            path,
+            ExtrasErrorKind::InvalidValue {
+                expected: expected.to_string(),
+                found: json_kind(found),
+            },
+        );
+    }
+
+    fn extras(&mut self, extras: &Value) {
+        let extras = if let Value::Object(extras) = extras {
+            extras
+        } else {
+            self.error("", ExtrasErrorKind::ExtrasNotObject);
+            return;
+        };
+
+        let entity = if let Some(entity) = extras.get("entity") {
+            entity
+        } else {
+            return;
+        };
+
+        if let Value::Object(entity) = entity {
+            self.entity(entity);
+        } else {
+            self.error("entity", ExtrasErrorKind::EntityNotObject);
+        }
+    }
+
+    fn entity(&mut self, entity: &Map<String, Value>) {
+        for (key, component) in entity.iter() {
+            self.component(key, component);
+        }
+    }
+
+    fn component(&mut self, key: &str, component: &Value) {
+        let path = format!("entity.{key:}");
+
+        let component_object = if let Value::Object(object) = component {
+            object
+        } else {
+            self.error(&path, ExtrasErrorKind::ComponentNotObject);
+            return;
+        };
+
+        let type_name = if let Some(Value::String(type_name)) = component_object.get("type") {
+            type_name
+        } else {
+            self.error(&path, ExtrasErrorKind::MissingType);
+            return;
+        };
+
+        if !self.metadata.type_variant.contains_key(type_name) {
+            self.error(&path, ExtrasErrorKind::UnknownType(type_name.clone()));
+            return;
+        }
+
+        if !self.metadata.component.contains(type_name)
+            && !self.metadata.bundle.contains(type_name)
+        {
+            self.error(&path, ExtrasErrorKind::NotAComponent(type_name.clone()));
+            return;
+        }
+
+        self.typed(&path, None, component);
+    }
+
+    /// Validate a typed value of the form { "type": ..., <payload> },
+    /// optionally checking its type against the one declared by its parent
+    fn typed(&mut self, path: &str, expected_type: Option<&str>, value: &Value) {
+        let object = if let Value::Object(object) = value {
+            object
+        } else {
+            self.invalid(path, "object", value);
+            return;
+        };
+
+        let type_name = if let Some(Value::String(type_name)) = object.get("type") {
+            type_name.as_str()
+        } else {
+            self.error(path, ExtrasErrorKind::MissingType);
+            return;
+        };
+
+        if let Some(expected) = expected_type {
+            if expected != type_name {
+                self.error(
+                    path,
+                    ExtrasErrorKind::TypeMismatch {
+                        expected: expected.to_string(),
+                        found: type_name.to_string(),
+                    },
+                );
+                return;
+            }
+        }
+
+        let variant = if let Some(variant) = self.metadata.type_variant.get(type_name) {
+            variant.as_str()
+        } else {
+            self.error(path, ExtrasErrorKind::UnknownType(type_name.to_string()));
+            return;
+        };
+
+        match variant {
+            "struct" => self.structure(path, type_name, object),
+            "tuple_struct" => self.tuple(path, type_name, "tuple_struct", object),
+            "tuple" => self.tuple(path, type_name, "tuple", object),
+            "list" => self.list(path, type_name, object),
+            "array" => self.array(path, type_name, object),
+            "map" => self.map(path, type_name, object),
+            _ => {
+                if let Some(value) = object.get("value") {
+                    self.value(path, type_name, value);
+                    self.variant_payload(path, type_name, value, object);
+                } else {
+                    self.error(path, ExtrasErrorKind::MissingKey("value".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Validate the tuple or struct payload of a data-carrying enum variant
+    fn variant_payload(
+        &mut self,
+        path: &str,
+        type_name: &str,
+        variant: &Value,
+        object: &Map<String, Value>,
+    ) {
+        let metadata = self.metadata;
+
+        let variant_fields =
+            if let Some(variant_fields) = metadata.enumeration_fields.get(type_name) {
+                variant_fields
+            } else {
+                return;
+            };
+
+        let variants = if let Some(variants) = metadata.enumeration.get(type_name) {
+            variants
+        } else {
+            return;
+        };
+
+        let variant = match variant {
+            Value::String(variant) => Some(variant.as_str()),
+            Value::Number(number) => number
+                .as_u64()
+                .and_then(|index| variants.get(index as usize))
+                .map(String::as_str),
+            _ => None,
+        };
+
+        match variant.and_then(|variant| variant_fields.get(variant)) {
+            Some(EnumVariantFields::Tuple(field_types)) => {
+                let payload = if let Some(payload) = self.payload(path, "tuple", object) {
+                    payload
+                } else {
+                    return;
+                };
+
+                let entries = self.indexed(path, payload);
+                if entries.len() != field_types.len() {
+                    self.error(
+                        path,
+                        ExtrasErrorKind::WrongLength {
+                            expected: field_types.len(),
+                            found: entries.len(),
+                        },
+                    );
+                }
+
+                for (index, value) in entries {
+                    let field_path = format!("{path:}.{index:}");
+                    if let Some(field_type) = field_types.get(index) {
+                        self.typed(&field_path, Some(field_type), value);
+                    } else {
+                        self.error(&field_path, ExtrasErrorKind::UnknownField(index.to_string()));
+                    }
+                }
+            }
+            Some(EnumVariantFields::Struct(field_types)) => {
+                let payload = if let Some(payload) = self.payload(path, "struct", object) {
+                    payload
+                } else {
+                    return;
+                };
+
+                for (key, value) in payload.iter() {
+                    let field_path = format!("{path:}.{key:}");
+                    if let Some(field_type) = field_types.get(key) {
+                        self.typed(&field_path, Some(field_type), value);
+                    } else {
+                        self.error(&field_path, ExtrasErrorKind::UnknownField(key.clone()));
+                    }
+                }
+
+                for field in field_types.keys() {
+                    if !payload.contains_key(field) {
+                        self.error(path, ExtrasErrorKind::MissingField(field.clone()));
+                    }
+                }
+            }
+            None => (),
+        }
+    }
+
+    fn payload<'v>(
+        &mut self,
+        path: &str,
+        key: &str,
+        object: &'v Map<String, Value>,
+    ) -> Option<&'v Map<String, Value>> {
+        match object.get(key) {
+            Some(Value::Object(payload)) => Some(payload),
+            Some(value) => {
+                self.invalid(&format!("{path:}.{key:}"), "object", value);
+                None
+            }
+            None => {
+                self.error(path, ExtrasErrorKind::MissingKey(key.to_string()));
+                None
+            }
+        }
+    }
+
+    fn structure(&mut self, path: &str, type_name: &str, object: &Map<String, Value>) {
+        let fields = if let Some(fields) = self.metadata.r#struct.get(type_name) {
+            fields.clone()
+        } else {
+            return;
+        };
+
+        let payload = if let Some(payload) = self.payload(path, "struct", object) {
+            payload
+        } else {
+            return;
+        };
+
+        for (key, value) in payload.iter() {
+            let field_path = format!("{path:}.{key:}");
+            if let Some(field_type) = fields.get(key) {
+                self.typed(&field_path, Some(field_type), value);
+            } else {
+                self.error(&field_path, ExtrasErrorKind::UnknownField(key.clone()));
+            }
+        }
+
+        // Missing fields fall back to the type's default, so only flag them when none exists
+        if !self.metadata.default.contains_key(type_name) {
+            for field in fields.keys() {
+                if !payload.contains_key(field) {
+                    self.error(path, ExtrasErrorKind::MissingField(field.clone()));
+                }
+            }
+        }
+    }
+
+    fn indexed<'v>(
+        &mut self,
+        path: &str,
+        payload: &'v Map<String, Value>,
+    ) -> Vec<(usize, &'v Value)> {
+        let mut entries = vec![];
+        for (key, value) in payload.iter() {
+            if let Ok(index) = key.parse::<usize>() {
+                entries.push((index, value));
+            } else {
+                self.error(
+                    &format!("{path:}.{key:}"),
+                    ExtrasErrorKind::InvalidIndex(key.clone()),
+                );
+            }
+        }
+        entries.sort_by_key(|(index, _)| *index);
+        entries
+    }
+
+    fn tuple(&mut self, path: &str, type_name: &str, key: &str, object: &Map<String, Value>) {
+        let field_types = if key == "tuple" {
+            self.metadata.tuple.get(type_name)
+        } else {
+            self.metadata.tuple_struct.get(type_name)
+        };
+
+        let field_types = if let Some(field_types) = field_types {
+            field_types.clone()
+        } else {
+            return;
+        };
+
+        let payload = if let Some(payload) = self.payload(path, key, object) {
+            payload
+        } else {
+            return;
+        };
+
+        let entries = self.indexed(path, payload);
+        if entries.len() != field_types.len() {
+            self.error(
+                path,
+                ExtrasErrorKind::WrongLength {
+                    expected: field_types.len(),
+                    found: entries.len(),
+                },
+            );
+        }
+
+        for (index, value) in entries {
+            let field_path = format!("{path:}.{index:}");
+            if let Some(field_type) = field_types.get(index) {
+                self.typed(&field_path, Some(field_type), value);
+            } else {
+                self.error(&field_path, ExtrasErrorKind::UnknownField(index.to_string()));
+            }
+        }
+    }
+
+    fn list(&mut self, path: &str, type_name: &str, object: &Map<String, Value>) {
+        let item_type = if let Some(info) = self.metadata.list.get(type_name) {
+            info.item_type.clone()
+        } else {
+            return;
+        };
+
+        let payload = if let Some(payload) = self.payload(path, "list", object) {
+            payload
+        } else {
+            return;
+        };
+
+        for (index, value) in self.indexed(path, payload) {
+            self.typed(&format!("{path:}.{index:}"), Some(&item_type), value);
+        }
+    }
+
+    fn array(&mut self, path: &str, type_name: &str, object: &Map<String, Value>) {
+        let info = if let Some(info) = self.metadata.array.get(type_name) {
+            info.clone()
+        } else {
+            return;
+        };
+
+        let payload = if let Some(payload) = self.payload(path, "array", object) {
+            payload
+        } else {
+            return;
+        };
+
+        let entries = self.indexed(path, payload);
+        if entries.len() != info.capacity {
+            self.error(
+                path,
+                ExtrasErrorKind::WrongLength {
+                    expected: info.capacity,
+                    found: entries.len(),
+                },
+            );
+        }
+
+        for (index, value) in entries {
+            self.typed(&format!("{path:}.{index:}"), Some(&info.item_type), value);
+        }
+    }
+
+    fn map(&mut self, path: &str, type_name: &str, object: &Map<String, Value>) {
+        let info = if let Some(info) = self.metadata.map.get(type_name) {
+            info.clone()
+        } else {
+            return;
+        };
+
+        let (keys, values) = match (object.get("keys"), object.get("values")) {
+            (Some(Value::Array(keys)), Some(Value::Array(values))) => (keys, values),
+            // An absent map is deserialized as empty
+            (None, None) => return,
+            _ => {
+                self.error(path, ExtrasErrorKind::MissingKey("keys/values".to_string()));
+                return;
+            }
+        };
+
+        if keys.len() != values.len() {
+            self.error(
+                path,
+                ExtrasErrorKind::WrongLength {
+                    expected: keys.len(),
+                    found: values.len(),
+                },
+            );
+        }
+
+        for (index, (key, value)) in keys.iter().zip(values.iter()).enumerate() {
+            let entry_path = format!("{path:}.{index:}");
+
+            match key.get("value") {
+                Some(Value::String(_)) => (),
+                Some(key) => self.invalid(&format!("{entry_path:}.key"), "string", key),
+                None => self.error(&entry_path, ExtrasErrorKind::MissingKey("key".to_string())),
+            }
+
+            match value.get("value") {
+                Some(value) => self.value(&format!("{entry_path:}.value"), &info.value_type, value),
+                None => self.error(&entry_path, ExtrasErrorKind::MissingKey("value".to_string())),
+            }
+        }
+    }
+
+    /// Validate the payload of a value-type, given its type name
+    fn value(&mut self, path: &str, type_name: &str, value: &Value) {
+        let metadata = self.metadata;
+
+        if metadata.boolean.contains(type_name) {
+            match value {
+                Value::Bool(_) => (),
+                Value::Number(number) if number.as_u64().is_some() => (),
+                _ => self.invalid(path, "bool", value),
+            }
+        } else if let Some(range) = metadata.integer_unsigned.get(type_name) {
+            match value.as_u64() {
+                Some(number) if (range.min..=range.max).contains(&(number as u128)) => (),
+                Some(number) => self.error(
+                    path,
+                    ExtrasErrorKind::OutOfRange {
+                        value: number.to_string(),
+                        min: range.min.to_string(),
+                        max: range.max.to_string(),
+                    },
+                ),
+                None => self.invalid(path, "unsigned integer", value),
+            }
+        } else if let Some(range) = metadata.integer_signed.get(type_name) {
+            match value.as_i64() {
+                Some(number) if (range.min..=range.max).contains(&(number as i128)) => (),
+                Some(number) => self.error(
+                    path,
+                    ExtrasErrorKind::OutOfRange {
+                        value: number.to_string(),
+                        min: range.min.to_string(),
+                        max: range.max.to_string(),
+                    },
+                ),
+                None => self.invalid(path, "signed integer", value),
+            }
+        } else if metadata.float.contains(type_name) {
+            if !value.is_number() {
+                self.invalid(path, "number", value);
+            }
+        } else if metadata.string.contains(type_name) {
+            if !value.is_string() {
+                self.invalid(path, "string", value);
+            }
+        } else if let Some(variants) = metadata.enumeration.get(type_name) {
+            match value {
+                Value::Number(number) => match number.as_u64() {
+                    Some(index) if (index as usize) < variants.len() => (),
+                    _ => self.error(path, ExtrasErrorKind::UnknownVariant(number.to_string())),
+                },
+                Value::String(variant) => {
+                    if !variants.contains(variant) {
+                        self.error(path, ExtrasErrorKind::UnknownVariant(variant.clone()))
+                    }
+                }
+                _ => self.invalid(path, "variant index or name", value),
+            }
+        } else if let Some(flags) = metadata.bitflags.get(type_name) {
+            let known = flags.keys().fold(0u64, |acc, flag| acc | *flag as u64);
+            match value.as_u64() {
+                Some(bits) if bits & !known == 0 => (),
+                Some(bits) => self.error(path, ExtrasErrorKind::UnknownFlags(bits & !known)),
+                None => self.invalid(path, "flag bits", value),
+            }
+        } else if let Some(option) = metadata.option.get(type_name) {
+            match value {
+                Value::Null => (),
+                Value::Object(object) => {
+                    if let Some(inner) = object.get("value") {
+                        self.value(&format!("{path:}.value"), &option.inner_type, inner);
+                    }
+                }
+                _ => self.invalid(path, "object or null", value),
+            }
+        } else if metadata.entity.contains(type_name) {
+            match value.get("name") {
+                Some(Value::String(_)) => (),
+                _ => self.error(path, ExtrasErrorKind::MissingKey("name".to_string())),
+            }
+        }
+    }
+}
+
+impl IntegrationMetadata {
+    /// Validate a single node's extras, returning every mismatch found
+    pub fn validate_extras(&self, node: &str, extras: &Value) -> Vec<ExtrasError> {
+        let mut validator = Validator {
+            metadata: self,
+            node,
+            errors: vec![],
+        };
+        validator.extras(extras);
+        validator.errors
+    }
+
+    /// Validate the contents of a node's entity entry, returning every mismatch found
+    pub fn validate_entity(&self, node: &str, entity: &Map<String, Value>) -> Vec<ExtrasError> {
+        let mut validator = Validator {
+            metadata: self,
+            node,
+            errors: vec![],
+        };
+        validator.entity(entity);
+        validator.errors
+    }
+
+    /// Validate a single component of a node's entity entry, returning every mismatch found
+    pub fn validate_component(&self, node: &str, key: &str, component: &Value) -> Vec<ExtrasError> {
+        let mut validator = Validator {
+            metadata: self,
+            node,
+            errors: vec![],
+        };
+        validator.component(key, component);
+        validator.errors
+    }
+
+    /// Validate the extras of every node in a parsed glTF document,
+    /// after applying any migrations exported alongside the metadata
+    pub fn validate_gltf(&self, gltf: &Value) -> Vec<ExtrasError> {
+        let nodes = if let Some(Value::Array(nodes)) = gltf.get("nodes") {
+            nodes
+        } else {
+            return vec![];
+        };
+
+        nodes
+            .iter()
+            .enumerate()
+            .flat_map(|(index, node)| {
+                let name = node
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .map(ToString::to_string)
+                    .unwrap_or_else(|| format!("Node{index:}"));
+
+                node.get("extras")
+                    .map(|extras| {
+                        let mut extras = extras.clone();
+                        self.migrations.migrate(&mut extras);
+                        self.validate_extras(&name, &extras)
+                    })
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+/// Validate every .gltf file under the given directory
+pub fn validate_gltf_directory(
+    metadata: &IntegrationMetadata,
+    root: impl AsRef<Path>,
+) -> anyhow::Result<BTreeMap<PathBuf, Vec<ExtrasError>>> {
+    let mut results = BTreeMap::new();
+    let mut directories = vec![root.as_ref().to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        for entry in std::fs::read_dir(&directory)
+            .with_context(|| format!("Failed to read directory {}", directory.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else if path.extension().and_then(|extension| extension.to_str()) == Some("gltf") {
+                let json = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let gltf: Value = serde_json::from_str(&json)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?;
+                results.insert(path, metadata.validate_gltf(&gltf));
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Offline validation entrypoint
+///
+/// Prints every error and returns a process exit code
+pub fn validate_gltf_cli(metadata_path: impl AsRef<Path>, root: impl AsRef<Path>) -> i32 {
+    let result = IntegrationMetadata::load(metadata_path)
+        .and_then(|metadata| validate_gltf_directory(&metadata, root));
+
+    let results = match result {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("{e:?}");
+            return 2;
+        }
+    };
+
+    let mut error_count = 0;
+    for (path, errors) in results.iter() {
+        if errors.is_empty() {
+            println!("{}: OK", path.display());
+            continue;
+        }
+
+        for error in errors {
+            println!("{}: {error:}", path.display());
+        }
+        error_count += errors.len();
+    }
+
+    println!("{} files checked, {error_count:} errors", results.len());
+
+    if error_count > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn metadata() -> IntegrationMetadata {
+        let mut metadata = IntegrationMetadata::default();
+        metadata
+            .type_variant
+            .insert("Flag".to_string(), "value".to_string());
+        metadata.boolean.insert("Flag".to_string());
+        metadata.component.insert("Flag".to_string());
+        metadata
+    }
+
+    #[test]
+    fn valid_component_has_no_errors() {
+        let errors = metadata().validate_component(
+            "Node",
+            "Flag",
+            &json!({ "type": "Flag", "value": true }),
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn invalid_component_reports_its_own_path() {
+        let errors = metadata().validate_component(
+            "Node",
+            "Missing",
+            &json!({ "type": "Missing", "value": true }),
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].node, "Node");
+        assert_eq!(errors[0].path, "entity.Missing");
+        assert_eq!(
+            errors[0].kind,
+            ExtrasErrorKind::UnknownType("Missing".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_value_is_reported() {
+        let errors = metadata().validate_component(
+            "Node",
+            "Flag",
+            &json!({ "type": "Flag", "value": "yes" }),
+        );
+        assert_eq!(errors.len(), 1);
+    }
+}
@@ -8,43 +8,55 @@ pub mod games;
 pub mod gltf_entity;
 pub mod gltf_json;
 pub mod hierarchy;
+pub mod input_binding;
 pub mod integration;
 pub mod iterator;
 pub mod scene;
 pub mod mesh_2d;
 pub mod movement;
+pub mod picking;
+pub mod profiling;
 pub mod prelude;
 pub mod shmup;
+pub mod simulation;
 pub mod user_interface;
 pub mod util;
 
 use bevy::{
     diagnostic::FrameTimeDiagnosticsPlugin,
+    gilrs::GilrsPlugin,
     gltf::GltfExtras,
+    log::LogPlugin,
     pbr::CubemapVisibleEntities,
     prelude::{
-        info, App, Button, ClearColor, Color, ComputedVisibility, CoreStage, Msaa,
+        default, info, App, Button, ClearColor, Color, ComputedVisibility, CoreStage, Msaa,
         ParallelSystemDescriptorCoercion, Parent,
     },
     reflect::{ReflectDeserialize, ReflectSerialize, TypeRegistry},
     render::{
         camera::CameraRenderGraph,
         primitives::{CubemapFrusta, Frustum},
+        settings::WgpuSettings,
         view::VisibleEntities,
     },
+    sprite::ColorMesh2dBundle,
+    time::TimePlugin,
     ui::{widget::ImageMode, CalculatedSize, FocusPolicy, Interaction, Node, UiColor},
+    winit::WinitPlugin,
     DefaultPlugins,
 };
-use bevy_rapier2d::prelude::{ActiveCollisionTypes, AdditionalMassProperties};
+use bevy_rapier2d::prelude::{ActiveCollisionTypes, AdditionalMassProperties, Collider};
 use gltf_entity::GltfEntityPlugin;
 use gltf_json::GltfJsonPlugin;
-use integration::register_test_types;
+use integration::{register_test_types, validate::validate_gltf_cli, IntegrationMetadataPlugin};
+use simulation::SimulationPlugin;
 use prelude::{
     convert_mesh_2d, insert_ball_collider, insert_capsule_collider, insert_kinematic_rigid_body,
     Bitflags, CollisionGroupsPlugin, ConvertMesh2d, DebugDumpPlugin, ExportIntegrationPlugin,
-    HitboxBundle, InsertBallCollider, InsertCapsuleCollider, InsertKinematicRigidBody,
-    KinematicBodyBundle, ReflectBitflags, ReflectIntegrationBlacklist, SensorBundle, ShmupPlugin,
-    Timeline,
+    HitboxBundle, InputBindingsPlugin, InsertBallCollider, InsertCapsuleCollider,
+    InsertKinematicRigidBody, KinematicBodyBundle, LineMaterialPlugin, PlayerInputMaps,
+    ReflectBitflags, ReflectIntegrationBlacklist, RegisterArchiveBundle, SceneInstancePlugin,
+    SensorBundle, ShmupPlugin, Timeline,
 };
 
 use crate::prelude::{HurtboxBundle, TimelineDamage};
@@ -68,20 +80,111 @@ impl Bitflags for ActiveCollisionTypes {
     }
 }
 
+/// Path of the type metadata consumed by the Blender integration
+pub const INTEGRATION_TYPES_PATH: &str = "assets/blender/types_shmup.json";
+
 fn main() {
+    // Validate glTF extras against previously-exported metadata without starting the game
+    let mut args = std::env::args().collect::<Vec<_>>();
+    if let Some(index) = args.iter().position(|arg| arg == "--validate-gltf") {
+        let root = args.get(index + 1).map(String::as_str).unwrap_or("assets");
+        std::process::exit(validate_gltf_cli(INTEGRATION_TYPES_PATH, root));
+    }
+
+    // Select a game mode, removing its arguments so they don't reach the simulation parser
+    let game = args.iter().position(|arg| arg == "--game").map(|index| {
+        let game = args.get(index + 1).cloned().unwrap_or_default();
+        args.drain(index..(index + 2).min(args.len()));
+        game
+    });
+
+    // Two local players, in place of any saved player bindings
+    let coop = if let Some(index) = args.iter().position(|arg| arg == "--coop") {
+        args.remove(index);
+        true
+    } else {
+        false
+    };
+
+    let shrike = match game.as_deref() {
+        None | Some("legend_of_r") => false,
+        Some("shrike") => true,
+        Some(game) => {
+            eprintln!("Unknown game {game:?}");
+            eprintln!("Usage: --game <legend_of_r|shrike>");
+            std::process::exit(2);
+        }
+    };
+
+    // Run headless for a fixed amount of timeline time and dump the resulting game state
+    let simulation = args
+        .iter()
+        .position(|arg| arg == "--simulate")
+        .map(|index| match SimulationPlugin::from_args(&args[index + 1..]) {
+            Ok(simulation) => simulation,
+            Err(e) => {
+                eprintln!("{e:?}");
+                eprintln!(
+                    "Usage: --simulate <seconds> {}",
+                    "[--script <path>] [--output <path>] [--step <seconds>]"
+                );
+                std::process::exit(2);
+            }
+        });
+    let headless = simulation.is_some();
+
     let mut app = App::default();
 
     info!("Main constructed app");
 
-    app.add_plugins(DefaultPlugins)
-        .add_plugin(bevy_framepace::FramepacePlugin)
-        .add_plugin(ShmupPlugin)
-        .add_plugin(games::legend_of_r::LegendOfRPlugin)
-        .add_plugin(user_interface::UserInterfacePlugin)
-        .add_plugin(FrameTimeDiagnosticsPlugin::default())
-        .add_plugin(CollisionGroupsPlugin)
+    if let Some(simulation) = simulation {
+        // Inserted ahead of the simulation, which strips device bindings from whichever maps exist
+        if coop {
+            app.insert_resource(PlayerInputMaps::coop());
+        }
+
+        app.insert_resource(WgpuSettings {
+            backends: None,
+            ..default()
+        })
+        .add_plugins_with(DefaultPlugins, |group| {
+            // Time is driven by the simulation's fixed-step clock instead
+            group
+                .disable::<WinitPlugin>()
+                .disable::<GilrsPlugin>()
+                .disable::<TimePlugin>()
+        })
+        .add_plugin(ShmupPlugin::headless())
+        .add_plugin(simulation);
+    } else {
+        // ProfilingPlugin installs the global tracing subscriber in place of LogPlugin
+        app.add_plugin(profiling::ProfilingPlugin::default())
+            .add_plugins_with(DefaultPlugins, |group| group.disable::<LogPlugin>())
+            .add_plugin(bevy_framepace::FramepacePlugin)
+            .add_plugin(ShmupPlugin::default())
+            .add_plugin(user_interface::UserInterfacePlugin::default())
+            .add_plugin(picking::PickingPlugin)
+            .add_plugin(InputBindingsPlugin {
+                coop,
+                ..default()
+            });
+    }
+
+    if shrike {
+        app.add_plugin(games::shrike::ShrikePlugin);
+    } else {
+        app.add_plugin(games::legend_of_r::LegendOfRPlugin);
+    }
+
+    app.add_plugin(FrameTimeDiagnosticsPlugin::default())
+        .add_plugin(CollisionGroupsPlugin::default())
+        .add_plugin(IntegrationMetadataPlugin {
+            path: INTEGRATION_TYPES_PATH,
+        })
         .add_plugin(GltfJsonPlugin)
-        .add_plugin(GltfEntityPlugin);
+        .add_plugin(GltfEntityPlugin)
+        .add_plugin(SceneInstancePlugin)
+        .add_plugin(LineMaterialPlugin);
 
     info!("Main added first plugin set");
 
@@ -97,6 +200,9 @@ fn main() {
         .register_type::<HitboxBundle>()
         .register_type::<HurtboxBundle>();
 
+    app.register_archive_bundle::<(Collider,)>()
+        .register_archive_bundle::<ColorMesh2dBundle>();
+
     info!("Main registered types");
 
     let mut type_registry = app.world.resource::<TypeRegistry>().write();
@@ -150,13 +256,15 @@ fn main() {
     app.insert_resource(Msaa { samples: 4 })
         .insert_resource(ClearColor(Color::BLACK));
 
-    info!("Main adding export plugins");
+    if !headless {
+        info!("Main adding export plugins");
 
-    app.add_plugin(ExportIntegrationPlugin {
-        path: "assets/blender/types_shmup.json",
-    });
+        app.add_plugin(ExportIntegrationPlugin {
+            path: INTEGRATION_TYPES_PATH,
+        });
 
-    app.add_plugin(DebugDumpPlugin);
+        app.add_plugin(DebugDumpPlugin);
+    }
 
     app.run();
 }
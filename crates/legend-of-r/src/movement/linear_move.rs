@@ -1,16 +1,27 @@
+//! Input-driven movement, recorded onto a timeline so it can be rewound
+
 use std::{hash::Hash, marker::PhantomData};
 
 use bevy::{
-    ecs::reflect::ReflectComponent,
+    ecs::{reflect::ReflectComponent, system::Command},
     prelude::{
-        default, Component, CoreStage, Input, ParallelSystemDescriptorCoercion, Plugin, Query, Res,
-        Transform, Vec3, With,
+        default, Component, CoreStage, Entity, ParallelSystemDescriptorCoercion, Plugin, Query,
+        Res, ResMut, Transform, Vec3, Without,
     },
     reflect::Reflect,
-    time::Time,
 };
 
-use crate::prelude::{player_input_press, player_input_release};
+use crate::prelude::{
+    evaluate_tagged, player_input_update, Determinisms, Discrete, DiscreteStop, DynamicAnimation,
+    DynamicSegmentPlayback, InsertDynamicAnimation, MomentumMove, PlayerId, PlayerInputs,
+    Timeline, Update,
+};
+
+/// Maximum length in seconds of a single recorded movement segment
+pub const LINEAR_MOVE_HORIZON: f64 = 1.0;
+
+/// Distance or speed below which recorded and actual movement are considered equal
+pub const LINEAR_MOVE_EPSILON: f32 = 0.0001;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LinearMoveInputMap<T> {
@@ -47,10 +58,9 @@ where
         app.add_system_to_stage(
             CoreStage::PreUpdate,
             linear_move_input::<T>
-                .after(player_input_press)
-                .after(player_input_release),
+                .after(player_input_update),
         )
-        .add_system(linear_move_integrate);
+        .add_system(linear_move_timeline.before(evaluate_tagged::<Update>));
     }
 }
 
@@ -67,6 +77,8 @@ where
     pub right: Option<T>,
     pub up: Option<T>,
     pub down: Option<T>,
+    /// Impulse input, used by MomentumMove
+    pub dash: Option<T>,
 }
 
 impl<T> Default for LinearMoveInput<T>
@@ -81,6 +93,7 @@ where
             right: default(),
             up: default(),
             down: default(),
+            dash: default(),
         }
     }
 }
@@ -89,7 +102,11 @@ where
 #[reflect(Component)]
 pub struct LinearMove {
     pub active: bool,
+    /// Velocity in units per timeline second
     pub delta: Vec3,
+    /// Bounds that translation is clamped to
+    pub min: Vec3,
+    pub max: Vec3,
 }
 
 impl Default for LinearMove {
@@ -97,17 +114,60 @@ impl Default for LinearMove {
         Self {
             active: true,
             delta: default(),
+            min: Vec3::splat(f32::NEG_INFINITY),
+            max: Vec3::splat(f32::INFINITY),
+        }
+    }
+}
+
+/// Drive an entity's translation from a dynamic animation recorded by its LinearMove
+pub struct InsertTimelineLinearMove {
+    pub timeline: Entity,
+    pub entity: Entity,
+}
+
+impl Command for InsertTimelineLinearMove {
+    fn write(self, world: &mut bevy::prelude::World) {
+        InsertDynamicAnimation::<Transform, Vec3> {
+            timeline: self.timeline,
+            entity: self.entity,
+            target: self.entity,
+            accessor: |transform| &transform.translation,
+            mutator: |transform| &mut transform.translation,
+        }
+        .write(world);
+
+        // Start from the spawn position rather than the origin
+        let translation = world.get::<Transform>(self.entity).unwrap().translation;
+        let dynamic = *world
+            .get::<DynamicAnimation<Transform, Vec3>>(self.entity)
+            .unwrap();
+
+        for stops in [dynamic.from_stops, dynamic.to_stops] {
+            let mut stops = world
+                .get_mut::<Discrete<DiscreteStop<Vec3>>>(stops)
+                .unwrap();
+
+            for stop in stops.stops_mut() {
+                stop.value.value = translation;
+            }
         }
     }
 }
 
 pub fn linear_move_input<T>(
-    input: Res<Input<T>>,
-    mut query: Query<(&LinearMoveInput<T>, &mut LinearMove), With<LinearMoveInput<T>>>,
+    inputs: Res<PlayerInputs<T>>,
+    mut query: Query<(&PlayerId, &LinearMoveInput<T>, &mut LinearMove), Without<MomentumMove>>,
 ) where
     T: 'static + Send + Sync + Copy + Eq + Hash + Reflect,
 {
-    for (linear_move_input, mut linear_move) in query.iter_mut() {
+    for (player, linear_move_input, mut linear_move) in query.iter_mut() {
+        let input = if let Some(input) = inputs.get(*player) {
+            input
+        } else {
+            continue;
+        };
+
         if linear_move_input.factor == 0.0 {
             linear_move.delta = Vec3::ZERO;
             continue;
@@ -147,15 +207,83 @@ pub fn linear_move_input<T>(
     }
 }
 
-pub fn linear_move_integrate(time: Res<Time>, mut query: Query<(&LinearMove, &mut Transform)>) {
-    let dt = time.delta_seconds();
-    for (linear_move, mut transform) in query.iter_mut() {
+/// Record LinearMove velocity changes as dynamic animation stops on the entity's timeline.
+///
+/// Translation is evaluated from the recorded segments rather than integrated each frame,
+/// so rewinding restores earlier positions exactly.
+/// Segments end where they meet the LinearMove bounds,
+/// and movement that didn't come from a segment is recorded as a new starting point.
+pub fn linear_move_timeline(
+    mut determinisms: ResMut<Determinisms>,
+    query_move: Query<(&LinearMove, &DynamicAnimation<Transform, Vec3>, &Transform)>,
+    query_timeline: Query<&Timeline>,
+    mut query_stops: Query<&mut Discrete<DiscreteStop<Vec3>>>,
+) {
+    for (linear_move, dynamic, transform) in query_move.iter() {
         if !linear_move.active {
             continue;
         }
 
-        let rotation = transform.rotation;
+        let DynamicSegmentPlayback { t, prev_t, segment } =
+            if let Some(playback) = dynamic.recordable_segment(&query_timeline, &query_stops) {
+                playback
+            } else {
+                continue;
+            };
+
+        // Detect outside movement, such as depenetration or a respawn
+        let displaced =
+            transform.translation.distance(segment.value_at(prev_t)) > LINEAR_MOVE_EPSILON;
+
+        let origin = if displaced {
+            transform.translation
+        } else {
+            segment.value_at(t)
+        }
+        .clamp(linear_move.min, linear_move.max);
+
+        // Don't push into bounds that have already been reached
+        let mut velocity = transform.rotation * linear_move.delta;
+        for axis in 0..3 {
+            if (origin[axis] <= linear_move.min[axis] && velocity[axis] < 0.0)
+                || (origin[axis] >= linear_move.max[axis] && velocity[axis] > 0.0)
+            {
+                velocity[axis] = 0.0;
+            }
+        }
+
+        let ended = t >= segment.to.t && velocity != Vec3::ZERO;
+        let changed = velocity.distance(segment.rate_at(t)) > LINEAR_MOVE_EPSILON;
+
+        if !displaced && !ended && !changed {
+            continue;
+        }
+
+        // Move until the first bound is met, or the horizon is reached
+        let duration = (0..3)
+            .filter(|axis| velocity[*axis] != 0.0)
+            .map(|axis| {
+                let bound = if velocity[axis] > 0.0 {
+                    linear_move.max[axis]
+                } else {
+                    linear_move.min[axis]
+                };
+
+                ((bound - origin[axis]) / velocity[axis]) as f64
+            })
+            .fold(LINEAR_MOVE_HORIZON, f64::min)
+            .max(f32::EPSILON as f64);
+
+        let target =
+            (origin + velocity * duration as f32).clamp(linear_move.min, linear_move.max);
 
-        transform.translation += rotation * linear_move.delta * dt;
+        dynamic.record_segment(
+            &mut determinisms,
+            &mut query_stops,
+            t,
+            origin,
+            target,
+            duration,
+        );
     }
 }
@@ -1,17 +1,18 @@
 use bevy::{
     ecs::reflect::ReflectComponent,
-    prelude::{Component, Plugin, Query, Res, Transform, Vec3},
+    prelude::{Component, ParallelSystemDescriptorCoercion, Plugin, Query, Transform, Vec3},
     reflect::Reflect,
-    time::Time,
 };
 
+use crate::prelude::{linear_move_timeline, LinearMove};
+
 pub struct LinearMoveToPlugin;
 
 impl Plugin for LinearMoveToPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.register_type::<LinearMoveTo>();
 
-        app.add_system(linear_move_to);
+        app.add_system(linear_move_to.before(linear_move_timeline));
     }
 }
 
@@ -24,18 +25,35 @@ pub struct LinearMoveTo {
     pub factor: Vec3,
 }
 
-pub fn linear_move_to(time: Res<Time>, mut query: Query<(&LinearMoveTo, &mut Transform)>) {
-    for (mover, mut transform) in query.iter_mut() {
+/// Steer a LinearMove toward its target, bounding it so movement stops exactly on arrival
+pub fn linear_move_to(mut query: Query<(&LinearMoveTo, &Transform, &mut LinearMove)>) {
+    for (mover, transform, mut linear_move) in query.iter_mut() {
+        if linear_move.active != mover.active {
+            linear_move.active = mover.active;
+        }
+
         if !mover.active {
             continue;
         }
 
-        let dt = time.delta_seconds();
         let delta = (mover.target - transform.translation) * mover.factor;
         let sign = delta.normalize_or_zero();
 
-        let delta_move = sign * mover.speed * dt;
-        transform.translation += delta_move.abs().min(delta.abs()) * sign;
+        let mut min = Vec3::splat(f32::NEG_INFINITY);
+        let mut max = Vec3::splat(f32::INFINITY);
+        for axis in 0..3 {
+            if sign[axis] > 0.0 {
+                max[axis] = mover.target[axis];
+            } else if sign[axis] < 0.0 {
+                min[axis] = mover.target[axis];
+            }
+        }
+
+        let new_delta = sign * mover.speed;
+        if linear_move.delta != new_delta || linear_move.min != min || linear_move.max != max {
+            linear_move.delta = new_delta;
+            linear_move.min = min;
+            linear_move.max = max;
+        }
     }
 }
-
@@ -1,2 +1,3 @@
 pub mod linear_move;
 pub mod linear_move_to;
+pub mod momentum_move;
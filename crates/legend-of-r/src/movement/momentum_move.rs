@@ -0,0 +1,182 @@
+//! Acceleration-based alternative to LinearMove's digital input response
+//!
+//! Entities with a MomentumMove read their LinearMoveInput bindings here instead of in
+//! linear_move_input, and steer their LinearMove velocity toward the input direction,
+//! so movement is still recorded onto the timeline.
+
+use std::{hash::Hash, marker::PhantomData};
+
+use bevy::{
+    ecs::reflect::ReflectComponent,
+    prelude::{
+        default, Component, CoreStage, ParallelSystemDescriptorCoercion, Plugin, Query,
+        ReflectDefault, Res, Transform, Vec3,
+    },
+    reflect::Reflect,
+    time::Time,
+};
+
+use crate::prelude::{
+    player_input_update, DynamicAnimation, LinearMove, LinearMoveInput, PlayerId, PlayerInputs,
+    Timeline,
+};
+
+pub struct MomentumMovePlugin<T> {
+    pub _phantom: PhantomData<T>,
+}
+
+impl<T> Default for MomentumMovePlugin<T> {
+    fn default() -> Self {
+        Self {
+            _phantom: default(),
+        }
+    }
+}
+
+impl<T> Plugin for MomentumMovePlugin<T>
+where
+    T: 'static + Send + Sync + Copy + Eq + Hash + Reflect,
+{
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.register_type::<MomentumMove>();
+
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            momentum_move::<T>.after(player_input_update),
+        );
+    }
+}
+
+/// Accelerates toward the input direction instead of moving at full speed immediately.
+///
+/// Top speed is taken from LinearMoveInput::factor, so it follows ShiftSpeed gears.
+#[derive(Debug, Copy, Clone, PartialEq, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct MomentumMove {
+    /// Units per second squared when speeding up or turning
+    pub acceleration: f32,
+    /// Units per second squared when slowing down, or shedding speed above the maximum
+    pub deceleration: f32,
+    /// Speed added along the input direction when dashing, or zero to disable dashing
+    pub dash_impulse: f32,
+    /// Scale speed with analog stick deflection instead of treating inputs as digital
+    pub analog: bool,
+}
+
+impl Default for MomentumMove {
+    fn default() -> Self {
+        Self {
+            acceleration: 48.0,
+            deceleration: 64.0,
+            dash_impulse: 12.0,
+            analog: true,
+        }
+    }
+}
+
+/// Move `from` toward `to` by at most `max_delta`
+fn move_towards(from: Vec3, to: Vec3, max_delta: f32) -> Vec3 {
+    let delta = to - from;
+    let length = delta.length();
+
+    if length <= max_delta || length == 0.0 {
+        to
+    } else {
+        from + delta / length * max_delta
+    }
+}
+
+pub fn momentum_move<T>(
+    time: Res<Time>,
+    inputs: Res<PlayerInputs<T>>,
+    query_timeline: Query<&Timeline>,
+    mut query: Query<(
+        &PlayerId,
+        &LinearMoveInput<T>,
+        &MomentumMove,
+        &Transform,
+        &mut LinearMove,
+        Option<&DynamicAnimation<Transform, Vec3>>,
+    )>,
+) where
+    T: 'static + Send + Sync + Copy + Eq + Hash + Reflect,
+{
+    for (player, input, momentum, transform, mut linear_move, dynamic) in query.iter_mut() {
+        // Follow timeline time when movement is recorded onto one
+        let dt = match dynamic.and_then(|dynamic| query_timeline.get(dynamic.timeline).ok()) {
+            Some(timeline) if timeline.paused => continue,
+            Some(timeline) => time.delta_seconds() * timeline.tick_rate as f32,
+            None => time.delta_seconds(),
+        };
+
+        let value = |input: Option<T>| {
+            input
+                .map(|input| {
+                    if momentum.analog {
+                        inputs.value(*player, input)
+                    } else {
+                        inputs.pressed(*player, input) as usize as f32
+                    }
+                })
+                .unwrap_or_default()
+        };
+
+        let direction = Vec3::new(
+            value(input.right) - value(input.left),
+            value(input.up) - value(input.down),
+            0.0,
+        );
+
+        // Analog input can ask for less than full speed, but never more
+        let direction = if input.normalize && direction.length() > 1.0 {
+            direction.normalize()
+        } else {
+            direction
+        };
+
+        let target = direction * input.factor;
+        let mut velocity = linear_move.delta;
+
+        let dash = input
+            .dash
+            .map(|dash| inputs.just_pressed(*player, dash))
+            .unwrap_or_default();
+
+        if dash && momentum.dash_impulse > 0.0 {
+            let dash_direction = if direction != Vec3::ZERO {
+                direction.normalize()
+            } else if velocity != Vec3::ZERO {
+                velocity.normalize()
+            } else {
+                Vec3::X
+            };
+
+            velocity += dash_direction * momentum.dash_impulse;
+        }
+
+        let accelerating = target != Vec3::ZERO
+            && target.dot(velocity) >= 0.0
+            && velocity.length() <= input.factor;
+
+        let rate = if accelerating {
+            momentum.acceleration
+        } else {
+            momentum.deceleration
+        };
+
+        velocity = move_towards(velocity, target, rate * dt);
+
+        // Don't build up speed against bounds that have already been reached
+        for axis in 0..3 {
+            if (transform.translation[axis] <= linear_move.min[axis] && velocity[axis] < 0.0)
+                || (transform.translation[axis] >= linear_move.max[axis] && velocity[axis] > 0.0)
+            {
+                velocity[axis] = 0.0;
+            }
+        }
+
+        if linear_move.delta != velocity {
+            linear_move.delta = velocity;
+        }
+    }
+}
@@ -0,0 +1,657 @@
+//! Click-to-select and transform gizmos for editing entities inside the playfield
+
+use std::borrow::Cow;
+
+use bevy::{
+    prelude::{
+        default, Camera, Children, Entity, GlobalTransform, IntoExclusiveSystem, Mat4, Name,
+        Parent, Plugin, Quat, Res, Transform, Vec3, With, World,
+    },
+    time::Time,
+};
+use bevy_egui::EguiContext;
+use bevy_inspector_egui::egui::{
+    Color32, Id, LayerId, Order, Pos2, Rect, Response, Stroke, Ui, Vec2,
+};
+use bevy_rapier2d::prelude::{Collider, QueryFilter, RapierContext};
+
+use crate::{
+    animation::adapters::offset::Offset,
+    prelude::{
+        AnimateComponents, AnimationTime, Dilate, Discrete, DiscreteStop, PlaneCollider2d,
+        PlayfieldCamera2d, Repeat, TimeSource, Timeline, TimelineTime, TryAnimateComponents,
+        Widget, Widgets, WorldTime,
+    },
+};
+
+/// Screen-space length of the translation axis handles
+pub const GIZMO_AXIS_LENGTH: f32 = 48.0;
+
+/// Screen-space radius of the rotation ring
+pub const GIZMO_ROTATE_RADIUS: f32 = 64.0;
+
+/// Distance from a handle within which the pointer will grab it
+pub const GIZMO_HANDLE_RADIUS: f32 = 8.0;
+
+pub struct PickingPlugin;
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Picking>();
+        // Run after the debug UI has been laid out so pointer-over-panel checks are current
+        app.add_system(picking.exclusive_system().at_end());
+        app.add_startup_system(setup_picking_widget);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+}
+
+impl Default for GizmoMode {
+    fn default() -> Self {
+        GizmoMode::Translate
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum GizmoHandle {
+    Free,
+    AxisX,
+    AxisY,
+    Rotate,
+}
+
+/// State captured when a gizmo handle is grabbed
+#[derive(Debug, Copy, Clone)]
+struct GizmoDrag {
+    handle: GizmoHandle,
+    origin: Pos2,
+    anchor: Pos2,
+    axis_x: Vec2,
+    axis_y: Vec2,
+    start: Transform,
+}
+
+/// Resource holding the current playfield selection
+#[derive(Debug, Default, Clone)]
+pub struct Picking {
+    pub selected: Option<Entity>,
+    /// 2D collider the selection was picked through, used to anchor the gizmo
+    pub collider: Option<Entity>,
+    pub mode: GizmoMode,
+    drag: Option<GizmoDrag>,
+}
+
+pub fn setup_picking_widget(widgets: Res<Widgets>) {
+    widgets
+        .panel_outer_min
+        .add_widget(PickingWidget::default());
+}
+
+pub fn picking(world: &mut World) {
+    let ctx = world.resource_mut::<EguiContext>().ctx_mut().clone();
+
+    let (camera, camera_transform) = if let Some((camera, camera_transform)) = world
+        .query_filtered::<(&Camera, &GlobalTransform), With<PlayfieldCamera2d>>()
+        .iter(world)
+        .next()
+    {
+        (camera.clone(), *camera_transform)
+    } else {
+        return;
+    };
+
+    let mut picking = world.resource::<Picking>().clone();
+
+    // Drop selections that have since been despawned
+    if picking
+        .selected
+        .map(|entity| world.get_entity(entity).is_none())
+        .unwrap_or_default()
+    {
+        picking = Picking {
+            mode: picking.mode,
+            ..default()
+        };
+    }
+
+    let (pointer, pressed, down, clicked) = {
+        let input = ctx.input();
+        (
+            input.pointer.interact_pos(),
+            input.pointer.any_pressed() && input.pointer.primary_down(),
+            input.pointer.primary_down(),
+            input.pointer.primary_clicked(),
+        )
+    };
+
+    let over_ui = ctx.is_pointer_over_area();
+
+    let anchor = picking
+        .collider
+        .or(picking.selected)
+        .and_then(|entity| world.get::<GlobalTransform>(entity))
+        .and_then(|transform| world_to_screen(&camera, &camera_transform, transform.translation()));
+
+    let axis_x = anchor
+        .map(|anchor| screen_axis(&camera, &camera_transform, anchor, Vec3::X))
+        .unwrap_or(Vec2::X);
+
+    let axis_y = anchor
+        .map(|anchor| screen_axis(&camera, &camera_transform, anchor, Vec3::Y))
+        .unwrap_or(-Vec2::Y);
+
+    // Gizmo interaction
+    let mut dragged = false;
+    if let (Some(selected), Some(anchor), Some(pointer)) = (picking.selected, anchor, pointer) {
+        if pressed && !over_ui && picking.drag.is_none() {
+            if let Some(handle) = gizmo_handle(picking.mode, anchor, axis_x, axis_y, pointer) {
+                if let Some(start) = world.get::<Transform>(selected).copied() {
+                    picking.drag = Some(GizmoDrag {
+                        handle,
+                        origin: pointer,
+                        anchor,
+                        axis_x,
+                        axis_y,
+                        start,
+                    });
+                }
+            }
+        }
+
+        if let Some(drag) = picking.drag {
+            dragged = true;
+
+            if down {
+                let transform =
+                    drag_transform(world, &camera, &camera_transform, selected, drag, pointer);
+                apply_transform(world, selected, transform);
+            } else {
+                picking.drag = None;
+            }
+        }
+    } else {
+        picking.drag = None;
+    }
+
+    // Selection
+    if clicked && !dragged && !over_ui {
+        if let Some(point) =
+            pointer.and_then(|pointer| screen_to_world(&camera, &camera_transform, pointer))
+        {
+            let collider = pick(world, point, picking.collider);
+            picking.collider = collider;
+            picking.selected = collider.map(|collider| {
+                world
+                    .get::<PlaneCollider2d>(collider)
+                    .map(|plane_collider| plane_collider.collider_3d)
+                    .unwrap_or(collider)
+            });
+        }
+    }
+
+    // Highlight and gizmo
+    let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("picking_gizmos")));
+
+    if let Some(anchor) = anchor.filter(|_| picking.selected.is_some()) {
+        let radius = picking
+            .collider
+            .and_then(|collider| {
+                let shape = world.get::<Collider>(collider)?;
+                let transform = world.get::<GlobalTransform>(collider)?;
+                let radius = shape.raw.compute_local_bounding_sphere().radius
+                    * transform.compute_transform().scale.max_element();
+                let edge = world_to_screen(
+                    &camera,
+                    &camera_transform,
+                    transform.translation() + Vec3::X * radius,
+                )?;
+                Some(edge.distance(anchor))
+            })
+            .unwrap_or_default()
+            .max(GIZMO_HANDLE_RADIUS * 1.5);
+
+        painter.circle_stroke(anchor, radius, Stroke::new(1.0, Color32::YELLOW));
+
+        let active = picking.drag.map(|drag| drag.handle);
+        let stroke = |handle: GizmoHandle, color: Color32| {
+            if active == Some(handle) {
+                Stroke::new(3.0, Color32::WHITE)
+            } else {
+                Stroke::new(2.0, color)
+            }
+        };
+
+        match picking.mode {
+            GizmoMode::Translate => {
+                painter.arrow(
+                    anchor,
+                    axis_x * GIZMO_AXIS_LENGTH,
+                    stroke(GizmoHandle::AxisX, Color32::RED),
+                );
+                painter.arrow(
+                    anchor,
+                    axis_y * GIZMO_AXIS_LENGTH,
+                    stroke(GizmoHandle::AxisY, Color32::GREEN),
+                );
+                painter.rect_stroke(
+                    Rect::from_center_size(anchor, Vec2::splat(GIZMO_HANDLE_RADIUS)),
+                    0.0,
+                    stroke(GizmoHandle::Free, Color32::YELLOW),
+                );
+            }
+            GizmoMode::Rotate => {
+                painter.circle_stroke(
+                    anchor,
+                    GIZMO_ROTATE_RADIUS,
+                    stroke(GizmoHandle::Rotate, Color32::LIGHT_BLUE),
+                );
+
+                if let (Some(drag), Some(pointer)) = (picking.drag, pointer) {
+                    let from = Stroke::new(1.0, Color32::GRAY);
+                    let to = Stroke::new(1.0, Color32::WHITE);
+                    painter.line_segment([drag.anchor, drag.origin], from);
+                    painter.line_segment([drag.anchor, pointer], to);
+                }
+            }
+        }
+    }
+
+    *world.resource_mut::<Picking>() = picking;
+}
+
+/// Find the collider under a playfield-space point,
+/// cycling through overlapping colliders on repeated clicks
+fn pick(world: &World, point: Vec3, current: Option<Entity>) -> Option<Entity> {
+    let mut candidates = vec![];
+    world.resource::<RapierContext>().intersections_with_point(
+        point.truncate(),
+        QueryFilter::default(),
+        |entity| {
+            candidates.push(entity);
+            true
+        },
+    );
+
+    candidates.sort();
+
+    candidates
+        .iter()
+        .position(|candidate| Some(*candidate) == current)
+        .and_then(|i| candidates.get(i + 1))
+        .or(candidates.first())
+        .copied()
+}
+
+fn gizmo_handle(
+    mode: GizmoMode,
+    anchor: Pos2,
+    axis_x: Vec2,
+    axis_y: Vec2,
+    pointer: Pos2,
+) -> Option<GizmoHandle> {
+    match mode {
+        GizmoMode::Translate => {
+            if pointer.distance(anchor) <= GIZMO_HANDLE_RADIUS {
+                Some(GizmoHandle::Free)
+            } else if segment_distance(anchor, anchor + axis_x * GIZMO_AXIS_LENGTH, pointer)
+                <= GIZMO_HANDLE_RADIUS
+            {
+                Some(GizmoHandle::AxisX)
+            } else if segment_distance(anchor, anchor + axis_y * GIZMO_AXIS_LENGTH, pointer)
+                <= GIZMO_HANDLE_RADIUS
+            {
+                Some(GizmoHandle::AxisY)
+            } else {
+                None
+            }
+        }
+        GizmoMode::Rotate => ((pointer.distance(anchor) - GIZMO_ROTATE_RADIUS).abs()
+            <= GIZMO_HANDLE_RADIUS)
+            .then_some(GizmoHandle::Rotate),
+    }
+}
+
+/// Calculate the local transform produced by dragging a gizmo handle to the given pointer position
+///
+/// Deltas are measured in playfield space and converted into the space of the entity's parent
+fn drag_transform(
+    world: &World,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    entity: Entity,
+    drag: GizmoDrag,
+    pointer: Pos2,
+) -> Transform {
+    let parent_inverse = world
+        .get::<Parent>(entity)
+        .and_then(|parent| world.get::<GlobalTransform>(parent.get()))
+        .map(|parent| parent.compute_matrix().inverse())
+        .unwrap_or(Mat4::IDENTITY);
+
+    let mut transform = drag.start;
+    let delta = pointer - drag.origin;
+
+    match drag.handle {
+        GizmoHandle::Rotate => {
+            // Screen space is Y-down, so the angle is flipped relative to the playfield
+            let angle = (drag.origin - drag.anchor).angle() - (pointer - drag.anchor).angle();
+
+            let axis = parent_inverse.transform_vector3(Vec3::Z).normalize_or_zero();
+            let axis = if axis == Vec3::ZERO { Vec3::Z } else { axis };
+
+            transform.rotation = Quat::from_axis_angle(axis, angle) * drag.start.rotation;
+        }
+        handle => {
+            let delta = match handle {
+                GizmoHandle::AxisX => drag.axis_x * delta.dot(drag.axis_x),
+                GizmoHandle::AxisY => drag.axis_y * delta.dot(drag.axis_y),
+                _ => delta,
+            };
+
+            if let (Some(from), Some(to)) = (
+                screen_to_world(camera, camera_transform, drag.anchor),
+                screen_to_world(camera, camera_transform, drag.anchor + delta),
+            ) {
+                transform.translation =
+                    drag.start.translation + parent_inverse.transform_vector3(to - from);
+            }
+        }
+    }
+
+    transform
+}
+
+/// Find the discrete stops driving an entity's Transform, if it is animated
+pub fn transform_stops(world: &World, entity: Entity) -> Option<Entity> {
+    let animates_entity = |animation: Entity| {
+        world
+            .get::<AnimateComponents<Transform>>(animation)
+            .map(|animate| animate.targets.contains(&entity))
+            .or_else(|| {
+                world
+                    .get::<TryAnimateComponents<Option<Transform>>>(animation)
+                    .map(|animate| animate.targets.contains(&entity))
+            })
+            .unwrap_or_default()
+    };
+
+    let has_stops = |animation: Entity| world.get::<Discrete<Transform>>(animation).is_some();
+
+    world.get::<Children>(entity)?.iter().find_map(|child| {
+        let animation = find_in_hierarchy(world, *child, &animates_entity)?;
+        find_in_hierarchy(world, animation, &has_stops)
+    })
+}
+
+/// Calculate the local time at which a set of discrete stops is currently being sampled
+///
+/// Walks up the animation hierarchy to its time source,
+/// then applies any temporal adapters between the two in evaluation order
+pub fn local_time(world: &World, stops: Entity) -> Option<f64> {
+    let mut adapters = vec![];
+    let mut entity = stops;
+
+    let time = loop {
+        if let Some(time) = source_time::<Transform>(world, entity)
+            .or_else(|| source_time::<Option<Transform>>(world, entity))
+        {
+            break time;
+        }
+
+        adapters.push(entity);
+        entity = world.get::<Parent>(entity)?.get();
+    };
+
+    let time = adapters.into_iter().rev().fold(time, |time, adapter| {
+        let time = map_time::<Transform>(world, adapter, time);
+        map_time::<Option<Transform>>(world, adapter, time)
+    });
+
+    Some(time.t)
+}
+
+fn source_time<T>(world: &World, entity: Entity) -> Option<AnimationTime>
+where
+    T: 'static + Send + Sync,
+{
+    if let Some(source) = world.get::<TimeSource<TimelineTime, T>>(entity) {
+        let timeline = world.get::<Timeline>(source.source.timeline)?;
+        Some(AnimationTime {
+            t: timeline.t,
+            paused: timeline.paused,
+            ..default()
+        })
+    } else if world.get::<TimeSource<WorldTime, T>>(entity).is_some() {
+        Some(AnimationTime {
+            t: world.resource::<Time>().seconds_since_startup(),
+            ..default()
+        })
+    } else {
+        None
+    }
+}
+
+fn map_time<T>(world: &World, entity: Entity, time: AnimationTime) -> AnimationTime
+where
+    T: 'static + Send + Sync + Default,
+{
+    if let Some(offset) = world.get::<Offset<T>>(entity) {
+        offset.map_time(time)
+    } else if let Some(dilate) = world.get::<Dilate<T>>(entity) {
+        dilate.map_time(time)
+    } else if let Some(repeat) = world.get::<Repeat<T>>(entity) {
+        repeat.map_time(time)
+    } else {
+        time
+    }
+}
+
+/// Write an edited transform back to an entity
+///
+/// If the entity's Transform is animated, a stop is inserted at the current local time
+/// so the edit survives rewinding. The stop joins the determinism group of the stop
+/// it overrides, so it is only pruned alongside the authored animation
+pub fn apply_transform(world: &mut World, entity: Entity, transform: Transform) {
+    if let Some(stops) = transform_stops(world, entity) {
+        let t = local_time(world, stops);
+        let mut discrete = world.get_mut::<Discrete<Transform>>(stops).unwrap();
+        let t = t.unwrap_or(discrete.t);
+
+        if let Some(stop) = discrete.stops_mut().find(|stop| stop.t == t) {
+            stop.value = transform;
+        } else {
+            let determinism = discrete
+                .stop_at(t)
+                .or_else(|| discrete.stops().next())
+                .map(|stop| stop.determinism)
+                .unwrap_or_default();
+
+            discrete.insert_stop(DiscreteStop {
+                t,
+                value: transform,
+                determinism,
+                ..default()
+            });
+        }
+
+        discrete.update_stops();
+    }
+
+    if let Some(mut entity_transform) = world.get_mut::<Transform>(entity) {
+        *entity_transform = transform;
+    }
+}
+
+fn find_in_hierarchy(
+    world: &World,
+    entity: Entity,
+    predicate: &dyn Fn(Entity) -> bool,
+) -> Option<Entity> {
+    if predicate(entity) {
+        return Some(entity);
+    }
+
+    world
+        .get::<Children>(entity)?
+        .iter()
+        .find_map(|child| find_in_hierarchy(world, *child, predicate))
+}
+
+fn segment_distance(from: Pos2, to: Pos2, point: Pos2) -> f32 {
+    let segment = to - from;
+    let t = ((point - from).dot(segment) / segment.length_sq()).clamp(0.0, 1.0);
+    point.distance(from + segment * t)
+}
+
+/// Project a world position into egui screen space via the given camera
+fn world_to_screen(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    position: Vec3,
+) -> Option<Pos2> {
+    let (min, max) = camera.logical_viewport_rect()?;
+    let size = max - min;
+    let view_projection = camera.projection_matrix() * camera_transform.compute_matrix().inverse();
+    let ndc = view_projection.project_point3(position);
+    Some(Pos2::new(
+        min.x + (ndc.x + 1.0) * 0.5 * size.x,
+        min.y + (1.0 - ndc.y) * 0.5 * size.y,
+    ))
+}
+
+/// Unproject an egui screen position onto the Z = 0 plane of the given camera
+fn screen_to_world(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    position: Pos2,
+) -> Option<Vec3> {
+    let (min, max) = camera.logical_viewport_rect()?;
+    let size = max - min;
+    let ndc = Vec3::new(
+        (position.x - min.x) / size.x * 2.0 - 1.0,
+        1.0 - (position.y - min.y) / size.y * 2.0,
+        0.0,
+    );
+    let inverse_view_projection =
+        camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+    let world = inverse_view_projection.project_point3(ndc);
+    Some(Vec3::new(world.x, world.y, 0.0))
+}
+
+/// Screen-space direction of a world axis at the given screen position
+fn screen_axis(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    anchor: Pos2,
+    axis: Vec3,
+) -> Vec2 {
+    screen_to_world(camera, camera_transform, anchor)
+        .and_then(|world| world_to_screen(camera, camera_transform, world + axis))
+        .map(|tip| (tip - anchor).normalized())
+        .unwrap_or(Vec2::X)
+}
+
+#[derive(Debug, Copy, Clone)]
+enum PickingAction {
+    SetMode(GizmoMode),
+    SelectParent,
+    Clear,
+}
+
+/// Debug UI widget for displaying and manipulating the current selection
+#[derive(Debug, Default, Clone)]
+pub struct PickingWidget {
+    selected: Option<(Entity, String)>,
+    mode: GizmoMode,
+    stop_t: Option<f64>,
+    action: Option<PickingAction>,
+}
+
+impl Widget for PickingWidget {
+    fn update(&mut self, world: &mut World) {
+        if let Some(action) = self.action.take() {
+            let parent = world
+                .resource::<Picking>()
+                .selected
+                .and_then(|entity| world.get::<Parent>(entity))
+                .map(Parent::get);
+
+            let mut picking = world.resource_mut::<Picking>();
+            match action {
+                PickingAction::SetMode(mode) => picking.mode = mode,
+                PickingAction::SelectParent => {
+                    if let Some(parent) = parent {
+                        picking.selected = Some(parent);
+                        picking.collider = None;
+                        picking.drag = None;
+                    }
+                }
+                PickingAction::Clear => {
+                    *picking = Picking {
+                        mode: picking.mode,
+                        ..default()
+                    }
+                }
+            }
+        }
+
+        let picking = world.resource::<Picking>();
+        self.mode = picking.mode;
+
+        self.selected = picking.selected.map(|entity| {
+            let name = world
+                .get::<Name>(entity)
+                .map(ToString::to_string)
+                .unwrap_or_else(|| format!("{entity:?}"));
+            (entity, name)
+        });
+
+        self.stop_t = picking
+            .selected
+            .and_then(|entity| transform_stops(world, entity))
+            .and_then(|stops| local_time(world, stops));
+    }
+
+    fn title(&self) -> Cow<'static, str> {
+        Cow::Borrowed("Picking")
+    }
+
+    fn ui(&mut self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            match &self.selected {
+                Some((entity, name)) => ui.label(format!("{name} ({entity:?})")),
+                None => ui.label("Click inside the playfield to select an entity"),
+            };
+
+            ui.horizontal(|ui| {
+                for (mode, label) in [
+                    (GizmoMode::Translate, "Translate"),
+                    (GizmoMode::Rotate, "Rotate"),
+                ] {
+                    if ui.selectable_label(self.mode == mode, label).clicked() {
+                        self.action = Some(PickingAction::SetMode(mode));
+                    }
+                }
+            });
+
+            if let Some(t) = self.stop_t {
+                ui.label(format!("Animated, edits insert a stop at t = {t:.3}"));
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Select Parent").clicked() {
+                    self.action = Some(PickingAction::SelectParent);
+                }
+
+                if ui.button("Clear").clicked() {
+                    self.action = Some(PickingAction::Clear);
+                }
+            });
+        })
+        .response
+    }
+}
@@ -7,10 +7,14 @@ pub use crate::{
             try_animate_component_fields::*, try_animate_components::*, try_replace_components::*,
             *,
         },
-        animations::{axial_function::*, discrete::*, entity_component::*, from_function::*, *},
+        animations::{
+            angular_velocity::*, axial_function::*, discrete::*, entity_component::*,
+            from_function::*, rotation_stops::*, *,
+        },
         dynamic_animation::*,
         float_ord_64::*,
         timeline::*,
+        timeline_input::*,
         *,
     },
     body::*,
@@ -19,12 +23,13 @@ pub use crate::{
     debug_dump::*,
     egui_diagnostics::*,
     games::{
-        legend_of_r::{force::*, *},
-        shrike::*,
+        legend_of_r::{audio::*, campaign::*, force::*, stage::*, *},
+        shrike::{flight::*, meter::*, reactor::*, route::*, widget::*, *},
     },
     gltf_entity::*,
     gltf_json::*,
     hierarchy::*,
+    input_binding::*,
     integration::{
         reflect_boolean::*, reflect_bundle::*, reflect_float::*, reflect_signed_integer::*,
         reflect_string::*, reflect_unsigned_integer::*, *,
@@ -32,12 +37,19 @@ pub use crate::{
     iterator::{interval::*, offset::*, partition_iterator::*, *},
     scene::*,
     mesh_2d::*,
-    movement::{linear_move::*, linear_move_to::*, *},
+    movement::{linear_move::*, linear_move_to::*, momentum_move::*, *},
+    picking::*,
+    profiling::*,
     shmup::{
-        archive::*, background::*, camera::*, collision_group::*, damage::*, enemy::*,
-        entity_pool::*, lives::*, plane_collider::*, player_input::*, playfield::*, shift_speed::*,
-        ship::*, vulcan::*, *,
+        archive::*, audio::*, background::*, boss::*, camera::*, collision_group::*, damage::*,
+        enemy::*, entity_pool::*, hud::*, lives::*, mesh_rotation::*, particle::*,
+        plane_collider::*, player::*, player_input::*, playfield::*, playfield_layer::*, score::*,
+        shift_speed::*, ship::*, time_ability::*, vulcan::*, *,
     },
+    simulation::*,
     user_interface::*,
-    util::{delayed_input::*, depth_material::*, reciprocal::*, sign::*, component_bundle::*, *},
+    util::{
+        delayed_input::*, depth_material::*, line_material::*, reciprocal::*, sign::*,
+        component_bundle::*, *,
+    },
 };
@@ -0,0 +1,525 @@
+//! Span timing capture and a debug UI widget for inspecting per-system and per-adapter cost
+//!
+//! Bevy's LogPlugin does not allow extra tracing layers to be attached,
+//! so ProfilingPlugin takes over installation of the global subscriber
+//! and should be used in its place.
+//!
+//! Adapter spans are always recorded; per-system timings require bevy's executor spans,
+//! which are only emitted when building with the `trace` feature.
+
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    log::LogSettings,
+    prelude::{CoreStage, Plugin, Res, World},
+    utils::tracing::{
+        self,
+        field::{Field, Visit},
+        span::{Attributes, Id},
+        Subscriber,
+    },
+};
+use bevy_inspector_egui::egui::{ProgressBar, Response, Ui};
+use parking_lot::Mutex;
+use tracing_log::LogTracer;
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    EnvFilter, Layer, Registry,
+};
+
+use crate::prelude::{Widget, Widgets};
+
+/// Number of entries shown by the profiling widget
+pub const PROFILING_WIDGET_ENTRIES: usize = 24;
+
+pub struct ProfilingPlugin {
+    /// Number of frames to average timings over
+    pub frames: usize,
+}
+
+impl Default for ProfilingPlugin {
+    fn default() -> Self {
+        Self { frames: 120 }
+    }
+}
+
+impl Plugin for ProfilingPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        let profiler = Profiler::new(self.frames);
+
+        let default_filter = {
+            let settings = app.world.get_resource_or_insert_with(LogSettings::default);
+            format!("{},{}", settings.level, settings.filter)
+        };
+
+        let filter = EnvFilter::try_from_default_env()
+            .or_else(|_| EnvFilter::try_new(&default_filter))
+            .unwrap();
+
+        let subscriber = Registry::default()
+            .with(filter)
+            .with(profiler.layer())
+            .with(tracing_subscriber::fmt::Layer::default());
+
+        // Forward `log` records from dependencies, as LogPlugin would
+        if let Err(e) = LogTracer::init() {
+            eprintln!("Failed to install log forwarder: {e}");
+        }
+
+        if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
+            eprintln!("Failed to install profiling subscriber: {e}");
+        }
+
+        app.insert_resource(profiler);
+        app.add_system_to_stage(CoreStage::Last, profiler_end_frame);
+        app.add_startup_system(setup_profiling_widget);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProfileKind {
+    /// A bevy system, as reported by the executor's `system` and `exclusive_system` spans
+    System,
+    /// Any other span, such as those wrapping animation adapters
+    Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProfileKey {
+    pub kind: ProfileKind,
+    pub name: String,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ProfileTiming {
+    pub duration: Duration,
+    pub calls: usize,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ProfileFrame {
+    pub timings: HashMap<ProfileKey, ProfileTiming>,
+}
+
+#[derive(Debug, Default)]
+struct ProfilerHistory {
+    frames: usize,
+    frames_recorded: VecDeque<ProfileFrame>,
+}
+
+#[derive(Debug, Default)]
+struct ProfilerState {
+    paused: AtomicBool,
+    /// In-progress frame of each thread that has recorded a span,
+    /// so recording only contends with end_frame rather than other threads
+    threads: Mutex<Vec<Arc<Mutex<ProfileFrame>>>>,
+    history: Mutex<ProfilerHistory>,
+}
+
+/// Per-thread recording state
+#[derive(Default)]
+struct ThreadState {
+    /// Number of currently-entered spans for each key
+    open: HashMap<ProfileKey, usize>,
+    /// In-progress frame of each profiler this thread has recorded into, keyed by address
+    frames: Vec<(usize, Arc<Mutex<ProfileFrame>>)>,
+}
+
+thread_local! {
+    static THREAD_STATE: RefCell<ThreadState> = RefCell::default();
+}
+
+/// Resource for accessing span timings recorded by a ProfilingLayer
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    state: Arc<ProfilerState>,
+}
+
+impl Profiler {
+    pub fn new(frames: usize) -> Self {
+        Self {
+            state: Arc::new(ProfilerState {
+                history: Mutex::new(ProfilerHistory {
+                    frames: frames.max(1),
+                    frames_recorded: VecDeque::with_capacity(frames.max(1)),
+                }),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Create a tracing layer that records into this profiler
+    pub fn layer(&self) -> ProfilingLayer {
+        ProfilingLayer {
+            profiler: self.clone(),
+        }
+    }
+
+    pub fn paused(&self) -> bool {
+        self.state.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.state.paused.store(paused, Ordering::Relaxed);
+    }
+
+    fn record(&self, key: &ProfileKey, duration: Duration) {
+        if self.paused() {
+            return;
+        }
+
+        let id = Arc::as_ptr(&self.state) as usize;
+
+        THREAD_STATE
+            .try_with(|thread| {
+                let mut thread = thread.borrow_mut();
+
+                let frame = if let Some((_, frame)) =
+                    thread.frames.iter().find(|(profiler, _)| *profiler == id)
+                {
+                    frame.clone()
+                } else {
+                    let frame = Arc::new(Mutex::new(ProfileFrame::default()));
+                    self.state.threads.lock().push(frame.clone());
+                    thread.frames.push((id, frame.clone()));
+                    frame
+                };
+
+                let mut frame = frame.lock();
+                if let Some(timing) = frame.timings.get_mut(key) {
+                    timing.duration += duration;
+                    timing.calls += 1;
+                } else {
+                    frame
+                        .timings
+                        .insert(key.clone(), ProfileTiming { duration, calls: 1 });
+                }
+            })
+            .ok();
+    }
+
+    /// Merge each thread's in-progress frame and move the result into history
+    pub fn end_frame(&self) {
+        if self.paused() {
+            return;
+        }
+
+        let mut frame = ProfileFrame::default();
+        for thread in self.state.threads.lock().iter() {
+            let thread = std::mem::take(&mut *thread.lock());
+            for (key, timing) in thread.timings {
+                let total = frame.timings.entry(key).or_default();
+                total.duration += timing.duration;
+                total.calls += timing.calls;
+            }
+        }
+
+        let mut history = self.state.history.lock();
+        if history.frames_recorded.len() >= history.frames {
+            history.frames_recorded.pop_front();
+        }
+
+        history.frames_recorded.push_back(frame);
+    }
+
+    /// Per-frame average timings over the recorded history, most expensive first
+    pub fn summary(&self) -> Vec<(ProfileKey, ProfileTiming)> {
+        let history = self.state.history.lock();
+        let frames = history.frames_recorded.len().max(1);
+
+        let mut totals = HashMap::<ProfileKey, ProfileTiming>::default();
+        for frame in history.frames_recorded.iter() {
+            for (key, timing) in frame.timings.iter() {
+                let total = totals.entry(key.clone()).or_default();
+                total.duration += timing.duration;
+                total.calls += timing.calls;
+            }
+        }
+
+        let mut summary = totals
+            .into_iter()
+            .map(|(key, total)| {
+                (
+                    key,
+                    ProfileTiming {
+                        duration: total.duration / frames as u32,
+                        calls: total.calls / frames,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        summary.sort_by(|(_, lhs), (_, rhs)| rhs.duration.cmp(&lhs.duration));
+        summary
+    }
+}
+
+/// Per-span data stored in the registry's extensions
+struct SpanTiming {
+    key: ProfileKey,
+    /// Entry time of each nested enter, or None if a span with the same key was already open
+    entered: Vec<Option<Instant>>,
+}
+
+/// Mark a span key as entered on the current thread,
+/// returning whether it is the outermost open span with that key
+fn open_span(key: &ProfileKey) -> bool {
+    THREAD_STATE
+        .try_with(|thread| {
+            let mut thread = thread.borrow_mut();
+            if let Some(count) = thread.open.get_mut(key) {
+                *count += 1;
+                *count == 1
+            } else {
+                thread.open.insert(key.clone(), 1);
+                true
+            }
+        })
+        .unwrap_or_default()
+}
+
+fn close_span(key: &ProfileKey) {
+    THREAD_STATE
+        .try_with(|thread| {
+            if let Some(count) = thread.borrow_mut().open.get_mut(key) {
+                *count = count.saturating_sub(1);
+            }
+        })
+        .ok();
+}
+
+/// Visitor for extracting the system name from bevy's executor spans
+#[derive(Default)]
+struct NameVisitor {
+    name: Option<String>,
+}
+
+impl Visit for NameVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "name" {
+            self.name = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "name" && self.name.is_none() {
+            self.name = Some(format!("{value:?}"));
+        }
+    }
+}
+
+/// Tracing layer that accumulates the inclusive time spent inside each span
+///
+/// Recursive entries into spans with the same key are only timed at the outermost level
+pub struct ProfilingLayer {
+    profiler: Profiler,
+}
+
+impl<S> Layer<S> for ProfilingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = if let Some(span) = ctx.span(id) {
+            span
+        } else {
+            return;
+        };
+
+        let name = attrs.metadata().name();
+        let key = match name {
+            "system" | "exclusive_system" => {
+                let mut visitor = NameVisitor::default();
+                attrs.record(&mut visitor);
+                ProfileKey {
+                    kind: ProfileKind::System,
+                    name: visitor.name.unwrap_or_else(|| name.to_string()),
+                }
+            }
+            _ => ProfileKey {
+                kind: ProfileKind::Span,
+                name: name.to_string(),
+            },
+        };
+
+        span.extensions_mut().insert(SpanTiming {
+            key,
+            entered: vec![],
+        });
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+                let outermost = open_span(&timing.key);
+                timing.entered.push(outermost.then(Instant::now));
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+                if let Some(entered) = timing.entered.pop() {
+                    close_span(&timing.key);
+
+                    if let Some(entered) = entered {
+                        self.profiler.record(&timing.key, entered.elapsed());
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn profiler_end_frame(profiler: Res<Profiler>) {
+    profiler.end_frame();
+}
+
+pub fn setup_profiling_widget(widgets: Res<Widgets>) {
+    widgets
+        .panel_outer_max
+        .add_widget(ProfilingWidget::default());
+}
+
+/// Debug UI widget displaying the most expensive systems or spans as a bar chart
+#[derive(Debug, Clone)]
+pub struct ProfilingWidget {
+    pub kind: ProfileKind,
+    pub paused: bool,
+    summary: Vec<(ProfileKey, ProfileTiming)>,
+}
+
+impl Default for ProfilingWidget {
+    fn default() -> Self {
+        Self {
+            kind: ProfileKind::System,
+            paused: false,
+            summary: Default::default(),
+        }
+    }
+}
+
+impl Widget for ProfilingWidget {
+    fn update(&mut self, world: &mut World) {
+        let profiler = if let Some(profiler) = world.get_resource::<Profiler>() {
+            profiler
+        } else {
+            return;
+        };
+
+        if profiler.paused() != self.paused {
+            profiler.set_paused(self.paused);
+        }
+
+        if !self.paused {
+            self.summary = profiler.summary();
+        }
+    }
+
+    fn title(&self) -> Cow<'static, str> {
+        Cow::Borrowed("Profiling")
+    }
+
+    fn ui(&mut self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                let kinds = [
+                    (ProfileKind::System, "Systems"),
+                    (ProfileKind::Span, "Spans"),
+                ];
+                for (kind, label) in kinds {
+                    if ui.selectable_label(self.kind == kind, label).clicked() {
+                        self.kind = kind;
+                    }
+                }
+
+                ui.checkbox(&mut self.paused, "Paused");
+            });
+
+            let entries = self
+                .summary
+                .iter()
+                .filter(|(key, _)| key.kind == self.kind)
+                .take(PROFILING_WIDGET_ENTRIES)
+                .collect::<Vec<_>>();
+
+            let max = entries
+                .first()
+                .map(|(_, timing)| timing.duration.as_secs_f32())
+                .unwrap_or_default()
+                .max(f32::EPSILON);
+
+            for (key, timing) in entries {
+                let ms = timing.duration.as_secs_f64() * 1000.0;
+                ui.add(
+                    ProgressBar::new(timing.duration.as_secs_f32() / max)
+                        .text(format!("{ms:.3}ms x{} {}", timing.calls, key.name)),
+                )
+                .on_hover_text(&key.name);
+            }
+        })
+        .response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::info_span;
+
+    use super::*;
+
+    fn span_key(name: &str) -> ProfileKey {
+        ProfileKey {
+            kind: ProfileKind::Span,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn end_frame_merges_threads() {
+        let profiler = Profiler::new(1);
+        let key = span_key("test");
+
+        profiler.record(&key, Duration::from_millis(1));
+        std::thread::scope(|scope| {
+            scope.spawn(|| profiler.record(&key, Duration::from_millis(2)));
+        });
+        profiler.end_frame();
+
+        let summary = profiler.summary();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].1.duration, Duration::from_millis(3));
+        assert_eq!(summary[0].1.calls, 2);
+    }
+
+    #[test]
+    fn recursive_spans_are_counted_once() {
+        let profiler = Profiler::new(1);
+        let subscriber = Registry::default().with(profiler.layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = info_span!("recursive");
+            let _outer = outer.enter();
+            let inner = info_span!("recursive");
+            let _inner = inner.enter();
+        });
+        profiler.end_frame();
+
+        let summary = profiler.summary();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].0, span_key("recursive"));
+        assert_eq!(summary[0].1.calls, 1);
+    }
+}
@@ -1,15 +1,64 @@
 use bevy::{
     ecs::system::Command,
-    prelude::{default, AssetServer, Entity, Handle, Name},
-    scene::{Scene, SceneBundle},
+    prelude::{
+        default, AssetServer, Commands, Component, CoreStage, Deref, DerefMut, Entity, Handle,
+        IntoExclusiveSystem, Name, ParallelSystemDescriptorCoercion, Plugin, Query, Res,
+        SpatialBundle,
+    },
+    scene::Scene,
 };
 
 use crate::{
     hierarchy::HierarchyBundle,
-    prelude::{ArchiveHandles, ArchiveScene},
+    prelude::{
+        gltf_entities_ready, unarchive_pending_scenes, ArchiveScene, GltfScenesReady,
+        InsertMeshRotationPivot,
+    },
     util::default_entity,
 };
 
+pub struct SceneInstancePlugin;
+
+impl Plugin for SceneInstancePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_to_stage(
+            CoreStage::Last,
+            insert_pending_scenes.after(gltf_entities_ready),
+        )
+        // Runs after the stage's parallel systems, so extras marked ready this frame are visible
+        .add_system_to_stage(
+            CoreStage::Last,
+            unarchive_pending_scenes.exclusive_system().at_end(),
+        );
+    }
+}
+
+/// Scene handle waiting on its Gltf extras to be deserialized before being spawned
+#[derive(Debug, Clone, Deref, DerefMut, Component)]
+pub struct PendingScene(pub Handle<Scene>);
+
+/// Hand pending scenes over to the stock scene spawner once they're ready
+pub fn insert_pending_scenes(
+    query: Query<(Entity, &PendingScene)>,
+    asset_server: Res<AssetServer>,
+    scenes_ready: Res<GltfScenesReady>,
+    mut commands: Commands,
+) {
+    for (entity, pending_scene) in query.iter() {
+        if !scenes_ready.is_ready(&asset_server, pending_scene) {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .remove::<PendingScene>()
+            .insert((**pending_scene).clone());
+    }
+}
+
+/// Spawn a scene as a child of the given entity using the stock scene machinery
+///
+/// Spawning is deferred until any Gltf extras in the scene have been deserialized
 #[derive(Debug, Clone)]
 pub struct InsertScene {
     pub entity: Entity,
@@ -31,7 +80,8 @@ impl Command for InsertScene {
         let scene: Handle<Scene> = asset_server.load(&self.path);
         world
             .entity_mut(self.entity)
-            .insert_bundle(SceneBundle { scene, ..default() })
+            .insert_bundle(SpatialBundle::default())
+            .insert(PendingScene(scene))
             .insert(Name::new("Scene Instance"));
     }
 }
@@ -40,6 +90,8 @@ impl Command for InsertScene {
 pub struct InsertSceneArchive {
     pub entity: Entity,
     pub path: String,
+    /// Parent the scene to a mesh rotation pivot child rather than the entity itself
+    pub mesh_rotation: bool,
 }
 
 impl Default for InsertSceneArchive {
@@ -47,6 +99,7 @@ impl Default for InsertSceneArchive {
         Self {
             entity: default_entity(),
             path: default(),
+            mesh_rotation: default(),
         }
     }
 }
@@ -57,9 +110,21 @@ impl Command for InsertSceneArchive {
         let scene: Handle<Scene> = asset_server.load(&self.path);
         world
             .entity_mut(self.entity)
-            .insert(ArchiveScene(scene))
             .insert_bundle(HierarchyBundle::default())
-            .insert(ArchiveHandles::default().with_scene())
             .insert(Name::new("Scene Instance"));
+
+        let scene_entity = if self.mesh_rotation {
+            let pivot = world.spawn().id();
+            InsertMeshRotationPivot {
+                entity: self.entity,
+                pivot,
+            }
+            .write(world);
+            pivot
+        } else {
+            self.entity
+        };
+
+        world.entity_mut(scene_entity).insert(ArchiveScene(scene));
     }
 }
@@ -1,102 +1,271 @@
 //! Abstraction for removing components from the ECS and storing them for restoration
-
-use std::fmt::Debug;
+//!
+//! An entity can carry any number of archive groups at once:
+//! * One ArchiveStorage<T> per registered bundle type T
+//! * A ReflectArchive holding named groups of reflected components
+//! * An ArchiveScene whose instance is despawned and respawned
+//!
+//! Archival and unarchival of a subtree happen synchronously within a single command,
+//! so the result is visible in the same frame the command is applied.
+//! The exception is a scene that isn't ready yet,
+//! which is spawned at the end of the frame it becomes ready in.
+
+use std::{
+    any::{type_name, TypeId},
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+};
 
 use bevy::{
     ecs::{system::Command, world::EntityMut},
     prelude::{
-        default, BuildWorldChildren, Bundle, Children, Component, Deref, DerefMut, Entity, Handle,
+        default, warn, App, AssetServer, BuildWorldChildren, Bundle, Children, Component, Deref,
+        DerefMut, Entity, GlobalTransform, Handle, Mut, Parent, ReflectComponent, Transform, With,
+        World,
     },
+    reflect::{Reflect, TypeRegistry},
     scene::{InstanceId, Scene, SceneSpawner},
 };
 
-use crate::prelude::default_entity;
+use crate::prelude::{default_entity, GltfScenesReady};
 
-/// Type-erased handle for archiving or unarchiving components and entities
-#[derive(Default, Clone, Component)]
+/// Type-erased handles for moving a bundle in and out of its ArchiveStorage
+#[derive(Copy, Clone)]
 pub struct ArchiveHandles {
-    pub archive_handles: Vec<fn(&mut EntityMut)>,
-    pub unarchive_handles: Vec<fn(&mut EntityMut)>,
+    pub archive: fn(&mut EntityMut),
+    pub unarchive: fn(&mut EntityMut),
 }
 
-#[derive(Debug, Copy, Clone, Deref, DerefMut, Component)]
-pub struct ArchiveSceneInstance(pub InstanceId);
+impl ArchiveHandles {
+    pub fn new<T>() -> Self
+    where
+        T: Bundle,
+    {
+        ArchiveHandles {
+            archive: |entity| {
+                ArchiveStorage::<T>::try_archive(entity);
+            },
+            unarchive: |entity| {
+                ArchiveStorage::<T>::try_unarchive(entity);
+            },
+        }
+    }
+}
 
-#[derive(Debug, Clone, Deref, DerefMut, Component)]
-pub struct ArchiveScene(pub Handle<Scene>);
+/// Registry of ArchiveHandles, keyed by the TypeId of their ArchiveStorage<T> component
+#[derive(Default, Clone)]
+pub struct ArchiveRegistry {
+    pub handles: HashMap<TypeId, ArchiveHandles>,
+}
 
-impl ArchiveHandles {
-    /// Create a set of handles to move components in and out of an ArchiveStorage
-    pub fn with_bundle<T>(mut self) -> Self
+impl ArchiveRegistry {
+    pub fn register<T>(&mut self)
     where
         T: Bundle,
     {
-        self.archive_handles.push(|entity| {
-            ArchiveStorage::<T>::try_archive(entity);
-        });
+        self.handles
+            .insert(TypeId::of::<ArchiveStorage<T>>(), ArchiveHandles::new::<T>());
+    }
 
-        self.unarchive_handles.push(|entity| {
-            ArchiveStorage::<T>::try_unarchive(entity);
-        });
+    /// Collect the handles for every archive storage present on the given entity
+    ///
+    /// Storages for bundle types that were never registered can't be archived,
+    /// and are reported rather than skipped silently.
+    pub fn entity_handles(world: &World, entity: Entity) -> Vec<ArchiveHandles> {
+        let empty = ArchiveRegistry::default();
+        let registry = world.get_resource::<ArchiveRegistry>().unwrap_or(&empty);
+
+        let storage_name = type_name::<ArchiveStorage<()>>();
+        let storage_prefix = &storage_name[..storage_name.find('<').unwrap_or(storage_name.len())];
+
+        let components = world.components();
+        world
+            .entity(entity)
+            .archetype()
+            .components()
+            .filter_map(|component_id| components.get_info(component_id))
+            .filter_map(|info| {
+                let handles = info
+                    .type_id()
+                    .and_then(|type_id| registry.handles.get(&type_id));
+
+                if handles.is_none() && info.name().starts_with(storage_prefix) {
+                    warn!(
+                        "Entity {entity:?} has unregistered {}, register its bundle via \
+                        RegisterArchiveBundle",
+                        info.name()
+                    );
+                }
 
+                handles
+            })
+            .copied()
+            .collect()
+    }
+}
+
+/// Extension trait for registering bundle types that can be stored in an ArchiveStorage
+pub trait RegisterArchiveBundle {
+    fn register_archive_bundle<T>(&mut self) -> &mut Self
+    where
+        T: Bundle;
+}
+
+impl RegisterArchiveBundle for App {
+    fn register_archive_bundle<T>(&mut self) -> &mut Self
+    where
+        T: Bundle,
+    {
+        self.world
+            .get_resource_or_insert_with(ArchiveRegistry::default)
+            .register::<T>();
         self
     }
+}
 
-    /// Create a set of handles to spawn and despawn a Scene
-    pub fn with_scene(mut self) -> Self {
-        self.archive_handles.push(|entity| {
-            if let Some(instance) = entity.remove::<ArchiveSceneInstance>() {
-                let children = (*entity.get::<Children>().unwrap())
-                    .into_iter()
-                    .copied()
-                    .collect::<Vec<_>>();
+#[derive(Debug, Copy, Clone, Deref, DerefMut, Component)]
+pub struct ArchiveSceneInstance(pub InstanceId);
 
-                let world = unsafe { entity.world_mut() };
-                let scene_spawner = world.resource_mut::<SceneSpawner>();
+#[derive(Debug, Clone, Deref, DerefMut, Component)]
+pub struct ArchiveScene(pub Handle<Scene>);
 
-                for instance_entity in scene_spawner
-                    .iter_instance_entities(*instance)
-                    .unwrap()
+/// Marks an unarchived ArchiveScene that is waiting on its scene to become ready
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct PendingArchiveScene;
+
+impl ArchiveScene {
+    /// Despawn the entity's scene instance, if one exists,
+    /// or cancel its spawn if it's still pending
+    pub fn archive(world: &mut World, entity: Entity) {
+        world.entity_mut(entity).remove::<PendingArchiveScene>();
+
+        let instance =
+            if let Some(instance) = world.entity_mut(entity).remove::<ArchiveSceneInstance>() {
+                instance
+            } else {
+                return;
+            };
+
+        let children = world
+            .get::<Children>(entity)
+            .map(|children| children.iter().copied().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let instance_children = world
+            .resource::<SceneSpawner>()
+            .iter_instance_entities(*instance)
+            .map(|entities| {
+                entities
+                    .filter(|entity| children.contains(entity))
                     .collect::<Vec<_>>()
-                {
-                    if children.contains(&&instance_entity) {
-                        entity.remove_children(&[instance_entity]);
-                    }
-                }
+            })
+            .unwrap_or_default();
 
-                let world = unsafe { entity.world_mut() };
-                let mut scene_spawner = world.resource_mut::<SceneSpawner>();
-                scene_spawner.despawn_instance(*instance);
-            }
+        world.entity_mut(entity).remove_children(&instance_children);
+
+        world.resource_scope(|world, mut scene_spawner: Mut<SceneSpawner>| {
+            scene_spawner.despawn_instance_sync(world, &instance);
         });
+    }
 
-        self.unarchive_handles.push(|entity| {
-            if entity.get::<ArchiveSceneInstance>().is_some() {
-                return;
+    /// Spawn the entity's scene as a child, if it isn't already instanced
+    ///
+    /// If the scene asset hasn't finished loading, or its Gltf extras have yet to be deserialized,
+    /// the entity is marked with PendingArchiveScene and spawned by unarchive_pending_scenes
+    /// once ready, so the instance never appears without its extras.
+    pub fn unarchive(world: &mut World, entity: Entity) {
+        if world.get::<ArchiveSceneInstance>(entity).is_some() {
+            return;
+        }
+
+        let handle = if let Some(scene) = world.get::<ArchiveScene>(entity) {
+            (**scene).clone()
+        } else {
+            return;
+        };
+
+        let ready = world
+            .resource::<GltfScenesReady>()
+            .is_ready(world.resource::<AssetServer>(), &handle);
+
+        let instance = world.resource_scope(|world, mut scene_spawner: Mut<SceneSpawner>| {
+            if !ready {
+                return None;
             }
 
-            let handle = entity.get::<ArchiveScene>().unwrap().clone();
-            let entity_id = entity.id();
-            let world = unsafe { entity.world_mut() };
-            let mut scene_spawner = world.resource_mut::<SceneSpawner>();
-            let id = scene_spawner.spawn_as_child((*handle).clone(), entity_id);
+            let instance = scene_spawner.spawn_sync(world, handle.clone()).ok()?;
+
+            let roots = scene_spawner
+                .iter_instance_entities(instance)
+                .map(|entities| {
+                    entities
+                        .filter(|instance_entity| world.get::<Parent>(*instance_entity).is_none())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
 
-            entity.insert(ArchiveSceneInstance(id));
+            world.entity_mut(entity).push_children(&roots);
+            propagate_global_transforms(world, entity);
+
+            Some(instance)
         });
 
-        self
+        if let Some(instance) = instance {
+            world
+                .entity_mut(entity)
+                .remove::<PendingArchiveScene>()
+                .insert(ArchiveSceneInstance(instance));
+        } else {
+            world.entity_mut(entity).insert(PendingArchiveScene);
+        }
     }
+}
 
-    pub fn archive(&self, entity: &mut EntityMut) {
-        for handle in self.archive_handles.iter() {
-            handle(entity)
+/// Spawn archive scenes that were unarchived before they were ready
+pub fn unarchive_pending_scenes(world: &mut World) {
+    let pending = world
+        .query_filtered::<(Entity, &ArchiveScene), With<PendingArchiveScene>>()
+        .iter(world)
+        .map(|(entity, scene)| (entity, (**scene).clone()))
+        .collect::<Vec<_>>();
+
+    for (entity, handle) in pending {
+        if world
+            .resource::<GltfScenesReady>()
+            .is_ready(world.resource::<AssetServer>(), &handle)
+        {
+            ArchiveScene::unarchive(world, entity);
         }
     }
+}
+
+/// Recompute GlobalTransform for the descendants of the given entity
+///
+/// Used to place freshly-spawned scene instances correctly
+/// without waiting for the next transform propagation pass
+fn propagate_global_transforms(world: &mut World, entity: Entity) {
+    let global_transform = world
+        .get::<GlobalTransform>(entity)
+        .copied()
+        .unwrap_or_default();
+
+    let children = world
+        .get::<Children>(entity)
+        .map(|children| children.iter().copied().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    for child in children {
+        let transform = if let Some(transform) = world.get::<Transform>(child) {
+            *transform
+        } else {
+            continue;
+        };
 
-    pub fn unarchive(&self, entity: &mut EntityMut) {
-        for handle in self.unarchive_handles.iter() {
-            handle(entity)
+        if let Some(mut child_global_transform) = world.get_mut::<GlobalTransform>(child) {
+            *child_global_transform = global_transform.mul_transform(transform);
         }
+
+        propagate_global_transforms(world, child);
     }
 }
 
@@ -146,10 +315,10 @@ where
     }
 }
 
-/// Utility bundle for including one archivable type and a corresponding ArchiveHandles
+/// Utility bundle for including one archivable type alongside its ArchiveStorage
 ///
-/// Not suitable for entities with multiple archive storages,
-/// as ArchiveHandles must hold references to all of them
+/// T must be registered via RegisterArchiveBundle.
+/// Several archive bundles of distinct types can coexist on one entity.
 #[derive(Clone, Bundle)]
 pub struct ArchiveBundle<T>
 where
@@ -158,7 +327,6 @@ where
     #[bundle]
     pub bundle: T,
     pub archive_storage: ArchiveStorage<T>,
-    pub archive_handles: ArchiveHandles,
 }
 
 impl<T> Default for ArchiveBundle<T>
@@ -169,11 +337,222 @@ where
         Self {
             bundle: default(),
             archive_storage: default(),
-            archive_handles: ArchiveHandles::default().with_bundle::<T>(),
         }
     }
 }
 
+/// A set of reflected components, identified by type name,
+/// that are archived and unarchived together
+#[derive(Default)]
+pub struct ReflectArchiveGroup {
+    pub components: Vec<String>,
+    archive: Vec<Box<dyn Reflect>>,
+}
+
+impl Debug for ReflectArchiveGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReflectArchiveGroup")
+            .field("components", &self.components)
+            .field("archived", &self.is_archived())
+            .finish()
+    }
+}
+
+impl Clone for ReflectArchiveGroup {
+    fn clone(&self) -> Self {
+        Self {
+            components: self.components.clone(),
+            archive: self.archive.iter().map(|value| value.clone_value()).collect(),
+        }
+    }
+}
+
+impl ReflectArchiveGroup {
+    pub fn is_archived(&self) -> bool {
+        !self.archive.is_empty()
+    }
+
+    fn archive(&mut self, type_registry: &TypeRegistry, world: &mut World, entity: Entity) {
+        if self.is_archived() {
+            return;
+        }
+
+        let type_registry = type_registry.read();
+        for type_name in self.components.iter() {
+            let reflect_component = type_registry
+                .get_with_name(type_name)
+                .and_then(|registration| registration.data::<ReflectComponent>())
+                .unwrap_or_else(|| panic!("No ReflectComponent registration for {type_name:}"));
+
+            if let Some(component) = reflect_component.reflect(world, entity) {
+                self.archive.push(component.clone_value());
+                reflect_component.remove(world, entity);
+            }
+        }
+    }
+
+    fn unarchive(&mut self, type_registry: &TypeRegistry, world: &mut World, entity: Entity) {
+        let type_registry = type_registry.read();
+        for component in self.archive.drain(..) {
+            let type_name = component.type_name();
+            let reflect_component = type_registry
+                .get_with_name(type_name)
+                .and_then(|registration| registration.data::<ReflectComponent>())
+                .unwrap_or_else(|| panic!("No ReflectComponent registration for {type_name:}"));
+
+            reflect_component.apply_or_insert(world, entity, &*component);
+        }
+    }
+}
+
+/// Named groups of reflected components that can be archived independently of one another
+#[derive(Debug, Default, Clone, Component)]
+pub struct ReflectArchive {
+    pub groups: BTreeMap<String, ReflectArchiveGroup>,
+}
+
+impl ReflectArchive {
+    /// Add the given component type names to a group, creating it if necessary
+    pub fn with_group<I, S>(mut self, group: impl Into<String>, components: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.groups
+            .entry(group.into())
+            .or_default()
+            .components
+            .extend(components.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add a component type to a group, creating it if necessary
+    pub fn with_component<T>(self, group: impl Into<String>) -> Self
+    where
+        T: Component + Reflect,
+    {
+        self.with_group(group, [type_name::<T>()])
+    }
+
+    /// Combine the groups of another ReflectArchive into this one
+    pub fn merge(&mut self, other: ReflectArchive) {
+        for (name, group) in other.groups {
+            let target = self.groups.entry(name).or_default();
+            for component in group.components {
+                if !target.components.contains(&component) {
+                    target.components.push(component);
+                }
+            }
+            target.archive.extend(group.archive);
+        }
+    }
+
+    /// Run a function over the entity's ReflectArchive with exclusive world access
+    fn scope(
+        world: &mut World,
+        entity: Entity,
+        f: impl FnOnce(&mut ReflectArchive, &TypeRegistry, &mut World),
+    ) {
+        let mut reflect_archive =
+            if let Some(reflect_archive) = world.entity_mut(entity).remove::<ReflectArchive>() {
+                reflect_archive
+            } else {
+                return;
+            };
+
+        let type_registry = world.resource::<TypeRegistry>().clone();
+        f(&mut reflect_archive, &type_registry, world);
+
+        world.entity_mut(entity).insert(reflect_archive);
+    }
+
+    pub fn archive(world: &mut World, entity: Entity) {
+        Self::scope(world, entity, |reflect_archive, type_registry, world| {
+            for group in reflect_archive.groups.values_mut() {
+                group.archive(type_registry, world, entity);
+            }
+        })
+    }
+
+    pub fn unarchive(world: &mut World, entity: Entity) {
+        Self::scope(world, entity, |reflect_archive, type_registry, world| {
+            for group in reflect_archive.groups.values_mut() {
+                group.unarchive(type_registry, world, entity);
+            }
+        })
+    }
+
+    pub fn archive_group(world: &mut World, entity: Entity, group: &str) {
+        Self::scope(world, entity, |reflect_archive, type_registry, world| {
+            if let Some(group) = reflect_archive.groups.get_mut(group) {
+                group.archive(type_registry, world, entity);
+            }
+        })
+    }
+
+    pub fn unarchive_group(world: &mut World, entity: Entity, group: &str) {
+        Self::scope(world, entity, |reflect_archive, type_registry, world| {
+            if let Some(group) = reflect_archive.groups.get_mut(group) {
+                group.unarchive(type_registry, world, entity);
+            }
+        })
+    }
+}
+
+/// Insert a ReflectArchive, merging it into any existing one on the target entity
+#[derive(Debug, Clone)]
+pub struct InsertReflectArchive {
+    pub entity: Entity,
+    pub archive: ReflectArchive,
+}
+
+impl Default for InsertReflectArchive {
+    fn default() -> Self {
+        Self {
+            entity: default_entity(),
+            archive: default(),
+        }
+    }
+}
+
+impl Command for InsertReflectArchive {
+    fn write(self, world: &mut World) {
+        let mut entity = world.entity_mut(self.entity);
+        if let Some(mut reflect_archive) = entity.get_mut::<ReflectArchive>() {
+            reflect_archive.merge(self.archive);
+        } else {
+            entity.insert(self.archive);
+        }
+    }
+}
+
+/// Archive every group present on a single entity
+pub fn archive_entity(world: &mut World, entity: Entity) {
+    for handles in ArchiveRegistry::entity_handles(world, entity) {
+        (handles.archive)(&mut world.entity_mut(entity));
+    }
+
+    ReflectArchive::archive(world, entity);
+    ArchiveScene::archive(world, entity);
+}
+
+/// Unarchive every group present on a single entity
+pub fn unarchive_entity(world: &mut World, entity: Entity) {
+    for handles in ArchiveRegistry::entity_handles(world, entity) {
+        (handles.unarchive)(&mut world.entity_mut(entity));
+    }
+
+    ReflectArchive::unarchive(world, entity);
+    ArchiveScene::unarchive(world, entity);
+}
+
+fn children(world: &World, entity: Entity) -> Vec<Entity> {
+    world
+        .get::<Children>(entity)
+        .map(|children| children.iter().copied().collect())
+        .unwrap_or_default()
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct ArchiveCommand {
     pub target: Entity,
@@ -190,20 +569,16 @@ impl Default for ArchiveCommand {
 }
 
 impl Command for ArchiveCommand {
-    fn write(self, world: &mut bevy::prelude::World) {
-        if let Some(handles) = world.entity(self.target).get::<ArchiveHandles>().cloned() {
-            handles.archive(&mut world.entity_mut(self.target));
-        }
+    fn write(self, world: &mut World) {
+        archive_entity(world, self.target);
 
         if self.recursive {
-            if let Some(children) = world.entity(self.target).get::<Children>() {
-                for child in children.into_iter().copied().collect::<Vec<_>>() {
-                    Self {
-                        target: child,
-                        recursive: true,
-                    }
-                    .write(world)
+            for child in children(world, self.target) {
+                Self {
+                    target: child,
+                    recursive: true,
                 }
+                .write(world)
             }
         }
     }
@@ -225,21 +600,62 @@ impl Default for Unarchive {
 }
 
 impl Command for Unarchive {
-    fn write(self, world: &mut bevy::prelude::World) {
-        if let Some(handles) = world.entity(self.target).get::<ArchiveHandles>().cloned() {
-            handles.unarchive(&mut world.entity_mut(self.target));
-        }
+    fn write(self, world: &mut World) {
+        unarchive_entity(world, self.target);
 
         if self.recursive {
-            if let Some(children) = world.entity(self.target).get::<Children>() {
-                for child in children.into_iter().copied().collect::<Vec<_>>() {
-                    Self {
-                        target: child,
-                        recursive: true,
-                    }
-                    .write(world)
+            for child in children(world, self.target) {
+                Self {
+                    target: child,
+                    recursive: true,
                 }
+                .write(world)
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, Copy, Clone, PartialEq, Component)]
+    struct Marker(usize);
+
+    #[test]
+    fn registered_bundle_round_trips() {
+        let mut world = World::default();
+        world
+            .get_resource_or_insert_with(ArchiveRegistry::default)
+            .register::<(Marker,)>();
+
+        let entity = world
+            .spawn()
+            .insert_bundle(ArchiveBundle {
+                bundle: (Marker(3),),
+                archive_storage: default(),
+            })
+            .id();
+
+        archive_entity(&mut world, entity);
+        assert!(world.get::<Marker>(entity).is_none());
+
+        unarchive_entity(&mut world, entity);
+        assert_eq!(world.get::<Marker>(entity), Some(&Marker(3)));
+    }
+
+    #[test]
+    fn unregistered_bundle_has_no_handles() {
+        let mut world = World::default();
+
+        let entity = world
+            .spawn()
+            .insert_bundle(ArchiveBundle {
+                bundle: (Marker(3),),
+                archive_storage: default(),
+            })
+            .id();
+
+        assert!(ArchiveRegistry::entity_handles(&world, entity).is_empty());
+    }
+}
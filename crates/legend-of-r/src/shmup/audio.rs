@@ -0,0 +1,296 @@
+//! Audio playback driven by timeline time
+//!
+//! One-shot sounds are scheduled as discrete stops on a timeline instead of being played
+//! directly, so rewinding stops cues that haven't happened yet and replaying plays them again.
+//! Music follows the timeline's tick rate and pause state.
+
+use bevy::{
+    ecs::system::Command,
+    prelude::{
+        default, Assets, Audio, AudioSink, AudioSource, Component, CoreStage, Entity, EventReader,
+        Handle, Name, ParallelSystemDescriptorCoercion, PlaybackSettings, Plugin, Query, Res,
+    },
+};
+
+use crate::{
+    animation::animations::discrete::{DeterminismId, Discrete, DiscreteStop},
+    prelude::Timeline,
+    util::default_entity,
+};
+
+/// Timeline seconds after which a playing cue is released and stopped
+///
+/// bevy's audio sinks can't report when playback has finished,
+/// so cues are held for a fixed window in case a rewind needs to stop them.
+pub const SOUND_CUE_LIFETIME: f64 = 10.0;
+
+pub struct TimelineAudioPlugin;
+
+impl Plugin for TimelineAudioPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<SoundCueEvent>();
+
+        app.add_system_to_stage(CoreStage::Last, sound_cue_events)
+            .add_system_to_stage(CoreStage::Last, timeline_audio.after(sound_cue_events))
+            .add_system_to_stage(CoreStage::Last, timeline_music);
+    }
+}
+
+/// A one-shot sound scheduled on a timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundCue {
+    pub sound: Handle<AudioSource>,
+    pub volume: f32,
+}
+
+impl Default for SoundCue {
+    fn default() -> Self {
+        Self {
+            sound: default(),
+            volume: 1.0,
+        }
+    }
+}
+
+/// Request to schedule a sound cue at a given timeline time
+#[derive(Debug, Clone)]
+pub struct SoundCueEvent {
+    pub timeline: Entity,
+    pub t: f64,
+    pub cue: SoundCue,
+    /// Ties the cue to the gameplay event that caused it, so both are pruned together
+    pub determinism: DeterminismId,
+}
+
+#[derive(Debug, Clone)]
+struct PlayingCue {
+    t: f64,
+    sink: Handle<AudioSink>,
+}
+
+/// Plays the sound cues stored in a sibling Discrete<SoundCue> as its timeline passes them
+#[derive(Debug, Clone, Component)]
+pub struct TimelineAudio {
+    pub timeline: Entity,
+    paused: bool,
+    playing: Vec<PlayingCue>,
+}
+
+impl Default for TimelineAudio {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            paused: default(),
+            playing: default(),
+        }
+    }
+}
+
+pub struct InsertTimelineAudio {
+    pub timeline: Entity,
+    pub entity: Entity,
+}
+
+impl Default for InsertTimelineAudio {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            entity: default_entity(),
+        }
+    }
+}
+
+impl Command for InsertTimelineAudio {
+    fn write(self, world: &mut bevy::prelude::World) {
+        world
+            .entity_mut(self.entity)
+            .insert(Name::new("Timeline Audio"))
+            .insert(TimelineAudio {
+                timeline: self.timeline,
+                ..default()
+            })
+            .insert(Discrete::<SoundCue>::default());
+    }
+}
+
+/// Looping music track that follows a timeline's tick rate and pause state
+#[derive(Debug, Clone, Component)]
+pub struct TimelineMusic {
+    pub timeline: Entity,
+    pub sound: Handle<AudioSource>,
+    pub volume: f32,
+    sink: Option<Handle<AudioSink>>,
+}
+
+impl Default for TimelineMusic {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            sound: default(),
+            volume: 1.0,
+            sink: None,
+        }
+    }
+}
+
+impl TimelineMusic {
+    pub fn new(timeline: Entity, sound: Handle<AudioSource>) -> Self {
+        TimelineMusic {
+            timeline,
+            sound,
+            ..default()
+        }
+    }
+}
+
+/// Match a sink's pause state and playback speed to its timeline
+fn sync_sink(sink: &AudioSink, timeline: &Timeline) {
+    let paused = timeline.paused || timeline.tick_rate <= 0.0;
+
+    if paused != sink.is_paused() {
+        if paused {
+            sink.pause();
+        } else {
+            sink.play();
+        }
+    }
+
+    let speed = timeline.tick_rate as f32;
+    if !paused && speed != sink.speed() {
+        sink.set_speed(speed);
+    }
+}
+
+pub fn sound_cue_events(
+    mut events: EventReader<SoundCueEvent>,
+    mut query_audio: Query<(&TimelineAudio, &mut Discrete<SoundCue>)>,
+) {
+    for event in events.iter() {
+        let mut cues = if let Some((_, cues)) = query_audio
+            .iter_mut()
+            .find(|(timeline_audio, _)| timeline_audio.timeline == event.timeline)
+        {
+            cues
+        } else {
+            continue;
+        };
+
+        cues.insert_stop(DiscreteStop {
+            t: event.t,
+            value: event.cue.clone(),
+            determinism: event.determinism,
+            ..default()
+        });
+    }
+}
+
+pub fn timeline_audio(
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    query_timeline: Query<&Timeline>,
+    mut query_audio: Query<(&mut TimelineAudio, &mut Discrete<SoundCue>)>,
+) {
+    for (mut timeline_audio, mut cues) in query_audio.iter_mut() {
+        let timeline = if let Ok(timeline) = query_timeline.get(timeline_audio.timeline) {
+            timeline
+        } else {
+            continue;
+        };
+
+        if cues.wants_update {
+            cues.update_stops();
+        }
+
+        let prev_t = cues.t;
+        let t = timeline.t;
+        cues.prev_t = prev_t;
+        cues.t = t;
+
+        // Mirror Discrete's pruning so cues vanish along with the events that scheduled them
+        let unpaused = timeline_audio.paused && !timeline.paused;
+        if unpaused || (!timeline.paused && t < prev_t) {
+            cues.prune_non_deterministic(t);
+        }
+        timeline_audio.paused = timeline.paused;
+
+        // Stop cues that haven't happened yet at the rewound time
+        if t < prev_t {
+            timeline_audio.playing.retain(|playing| {
+                if playing.t <= t {
+                    return true;
+                }
+
+                if let Some(sink) = audio_sinks.get(&playing.sink) {
+                    sink.stop();
+                }
+
+                false
+            });
+        }
+
+        // Only fire cues during regular playback, so scrubbing doesn't play a burst of one-shots
+        if t > prev_t && timeline.scrub_rate == 0.0 {
+            let fired = cues
+                .stops()
+                .filter(|stop| stop.t > prev_t && stop.t <= t && !stop.disabled)
+                .map(|stop| (stop.t, stop.value.clone()))
+                .collect::<Vec<_>>();
+
+            for (cue_t, cue) in fired {
+                let sink = audio.play_with_settings(
+                    cue.sound,
+                    PlaybackSettings::ONCE
+                        .with_volume(cue.volume)
+                        .with_speed(timeline.tick_rate as f32),
+                );
+
+                // A strong handle keeps the sink alive so it can be paused or stopped
+                timeline_audio.playing.push(PlayingCue {
+                    t: cue_t,
+                    sink: audio_sinks.get_handle(sink),
+                });
+            }
+        }
+
+        timeline_audio
+            .playing
+            .retain(|playing| t - playing.t < SOUND_CUE_LIFETIME);
+
+        for playing in timeline_audio.playing.iter() {
+            if let Some(sink) = audio_sinks.get(&playing.sink) {
+                sync_sink(sink, timeline);
+            }
+        }
+    }
+}
+
+pub fn timeline_music(
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    query_timeline: Query<&Timeline>,
+    mut query_music: Query<&mut TimelineMusic>,
+) {
+    for mut music in query_music.iter_mut() {
+        let timeline = if let Ok(timeline) = query_timeline.get(music.timeline) {
+            timeline
+        } else {
+            continue;
+        };
+
+        let sink = if let Some(sink) = &music.sink {
+            sink
+        } else {
+            let sink = audio.play_with_settings(
+                music.sound.clone(),
+                PlaybackSettings::LOOP.with_volume(music.volume),
+            );
+            music.sink = Some(audio_sinks.get_handle(sink));
+            continue;
+        };
+
+        // Sinks are created by the audio backend a frame after playback is requested
+        if let Some(sink) = audio_sinks.get(sink) {
+            sync_sink(sink, timeline);
+        }
+    }
+}
@@ -2,7 +2,7 @@ use bevy::{
     ecs::system::Command,
     prelude::{
         default, BuildWorldChildren, Bundle, Camera, Camera3dBundle, Component, Entity, Name,
-        PerspectiveProjection, Plugin, Query, Res, Transform, With,
+        PerspectiveProjection, Plugin, Query, Res, Transform, UiCameraConfig, With,
     },
     render::{camera::Projection, view::RenderLayers},
     transform::TransformBundle,
@@ -16,7 +16,7 @@ use crate::{
             CustomPerspectiveProjection, PerspectiveCameraArmTarget, PlaneTransformTarget,
         },
         playfield::Playfield,
-        AspectRatio,
+        AspectRatio, BACKGROUND_RENDER_LAYER,
     },
 };
 
@@ -67,7 +67,8 @@ impl Command for AssembleBackground {
                 },
                 ..default()
             })
-            .insert(RenderLayers::layer(1))
+            .insert(RenderLayers::layer(BACKGROUND_RENDER_LAYER))
+            .insert(UiCameraConfig { show_ui: false })
             .insert(CustomPerspectiveProjection {
                 perspective: PerspectiveProjection {
                     fov: 60.0_f32.to_radians(),
@@ -0,0 +1,837 @@
+//! Multi-part bosses with timeline-driven phases
+//!
+//! A boss is a body carrying a core and any number of destructible parts,
+//! each of which is a regular enemy with its own hit points and hurtbox scene.
+//! Phases are stored as [`Discrete`] stops on the boss timeline so rewinding
+//! restores the phase, movement and bullet patterns that were active at that point.
+
+use bevy::{
+    ecs::{reflect::ReflectComponent, system::Command},
+    prelude::{
+        default, info, BuildWorldChildren, Commands, Component, CoreStage, Entity, EventReader,
+        Name, ParallelSystemDescriptorCoercion, Plugin, Quat, Query, Transform, Vec3, With,
+        Without, World,
+    },
+    reflect::Reflect,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prelude::{
+        construct_enemy_bullet, default_entity, evaluate_tagged, hitbox_collision,
+        timeline_damage, Alive, AnimateComponentsTrait, AnimationTagTrait, BuildAnimation,
+        BulletAnimation, DeathEffect, DeathEvent, Determinisms, DilateTrait, Discrete,
+        DiscreteStop, DiscreteStopsTrait, EvaluateTrait, HierarchyBundle, HitPoints, InsertEnemy,
+        InsertMeshRotation, InsertTimelineAlive, InsertTimelineDamage, InterpolateTrait,
+        Invulnerable, MeshRotationDesc, ParticleEffect, PlayfieldLayer, PostUpdate,
+        RegisterAnimationType, RepeatTrait, TimeSourceTrait, Timeline, TimelineAlive,
+        TimelineDamage, TimelineTime, TryAnimateComponentsTrait, UnpoolEntity, Update,
+        SCENE_ENEMY,
+    },
+    scene::InsertSceneArchive,
+};
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.register_type::<Boss>()
+            .register_type::<BossPhase>()
+            .register_type::<BossBody>()
+            .register_type::<BossCore>()
+            .register_type::<BossPart>()
+            .register_type::<BossPhaseMember>();
+
+        app.register_animation_type::<BossPhase>();
+
+        app.add_system(boss_body.after(evaluate_tagged::<Update>))
+            .add_system(boss_core_vulnerability)
+            .add_system(boss_fire.before(evaluate_tagged::<Update>))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                boss_core_death
+                    .after(timeline_damage)
+                    .before(evaluate_tagged::<PostUpdate>),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                boss_phase_transition
+                    .after(hitbox_collision)
+                    .after(timeline_damage)
+                    .before(evaluate_tagged::<PostUpdate>),
+            );
+    }
+}
+
+/// Index of the active boss phase
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Component, Reflect,
+)]
+#[reflect(Component)]
+pub struct BossPhase(pub usize);
+
+/// Condition for entering a phase from the one before it
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PhaseTrigger {
+    /// Seconds spent in the previous phase
+    Elapsed(f64),
+    /// Core hit points at or below this value
+    CoreHitPoints(usize),
+    /// Every part has been destroyed, or immediately if the boss has no parts
+    PartsDestroyed,
+}
+
+impl Default for PhaseTrigger {
+    fn default() -> Self {
+        PhaseTrigger::Elapsed(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct Boss {
+    pub timeline: Entity,
+    pub spawn_at: f64,
+    pub body: Entity,
+    pub core: Entity,
+    pub parts: Vec<Entity>,
+    pub bullet_pool: Entity,
+    pub phase_stops: Entity,
+    /// Trigger for entering each phase, indexed by phase
+    #[reflect(ignore)]
+    pub triggers: Vec<PhaseTrigger>,
+    /// Pivot entity for each phase, indexed by phase
+    pub phases: Vec<Entity>,
+}
+
+impl Default for Boss {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            spawn_at: default(),
+            body: default_entity(),
+            core: default_entity(),
+            parts: default(),
+            bullet_pool: default_entity(),
+            phase_stops: default_entity(),
+            triggers: default(),
+            phases: default(),
+        }
+    }
+}
+
+/// Parent of the core and parts, following the active phase's pivot
+#[derive(Debug, Copy, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct BossBody {
+    pub boss: Entity,
+}
+
+impl Default for BossBody {
+    fn default() -> Self {
+        Self {
+            boss: default_entity(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct BossCore {
+    pub boss: Entity,
+}
+
+impl Default for BossCore {
+    fn default() -> Self {
+        Self {
+            boss: default_entity(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct BossPart {
+    pub boss: Entity,
+    /// The core is invulnerable while any shielding part is alive
+    pub shields_core: bool,
+}
+
+impl Default for BossPart {
+    fn default() -> Self {
+        Self {
+            boss: default_entity(),
+            shields_core: default(),
+        }
+    }
+}
+
+/// Pivot that is only active during a given boss phase
+#[derive(Debug, Copy, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct BossPhaseMember {
+    pub boss: Entity,
+    pub phase: usize,
+}
+
+impl Default for BossPhaseMember {
+    fn default() -> Self {
+        Self {
+            boss: default_entity(),
+            phase: default(),
+        }
+    }
+}
+
+/// Bullet volleys fired from a phase pivot
+#[derive(Debug, Default, Clone, Component)]
+pub struct BossEmitters {
+    pub volleys: Vec<BossVolley>,
+    prev_t: f64,
+}
+
+/// A ring of straight bullets fired at a fixed interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossVolley {
+    pub interval: f64,
+    /// Firing angles in radians, where zero points along +X
+    pub angles: Vec<f32>,
+    pub speed: f32,
+    pub lifetime: f64,
+}
+
+impl Default for BossVolley {
+    fn default() -> Self {
+        Self {
+            interval: 0.5,
+            angles: vec![std::f32::consts::PI],
+            speed: 10.0,
+            lifetime: 3.0,
+        }
+    }
+}
+
+/// Description of a boss, as authored in stage assets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossDesc {
+    pub name: String,
+    pub core: BossPartDesc,
+    #[serde(default)]
+    pub parts: Vec<BossPartDesc>,
+    pub phases: Vec<BossPhaseDesc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossPartDesc {
+    /// Scene providing the part's mesh and hurtbox
+    #[serde(default = "BossPartDesc::default_scene")]
+    pub scene: String,
+    pub hit_points: usize,
+    /// Position relative to the boss body
+    #[serde(default)]
+    pub offset: (f32, f32),
+    #[serde(default)]
+    pub shields_core: bool,
+    #[serde(default)]
+    pub mesh_rotation: Option<MeshRotationDesc>,
+}
+
+impl BossPartDesc {
+    fn default_scene() -> String {
+        SCENE_ENEMY.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossPhaseDesc {
+    /// Ignored for the first phase
+    #[serde(default)]
+    pub trigger: PhaseTrigger,
+    #[serde(default)]
+    pub movement: Option<BossMovement>,
+    #[serde(default)]
+    pub volleys: Vec<BossVolley>,
+}
+
+/// Looping path traced by the boss body during a phase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossMovement {
+    pub points: Vec<(f32, f32)>,
+    pub period: f64,
+}
+
+impl BossMovement {
+    fn stops(&self) -> Vec<DiscreteStop<Transform>> {
+        // Close the loop so the path repeats seamlessly
+        let points = self
+            .points
+            .iter()
+            .chain(self.points.first())
+            .collect::<Vec<_>>();
+
+        let divisor = points.len().saturating_sub(1).max(1) as f64;
+
+        points
+            .into_iter()
+            .enumerate()
+            .map(|(i, (x, y))| DiscreteStop {
+                t: i as f64 / divisor,
+                value: Transform::from_xyz(*x, *y, 0.0),
+                ..default()
+            })
+            .collect()
+    }
+}
+
+pub struct InsertBoss {
+    pub playfield: Entity,
+    pub timeline: Entity,
+    pub entity: Entity,
+    pub bullet_pool: Entity,
+    pub spawn_at: f64,
+    pub transform: Transform,
+    pub desc: BossDesc,
+}
+
+impl Command for InsertBoss {
+    fn write(self, world: &mut World) {
+        let timeline = TimelineTime::new(self.timeline);
+
+        world
+            .entity_mut(self.entity)
+            .insert(Name::new(self.desc.name.clone()))
+            .insert_bundle(HierarchyBundle::default())
+            .insert(self.transform)
+            .insert(PlayfieldLayer::Enemies);
+
+        world
+            .entity_mut(self.playfield)
+            .push_children(&[self.entity]);
+
+        // Phases
+        let animation_phase = world.build_animation().from_discrete_stops([DiscreteStop {
+            t: 0.0,
+            value: BossPhase(0),
+            ..default()
+        }]);
+
+        let phase_stops = animation_phase.id();
+
+        let animation_phase = animation_phase
+            .try_animating_component(self.entity)
+            .with_time_source(timeline)
+            .evaluate()
+            .tagged::<PostUpdate>()
+            .insert(Name::new(format!("Boss {:?} Phase", self.entity)))
+            .id();
+
+        world
+            .entity_mut(self.entity)
+            .insert(BossPhase(0))
+            .push_children(&[animation_phase]);
+
+        let mut phases = vec![];
+        for (i, phase) in self.desc.phases.iter().enumerate() {
+            let pivot = world
+                .spawn()
+                .insert(Name::new(format!("Phase {i}")))
+                .insert_bundle(HierarchyBundle::default())
+                .insert(BossPhaseMember {
+                    boss: self.entity,
+                    phase: i,
+                })
+                .insert(BossEmitters {
+                    volleys: phase.volleys.clone(),
+                    prev_t: self.spawn_at,
+                })
+                .id();
+
+            if let Some(movement) = &phase.movement {
+                let animation_movement = world
+                    .build_animation()
+                    .from_discrete_stops(movement.stops())
+                    .interpolate()
+                    .with_dilation(1.0 / movement.period)
+                    .with_repeat(movement.period)
+                    .with_time_source(timeline)
+                    .animating_component(pivot)
+                    .evaluate()
+                    .tagged::<Update>()
+                    .insert(Name::new(format!("Phase {i} Movement")))
+                    .id();
+
+                world.entity_mut(pivot).push_children(&[animation_movement]);
+            }
+
+            world.entity_mut(self.entity).push_children(&[pivot]);
+            phases.push(pivot);
+        }
+
+        // Body
+        let body = world
+            .spawn()
+            .insert(Name::new("Body"))
+            .insert_bundle(HierarchyBundle::default())
+            .insert(BossBody { boss: self.entity })
+            .id();
+
+        world.entity_mut(self.entity).push_children(&[body]);
+
+        let insert_part = |world: &mut World, desc: &BossPartDesc| {
+            let part = world.spawn().id();
+
+            InsertEnemy {
+                playfield: body,
+                timeline: self.timeline,
+                entity: part,
+                scene: InsertSceneArchive {
+                    path: desc.scene.clone(),
+                    ..default()
+                },
+                timeline_damage: InsertTimelineDamage {
+                    hit_points: desc.hit_points,
+                    ..default()
+                },
+                timeline_alive: InsertTimelineAlive {
+                    alive_at: Some(self.spawn_at),
+                    ..default()
+                },
+                ..default()
+            }
+            .write(world);
+
+            world
+                .entity_mut(part)
+                .insert(Transform::from_xyz(desc.offset.0, desc.offset.1, 0.0));
+
+            if let Some(mesh_rotation) = &desc.mesh_rotation {
+                InsertMeshRotation {
+                    entity: part,
+                    timeline: self.timeline,
+                    t: self.spawn_at,
+                    desc: mesh_rotation.clone(),
+                }
+                .write(world);
+            }
+
+            part
+        };
+
+        let core = insert_part(world, &self.desc.core);
+        world
+            .entity_mut(core)
+            .insert(Name::new("Core"))
+            .insert(BossCore { boss: self.entity })
+            .insert(DeathEffect(Some(ParticleEffect::large_explosion())));
+
+        let parts = self
+            .desc
+            .parts
+            .iter()
+            .enumerate()
+            .map(|(i, desc)| {
+                let part = insert_part(world, desc);
+                world
+                    .entity_mut(part)
+                    .insert(Name::new(format!("Part {i}")))
+                    .insert(BossPart {
+                        boss: self.entity,
+                        shields_core: desc.shields_core,
+                    });
+                part
+            })
+            .collect::<Vec<_>>();
+
+        world.entity_mut(self.entity).insert(Boss {
+            timeline: self.timeline,
+            spawn_at: self.spawn_at,
+            body,
+            core,
+            parts,
+            bullet_pool: self.bullet_pool,
+            phase_stops,
+            triggers: self
+                .desc
+                .phases
+                .iter()
+                .map(|phase| phase.trigger)
+                .collect(),
+            phases,
+        });
+    }
+}
+
+/// Move the boss body to the pivot of the active phase
+pub fn boss_body(
+    query_boss: Query<(&Boss, &BossPhase)>,
+    query_pivot: Query<&Transform, (With<BossPhaseMember>, Without<BossBody>)>,
+    mut query_body: Query<&mut Transform, With<BossBody>>,
+) {
+    for (boss, phase) in query_boss.iter() {
+        let pivot = if let Some(pivot) = boss.phases.get(phase.0) {
+            *pivot
+        } else {
+            continue;
+        };
+
+        let pivot_transform = if let Ok(components) = query_pivot.get(pivot) {
+            *components
+        } else {
+            continue;
+        };
+
+        if let Ok(mut body_transform) = query_body.get_mut(boss.body) {
+            *body_transform = pivot_transform;
+        }
+    }
+}
+
+/// Make the core invulnerable while any of its shielding parts survive
+pub fn boss_core_vulnerability(
+    query_boss: Query<&Boss>,
+    query_part: Query<(&BossPart, &Alive)>,
+    query_invulnerable: Query<(), With<Invulnerable>>,
+    mut commands: Commands,
+) {
+    for boss in query_boss.iter() {
+        let shielded = boss.parts.iter().any(|part| {
+            query_part
+                .get(*part)
+                .map(|(part, alive)| part.shields_core && **alive)
+                .unwrap_or_default()
+        });
+
+        let invulnerable = query_invulnerable.get(boss.core).is_ok();
+
+        if shielded && !invulnerable {
+            commands.entity(boss.core).insert(Invulnerable);
+        } else if !shielded && invulnerable {
+            commands.entity(boss.core).remove::<Invulnerable>();
+        }
+    }
+}
+
+/// Destroy all remaining parts when the core dies
+pub fn boss_core_death(
+    mut death_events: EventReader<DeathEvent>,
+    query_core: Query<&BossCore>,
+    query_boss: Query<&Boss>,
+    query_timeline: Query<&Timeline>,
+    query_timeline_alive: Query<&TimelineAlive>,
+    mut query_alive_stops: Query<&mut Discrete<Alive>>,
+) {
+    for event in death_events.iter() {
+        let core = if let Ok(components) = query_core.get(event.entity) {
+            components
+        } else {
+            continue;
+        };
+
+        let boss = if let Ok(components) = query_boss.get(core.boss) {
+            components
+        } else {
+            continue;
+        };
+
+        let t = if let Ok(timeline) = query_timeline.get(boss.timeline) {
+            timeline.t
+        } else {
+            continue;
+        };
+
+        info!("Boss {:?} destroyed", core.boss);
+
+        for part in boss.parts.iter() {
+            let timeline_alive = if let Ok(components) = query_timeline_alive.get(*part) {
+                components
+            } else {
+                continue;
+            };
+
+            let mut alive_stops =
+                if let Ok(components) = query_alive_stops.get_mut(timeline_alive.alive_stops) {
+                    components
+                } else {
+                    continue;
+                };
+
+            alive_stops.insert_stop(DiscreteStop {
+                t,
+                value: Alive(false),
+                determinism: event.determinism,
+                ..default()
+            });
+        }
+    }
+}
+
+/// Advance to the next phase once its trigger is satisfied
+///
+/// New phase stops share a determinism group with the stop that caused them,
+/// so rewinding past the cause also discards the transition
+pub fn boss_phase_transition(
+    query_boss: Query<&Boss>,
+    query_timeline: Query<&Timeline>,
+    query_timeline_damage: Query<&TimelineDamage>,
+    query_hit_points_stops: Query<&Discrete<HitPoints>>,
+    query_timeline_alive: Query<&TimelineAlive>,
+    query_alive_stops: Query<&Discrete<Alive>>,
+    mut query_phase_stops: Query<&mut Discrete<BossPhase>>,
+) {
+    for boss in query_boss.iter() {
+        let timeline = if let Ok(components) = query_timeline.get(boss.timeline) {
+            components
+        } else {
+            continue;
+        };
+
+        if timeline.paused {
+            continue;
+        }
+
+        let t = timeline.t;
+        if t < boss.spawn_at {
+            continue;
+        }
+
+        let mut phase_stops = if let Ok(components) = query_phase_stops.get_mut(boss.phase_stops)
+        {
+            components
+        } else {
+            continue;
+        };
+
+        let current = if let Some(stop) = phase_stops.stop_at(t) {
+            stop.clone()
+        } else {
+            continue;
+        };
+
+        let next = current.value.0 + 1;
+        let trigger = if let Some(trigger) = boss.triggers.get(next) {
+            *trigger
+        } else {
+            continue;
+        };
+
+        let determinism = match trigger {
+            PhaseTrigger::Elapsed(elapsed) => {
+                if t >= current.t.max(boss.spawn_at) + elapsed {
+                    Some(current.determinism)
+                } else {
+                    None
+                }
+            }
+            PhaseTrigger::CoreHitPoints(hit_points) => {
+                let timeline_damage = if let Ok(components) = query_timeline_damage.get(boss.core)
+                {
+                    components
+                } else {
+                    continue;
+                };
+
+                let hit_points_stops = if let Ok(components) =
+                    query_hit_points_stops.get(timeline_damage.hit_points_stops)
+                {
+                    components
+                } else {
+                    continue;
+                };
+
+                hit_points_stops
+                    .stop_at(t)
+                    .filter(|stop| stop.value.0 <= hit_points)
+                    .map(|stop| stop.determinism)
+            }
+            // A boss without parts has nothing left to destroy
+            PhaseTrigger::PartsDestroyed if boss.parts.is_empty() => Some(current.determinism),
+            PhaseTrigger::PartsDestroyed => {
+                let part_deaths = boss
+                    .parts
+                    .iter()
+                    .map(|part| {
+                        // Parts that can't be looked up don't count as destroyed
+                        let timeline_alive = query_timeline_alive.get(*part).ok()?;
+                        query_alive_stops
+                            .get(timeline_alive.alive_stops)
+                            .ok()?
+                            .stop_at(t)
+                            .filter(|stop| !*stop.value)
+                            .cloned()
+                    })
+                    .collect::<Option<Vec<_>>>();
+
+                part_deaths.and_then(|stops| {
+                    stops
+                        .into_iter()
+                        .max_by(|lhs, rhs| lhs.t.total_cmp(&rhs.t))
+                        .map(|stop| stop.determinism)
+                })
+            }
+        };
+
+        let determinism = if let Some(determinism) = determinism {
+            determinism
+        } else {
+            continue;
+        };
+
+        info!("Boss entering phase {next:} at {t:}");
+
+        phase_stops.insert_stop(DiscreteStop {
+            t,
+            value: BossPhase(next),
+            determinism,
+            ..default()
+        });
+    }
+}
+
+/// Fire the volleys of the active phase
+pub fn boss_fire(
+    query_boss: Query<(Entity, &Boss, &BossPhase)>,
+    query_timeline: Query<&Timeline>,
+    query_alive: Query<&Alive>,
+    mut query_emitters: Query<(&BossPhaseMember, &Transform, &mut BossEmitters)>,
+    mut commands: Commands,
+) {
+    for (phase_member, transform, mut emitters) in query_emitters.iter_mut() {
+        let (boss_entity, boss, phase) = if let Ok(components) = query_boss.get(phase_member.boss)
+        {
+            components
+        } else {
+            continue;
+        };
+
+        let timeline = if let Ok(components) = query_timeline.get(boss.timeline) {
+            components
+        } else {
+            continue;
+        };
+
+        let prev_t = emitters.prev_t;
+        let t = timeline.t;
+        emitters.prev_t = t;
+
+        if timeline.paused || t <= prev_t || t < boss.spawn_at {
+            continue;
+        }
+
+        if phase.0 != phase_member.phase {
+            continue;
+        }
+
+        let core_alive = query_alive
+            .get(boss.core)
+            .map(|alive| **alive)
+            .unwrap_or_default();
+
+        if !core_alive {
+            continue;
+        }
+
+        for volley in emitters.volleys.iter() {
+            if (t / volley.interval).floor() <= (prev_t / volley.interval).floor() {
+                continue;
+            }
+
+            let speed = volley.speed;
+            let lifetime = volley.lifetime;
+
+            commands.add(UnpoolEntity {
+                source: boss_entity,
+                entity_pool: boss.bullet_pool,
+                t,
+                emitters: volley
+                    .angles
+                    .iter()
+                    .map(|angle| {
+                        *transform * Transform::from_rotation(Quat::from_rotation_z(*angle))
+                    })
+                    .collect(),
+                unpool: move |world: &mut World,
+                              bullet_entity: Entity,
+                              transform: Transform,
+                              t: f64| {
+                    let (from_stops, to_stops, alive_stops) = if let (Some(bullet), Some(alive)) = (
+                        world.get::<BulletAnimation>(bullet_entity),
+                        world.get::<TimelineAlive>(bullet_entity),
+                    ) {
+                        (bullet.from_stops, bullet.to_stops, alive.alive_stops)
+                    } else {
+                        return;
+                    };
+
+                    let mut determinisms = world.resource_mut::<Determinisms>();
+                    let determinism = determinisms.next();
+
+                    let mut from_stops = if let Some(components) =
+                        world.get_mut::<Discrete<DiscreteStop<Transform>>>(from_stops)
+                    {
+                        components
+                    } else {
+                        return;
+                    };
+
+                    from_stops.insert_stop(DiscreteStop {
+                        t,
+                        value: DiscreteStop {
+                            t,
+                            value: transform,
+                            ..default()
+                        },
+                        determinism,
+                        ..default()
+                    });
+
+                    let mut to_stops = if let Some(components) =
+                        world.get_mut::<Discrete<DiscreteStop<Transform>>>(to_stops)
+                    {
+                        components
+                    } else {
+                        return;
+                    };
+
+                    to_stops.insert_stop(DiscreteStop {
+                        t,
+                        value: DiscreteStop {
+                            t: t + lifetime,
+                            value: transform
+                                * Transform::from_translation(
+                                    Vec3::X * speed * lifetime as f32,
+                                ),
+                            ..default()
+                        },
+                        determinism,
+                        ..default()
+                    });
+
+                    let mut alive_stops =
+                        if let Some(components) = world.get_mut::<Discrete<Alive>>(alive_stops) {
+                            components
+                        } else {
+                            return;
+                        };
+
+                    alive_stops.insert_stop(DiscreteStop {
+                        t,
+                        value: Alive(true),
+                        determinism,
+                        ..default()
+                    });
+
+                    alive_stops.insert_stop(DiscreteStop {
+                        t: t + lifetime,
+                        value: Alive(false),
+                        determinism,
+                        ..default()
+                    });
+                },
+                instance_constructor: Some((
+                    boss.timeline,
+                    construct_enemy_bullet as fn(&mut World, Entity, Entity),
+                )),
+            });
+        }
+    }
+}
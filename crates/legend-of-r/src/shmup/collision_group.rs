@@ -1,24 +1,70 @@
+//! Data-driven collision groups
+//!
+//! Groups and their interaction matrix are declared in a RON config asset,
+//! and resolved into rapier [`CollisionGroups`] by name.
+//! The same file is embedded at build time to provide the defaults used until it loads,
+//! or if it fails to parse.
+//!
+//! [`CollisionGroups`]: bevy_rapier2d::prelude::CollisionGroups
+
+use std::{any::TypeId, collections::BTreeMap, ops::BitOr, ops::BitOrAssign};
+
 use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
     prelude::{
-        Commands, Component, CoreStage, Entity, ParallelSystemDescriptorCoercion, Plugin, Query,
-        ReflectComponent,
+        info, warn, AddAsset, AssetEvent, AssetServer, Assets, Commands, Component, CoreStage,
+        Entity, EventReader, Handle, Or, ParallelSystemDescriptorCoercion, Plugin, Query,
+        ReflectComponent, Res, ResMut, With,
     },
-    reflect::Reflect,
+    reflect::{Reflect, TypeRegistry, TypeUuid},
+    utils::BoxedFuture,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::{insert_ball_collider, insert_capsule_collider, ReflectBitflags};
 
-pub struct CollisionGroupsPlugin;
+/// Wildcard filter matching every declared group
+pub const COLLISION_GROUP_WILDCARD: &str = "*";
+
+pub struct CollisionGroupsPlugin {
+    pub path: &'static str,
+}
+
+/// Default collision group config, embedded so that it has a single source
+pub const COLLISION_GROUP_CONFIG_DEFAULT: &str =
+    include_str!("../../assets/config/shmup.collision_groups.ron");
+
+impl Default for CollisionGroupsPlugin {
+    fn default() -> Self {
+        Self {
+            path: "config/shmup.collision_groups.ron",
+        }
+    }
+}
 
 impl Plugin for CollisionGroupsPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.register_type::<CollisionGroup>()
-            .register_type::<CollisionGroupFlags>();
+            .register_type::<CollisionGroupFlags>()
+            .register_type::<CollisionGroupNames>();
+
+        app.add_asset::<CollisionGroupConfig>()
+            .init_asset_loader::<CollisionGroupConfigLoader>();
+
+        let config = CollisionGroupConfig::default();
+        update_bitflags(&app.world.resource::<TypeRegistry>(), &config);
+        app.insert_resource(config);
+
+        let handle: Handle<CollisionGroupConfig> =
+            app.world.resource::<AssetServer>().load(self.path);
+        app.insert_resource(CollisionGroupConfigHandle(handle));
 
         app.add_startup_system(collision_groups)
+            .add_system_to_stage(CoreStage::PreUpdate, collision_group_config)
             .add_system_to_stage(
                 CoreStage::PreUpdate,
                 collision_groups
+                    .after(collision_group_config)
                     .after(insert_ball_collider)
                     .after(insert_capsule_collider),
             );
@@ -31,27 +77,252 @@ impl Plugin for CollisionGroupsPlugin {
 // Hurtbox - Receives damage from hitboxes
 // Hitbox - Deals damage to hurtboxes
 // Sensor - Used for intersection tests not covered by the above
-bitflags::bitflags! {
-    #[derive(Default, Reflect)]
-    #[reflect(Bitflags)]
-    pub struct CollisionGroup: u32 {
-        const STATIC        = 0b00000000001;
 
-        const SHIP_BODY     = 0b00000000010;
-        const SHIP_HURTBOX  = 0b00000000100;
-        const SHIP_HITBOX   = 0b00000001000;
-        const SHIP_SENSOR   = 0b00000010000;
+/// Declaration of a single collision group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollisionGroupDesc {
+    pub name: String,
+    /// Names of the groups this group interacts with,
+    /// or [`COLLISION_GROUP_WILDCARD`] to interact with all of them
+    #[serde(default)]
+    pub filters: Vec<String>,
+}
 
-        const FORCE_BODY    = 0b00000100000;
-        const FORCE_HITBOX  = 0b00001000000;
-        const FORCE_SENSOR  = 0b00010000000;
+impl CollisionGroupDesc {
+    pub fn new<I, S>(name: &str, filters: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        CollisionGroupDesc {
+            name: name.to_string(),
+            filters: filters.into_iter().map(|filter| filter.to_string()).collect(),
+        }
+    }
+}
+
+/// Resource describing the available collision groups and their interaction matrix
+///
+/// Bits are assigned in declaration order, so appending new groups
+/// keeps existing scene data valid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TypeUuid)]
+#[uuid = "3c5e0d4a-7b61-4f2e-9d8a-0b4f6e2a91c7"]
+pub struct CollisionGroupConfig {
+    pub groups: Vec<CollisionGroupDesc>,
+}
 
-        const ENEMY_BODY    = 0b00100000000;
-        const ENEMY_HURTBOX = 0b01000000000;
-        const ENEMY_HITBOX  = 0b10000000000;
+impl Default for CollisionGroupConfig {
+    fn default() -> Self {
+        Self::parse(COLLISION_GROUP_CONFIG_DEFAULT.as_bytes())
+            .expect("Embedded collision group config is invalid")
     }
 }
 
+impl CollisionGroupConfig {
+    /// Parse and validate config from RON
+    pub fn parse(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let config: CollisionGroupConfig = ron::de::from_bytes(bytes)?;
+
+        if config.groups.len() > u32::BITS as usize {
+            return Err(anyhow::anyhow!(
+                "Declares {} groups, more than the maximum of {}",
+                config.groups.len(),
+                u32::BITS
+            ));
+        }
+
+        Ok(config)
+    }
+
+    /// Group with the given name
+    pub fn group(&self, name: &str) -> Option<CollisionGroup> {
+        self.groups
+            .iter()
+            .position(|group| group.name == name)
+            .map(|i| CollisionGroup::from_bits(1 << i))
+    }
+
+    /// Union of all declared groups
+    pub fn all(&self) -> CollisionGroup {
+        (0..self.groups.len()).fold(CollisionGroup::empty(), |acc, i| {
+            acc | CollisionGroup::from_bits(1 << i)
+        })
+    }
+
+    /// Groups that the named group interacts with
+    pub fn filters(&self, name: &str) -> Option<CollisionGroup> {
+        let desc = self.groups.iter().find(|group| group.name == name)?;
+
+        Some(
+            desc.filters
+                .iter()
+                .fold(CollisionGroup::empty(), |acc, filter| {
+                    if filter == COLLISION_GROUP_WILDCARD {
+                        return acc | self.all();
+                    }
+
+                    if let Some(group) = self.group(filter) {
+                        acc | group
+                    } else {
+                        warn!("Collision group {name} filters unknown group {filter}");
+                        acc
+                    }
+                }),
+        )
+    }
+
+    /// Memberships and filters for a set of group names
+    pub fn flags_for<'a, I>(&self, names: I) -> CollisionGroupFlags
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut flags = CollisionGroupFlags::default();
+
+        for name in names {
+            let (group, filters) =
+                if let (Some(group), Some(filters)) = (self.group(name), self.filters(name)) {
+                    (group, filters)
+                } else {
+                    warn!("Unknown collision group {name}");
+                    continue;
+                };
+
+            flags.memberships |= group;
+            flags.filters |= filters;
+        }
+
+        flags
+    }
+
+    /// Name to bit mapping, as consumed by [`ReflectBitflags`]
+    pub fn flags(&self) -> BTreeMap<String, usize> {
+        self.groups
+            .iter()
+            .enumerate()
+            .map(|(i, group)| (group.name.clone(), 1 << i))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct CollisionGroupConfigLoader;
+
+impl AssetLoader for CollisionGroupConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let config = CollisionGroupConfig::parse(bytes).unwrap_or_else(|e| {
+                warn!(
+                    "Failed to parse collision group config {:?}: {e}, using defaults",
+                    load_context.path()
+                );
+                CollisionGroupConfig::default()
+            });
+
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["collision_groups.ron"]
+    }
+}
+
+/// Handle to the config asset that drives the [`CollisionGroupConfig`] resource
+pub struct CollisionGroupConfigHandle(pub Handle<CollisionGroupConfig>);
+
+/// Flags are only known at runtime, so the bitflags type data
+/// is populated from config rather than derived
+fn update_bitflags(type_registry: &TypeRegistry, config: &CollisionGroupConfig) {
+    type_registry
+        .write()
+        .get_mut(TypeId::of::<CollisionGroup>())
+        .unwrap()
+        .insert(ReflectBitflags {
+            flags: config.flags(),
+        });
+}
+
+/// Replace the config resource whenever its asset loads or changes
+///
+/// Entities that have already been resolved keep their groups.
+pub fn collision_group_config(
+    mut events: EventReader<AssetEvent<CollisionGroupConfig>>,
+    handle: Res<CollisionGroupConfigHandle>,
+    assets: Res<Assets<CollisionGroupConfig>>,
+    type_registry: Res<TypeRegistry>,
+    mut config: ResMut<CollisionGroupConfig>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 => {}
+            _ => continue,
+        }
+
+        let loaded = if let Some(loaded) = assets.get(&handle.0) {
+            loaded
+        } else {
+            continue;
+        };
+
+        if *loaded != *config {
+            info!("Collision group config changed, updating");
+            *config = loaded.clone();
+            update_bitflags(&type_registry, &config);
+        }
+    }
+}
+
+/// Bitmask of collision groups, with bit meanings given by [`CollisionGroupConfig`]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
+pub struct CollisionGroup {
+    bits: u32,
+}
+
+impl CollisionGroup {
+    pub const fn empty() -> Self {
+        CollisionGroup { bits: 0 }
+    }
+
+    pub const fn from_bits(bits: u32) -> Self {
+        CollisionGroup { bits }
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub const fn contains(&self, other: CollisionGroup) -> bool {
+        self.bits & other.bits == other.bits
+    }
+}
+
+impl BitOr for CollisionGroup {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        CollisionGroup {
+            bits: self.bits | rhs.bits,
+        }
+    }
+}
+
+impl BitOrAssign for CollisionGroup {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.bits |= rhs.bits;
+    }
+}
+
+/// Explicit collision group bitmasks, as authored in external editors
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Component, Reflect)]
 #[reflect(Component)]
 pub struct CollisionGroupFlags {
@@ -59,6 +330,17 @@ pub struct CollisionGroupFlags {
     pub filters: CollisionGroup,
 }
 
+impl BitOr for CollisionGroupFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        CollisionGroupFlags {
+            memberships: self.memberships | rhs.memberships,
+            filters: self.filters | rhs.filters,
+        }
+    }
+}
+
 impl From<CollisionGroupFlags> for bevy_rapier2d::prelude::CollisionGroups {
     fn from(groups: CollisionGroupFlags) -> Self {
         bevy_rapier2d::prelude::CollisionGroups {
@@ -68,13 +350,98 @@ impl From<CollisionGroupFlags> for bevy_rapier2d::prelude::CollisionGroups {
     }
 }
 
-pub fn collision_groups(query: Query<(Entity, &CollisionGroupFlags)>, mut commands: Commands) {
-    for (entity, collision_groups) in query.iter() {
+/// Collision group memberships by name
+///
+/// Filters are looked up from the interaction matrix in [`CollisionGroupConfig`]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component)]
+pub struct CollisionGroupNames {
+    pub memberships: Vec<String>,
+}
+
+impl CollisionGroupNames {
+    pub fn new<I, S>(memberships: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        CollisionGroupNames {
+            memberships: memberships
+                .into_iter()
+                .map(|membership| membership.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Resolves [`CollisionGroupFlags`] and [`CollisionGroupNames`] into rapier collision groups
+pub fn collision_groups(
+    config: Res<CollisionGroupConfig>,
+    query: Query<
+        (
+            Entity,
+            Option<&CollisionGroupFlags>,
+            Option<&CollisionGroupNames>,
+        ),
+        Or<(With<CollisionGroupFlags>, With<CollisionGroupNames>)>,
+    >,
+    mut commands: Commands,
+) {
+    for (entity, collision_group_flags, collision_group_names) in query.iter() {
+        let mut flags = collision_group_flags.copied().unwrap_or_default();
+
+        if let Some(collision_group_names) = collision_group_names {
+            flags = flags
+                | config.flags_for(
+                    collision_group_names
+                        .memberships
+                        .iter()
+                        .map(String::as_str),
+                );
+        }
+
         commands
             .entity(entity)
             .remove::<CollisionGroupFlags>()
-            .insert(bevy_rapier2d::prelude::CollisionGroups::from(
-                *collision_groups,
-            ));
+            .remove::<CollisionGroupNames>()
+            .insert(bevy_rapier2d::prelude::CollisionGroups::from(flags));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_parses() {
+        let config = CollisionGroupConfig::default();
+        assert_eq!(config.group("STATIC"), Some(CollisionGroup::from_bits(1)));
+        assert_eq!(config.filters("STATIC"), Some(config.all()));
+    }
+
+    #[test]
+    fn flags_for_unions_memberships_and_filters() {
+        let config = CollisionGroupConfig {
+            groups: vec![
+                CollisionGroupDesc::new("A", ["B"]),
+                CollisionGroupDesc::new("B", ["A", "C"]),
+                CollisionGroupDesc::new("C", [COLLISION_GROUP_WILDCARD]),
+            ],
+        };
+
+        let flags = config.flags_for(["A", "B", "UNKNOWN"]);
+        assert_eq!(flags.memberships, CollisionGroup::from_bits(0b011));
+        assert_eq!(flags.filters, CollisionGroup::from_bits(0b111));
+    }
+
+    #[test]
+    fn parse_rejects_too_many_groups() {
+        let groups = (0..=u32::BITS)
+            .map(|i| format!("(name: \"G{i}\")"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let ron = format!("(groups: [{groups}])");
+        assert!(CollisionGroupConfig::parse(ron.as_bytes()).is_err());
     }
 }
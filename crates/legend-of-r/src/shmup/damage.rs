@@ -1,13 +1,15 @@
 use bevy::{
     ecs::{reflect::ReflectComponent, system::Command},
     prelude::{
-        default, info, Assets, BuildWorldChildren, Bundle, Changed, Children, Commands, Component,
-        CoreStage, Deref, DerefMut, Entity, EventReader, EventWriter, Handle, Mesh, Name,
+        default, info, AssetEvent, Assets, BuildWorldChildren, Bundle, Changed, Children, Commands,
+        Component,
+        CoreStage, Deref, DerefMut, Entity, EventReader, EventWriter, Handle, Local, Mesh, Name,
         ParallelSystemDescriptorCoercion, Parent, Plugin, Query, Res, ResMut, StandardMaterial,
         With, World, ReflectDefault,
     },
     reflect::Reflect,
     sprite::{ColorMaterial, Mesh2dHandle},
+    utils::HashMap,
 };
 use bevy_rapier2d::prelude::{Collider, RapierContext};
 
@@ -37,6 +39,7 @@ impl Plugin for DamagePlugin {
             .register_type::<Alive>()
             .register_type::<Hitbox>()
             .register_type::<Hurtbox>()
+            .register_type::<Invulnerable>()
             .register_type::<TimelineDamage>();
 
         app.add_event::<DamageEvent>().add_event::<DeathEvent>();
@@ -123,6 +126,11 @@ impl Default for Hitbox {
 #[reflect(Component)]
 pub struct Hurtbox;
 
+/// Prevents an entity with [`TimelineDamage`] from taking damage
+#[derive(Debug, Default, Copy, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct Invulnerable;
+
 #[derive(Debug, Clone, Component, Reflect)]
 #[reflect(Component)]
 pub struct TimelineDamage {
@@ -306,6 +314,8 @@ pub struct DamageEvent {
 #[derive(Debug, Copy, Clone)]
 pub struct DeathEvent {
     pub entity: Entity,
+    /// Hitbox that dealt the killing blow
+    pub source: Entity,
     pub determinism: DeterminismId,
 }
 
@@ -313,18 +323,54 @@ pub struct DeathEvent {
 #[reflect(Component)]
 pub struct ConvertMesh2d;
 
+/// Convert the child meshes of marked entities for use with the 2D mesh pipeline
+///
+/// Converted copies are cached by weak handle, so meshes shared between entities
+/// are only copied once without keeping either mesh alive.
 pub fn convert_mesh_2d(
     query_convert: Query<(Entity, &Children), With<ConvertMesh2d>>,
+    query_mesh: Query<&Handle<Mesh>>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut stripped: Local<HashMap<Handle<Mesh>, Handle<Mesh>>>,
     mut commands: Commands,
 ) {
+    for event in mesh_events.iter() {
+        if let AssetEvent::Removed { handle } = event {
+            stripped.retain(|from, to| from != handle && to != handle);
+        }
+    }
+
     for (entity, children) in query_convert.iter() {
         commands.entity(entity).remove::<ConvertMesh2d>();
         for child in children {
             let child = *child;
+
+            // The 2D mesh pipeline doesn't accept tangents, so convert to a copy without them
+            let mesh_2d = query_mesh.get(child).ok().map(|handle| {
+                if let Some(mesh_2d) = stripped.get(handle) {
+                    if meshes.contains(mesh_2d) {
+                        return meshes.get_handle(mesh_2d);
+                    }
+                }
+
+                let mesh_2d = match meshes.get(handle) {
+                    Some(mesh) if mesh.attribute(Mesh::ATTRIBUTE_TANGENT).is_some() => {
+                        let mut mesh = mesh.clone();
+                        mesh.remove_attribute(Mesh::ATTRIBUTE_TANGENT);
+                        meshes.add(mesh)
+                    }
+                    _ => return handle.clone(),
+                };
+
+                stripped.insert(handle.clone_weak(), mesh_2d.clone_weak());
+                mesh_2d
+            });
+
             commands.add(move |world: &mut World| {
                 let mut entity = world.entity_mut(child);
                 if let Some(handle) = entity.remove::<Handle<Mesh>>() {
-                    entity.insert(Mesh2dHandle(handle));
+                    entity.insert(Mesh2dHandle(mesh_2d.unwrap_or(handle)));
                 }
 
                 if let Some(handle) = entity.remove::<Handle<StandardMaterial>>() {
@@ -473,6 +519,7 @@ pub fn timeline_damage(
     mut determinisms: ResMut<Determinisms>,
     query_timeline: Query<&Timeline>,
     query_timeline_damage: Query<&TimelineDamage>,
+    query_invulnerable: Query<(), With<Invulnerable>>,
     mut query_hit_points_stops: Query<&mut Discrete<HitPoints>>,
     mut death_events: EventWriter<DeathEvent>,
 ) {
@@ -483,6 +530,10 @@ pub fn timeline_damage(
             continue;
         };
 
+        if query_invulnerable.get(event.target).is_ok() {
+            continue;
+        }
+
         let timeline = query_timeline.get(timeline_damage.timeline).unwrap();
         if timeline.paused {
             continue;
@@ -529,6 +580,7 @@ pub fn timeline_damage(
 
         death_events.send(DeathEvent {
             entity: event.target,
+            source: event.source,
             determinism,
         });
     }
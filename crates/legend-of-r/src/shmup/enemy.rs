@@ -1,6 +1,8 @@
 use crate::{
     scene::InsertSceneArchive,
-    prelude::{default_entity, InsertTimelineAlive, InsertTimelineDamage},
+    prelude::{
+        default_entity, InsertTimelineAlive, InsertTimelineDamage, PlayfieldLayer, ScoreValue,
+    },
 };
 use bevy::{
     ecs::system::Command,
@@ -16,6 +18,8 @@ pub struct InsertEnemy {
     pub scene: InsertSceneArchive,
     pub timeline_damage: InsertTimelineDamage,
     pub timeline_alive: InsertTimelineAlive,
+    pub score_value: ScoreValue,
+    pub playfield_layer: PlayfieldLayer,
 }
 
 impl Default for InsertEnemy {
@@ -30,16 +34,25 @@ impl Default for InsertEnemy {
             },
             timeline_damage: default(),
             timeline_alive: default(),
+            score_value: default(),
+            playfield_layer: PlayfieldLayer::Enemies,
         }
     }
 }
 
 impl Command for InsertEnemy {
     fn write(self, world: &mut bevy::prelude::World) {
+        // Leave room for mesh rotation animations alongside the movement transform
         let mut scene = self.scene;
         scene.entity = self.entity;
+        scene.mesh_rotation = true;
         scene.write(world);
 
+        world
+            .entity_mut(self.entity)
+            .insert(self.score_value)
+            .insert(self.playfield_layer);
+
         world
             .entity_mut(self.playfield)
             .push_children(&[self.entity]);
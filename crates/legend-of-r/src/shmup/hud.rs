@@ -0,0 +1,698 @@
+//! Player-facing heads-up display drawn in the letterbox around the playfield
+//!
+//! While the HUD is present, debug widgets without a saved layout float over the playfield
+//! rather than docking into the letterbox.
+//! Readings are gathered each frame from the components they display.
+//! Lives, scores and boss health are evaluated from timeline stops,
+//! so scrubbing or rewinding a timeline is reflected on the HUD without any bookkeeping.
+//! Time ability gauges are spent in real time instead, and aren't restored by a rewind.
+//! Nodes are only rebuilt when the layout changes, such as a life being lost or a boss spawning;
+//! scores and gauges update existing nodes in place.
+
+use std::{any::type_name, collections::BTreeMap};
+
+use bevy::prelude::{
+    default, AlignItems, App, AssetServer, BuildChildren, Color, Commands, Component, CoreStage,
+    DespawnRecursiveExt, Entity, FlexDirection, Font, Handle, JustifyContent, Local, Mut, Name,
+    NodeBundle, ParallelSystemDescriptorCoercion, Plugin, PositionType, Query, Res, ResMut, Size,
+    Style, Text, TextBundle, TextStyle, UiRect, Val, Windows,
+};
+
+use crate::prelude::{
+    evaluate_tagged, AspectRatio, Boss, Discrete, HitPoints, Last, Lives, PlayerId,
+    PlayfieldLayer, Score, TimeAbilities, TimelineDamage, TimelineUiState,
+};
+
+/// Font used for all HUD text, see `assets/fonts/README.md` for its license
+pub const HUD_FONT: &str = "fonts/DejaVuSansMono.ttf";
+pub const HUD_FONT_SIZE: f32 = 20.0;
+pub const HUD_PADDING: f32 = 12.0;
+pub const HUD_LIFE_SIZE: f32 = 12.0;
+pub const HUD_GAUGE_WIDTH: f32 = 96.0;
+pub const HUD_GAUGE_HEIGHT: f32 = 8.0;
+pub const HUD_BOSS_WIDTH: f32 = 160.0;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HudState>();
+
+        app.add_startup_system(setup_hud);
+
+        app.add_system_to_stage(CoreStage::Last, hud_players.after(evaluate_tagged::<Last>))
+            .add_system_to_stage(CoreStage::Last, hud_bosses.after(evaluate_tagged::<Last>))
+            .add_system_to_stage(CoreStage::Last, hud_letterbox)
+            .add_system_to_stage(
+                CoreStage::Last,
+                hud_layout.after(hud_players).after(hud_bosses),
+            )
+            .add_system_to_stage(CoreStage::Last, hud_values.after(hud_layout));
+
+        app.add_hud_gauges::<TimeAbilities>();
+    }
+}
+
+/// A component that contributes gauges to its player's HUD block
+pub trait HudGauges: 'static + Send + Sync + Component {
+    /// Label and fill from empty at 0 to full at 1 for each gauge
+    fn gauges(&self) -> Vec<(&'static str, f32)>;
+}
+
+/// Gauges are spent in real time rather than recorded on a timeline,
+/// so rewinding doesn't refill them
+impl HudGauges for TimeAbilities {
+    fn gauges(&self) -> Vec<(&'static str, f32)> {
+        let mut gauges = vec![];
+
+        if self.slow.is_some() || self.stop.is_some() {
+            gauges.push(("TIME", self.scale_gauge));
+        }
+
+        if self.rewind.is_some() {
+            gauges.push(("RWND", self.rewind_gauge));
+        }
+
+        gauges
+    }
+}
+
+pub trait AddHudGauges {
+    /// Show the gauges of a component on its player's HUD block
+    ///
+    /// Does nothing if the HUD has been disabled, so games can register gauges unconditionally.
+    ///
+    /// # Panics
+    ///
+    /// If called before ShmupPlugin has been added.
+    fn add_hud_gauges<C>(&mut self) -> &mut Self
+    where
+        C: HudGauges;
+}
+
+impl AddHudGauges for App {
+    fn add_hud_gauges<C>(&mut self) -> &mut Self
+    where
+        C: HudGauges,
+    {
+        if !self.world.contains_resource::<HudState>() {
+            assert!(
+                self.world.contains_resource::<HudDisabled>(),
+                "Gauges for {} were added before ShmupPlugin",
+                type_name::<C>()
+            );
+            return self;
+        }
+
+        self.add_system_to_stage(
+            CoreStage::Last,
+            hud_gauges::<C>.after(hud_players).before(hud_layout),
+        )
+    }
+}
+
+/// Marks an app that runs without the HUD, e.g. headless simulation
+#[derive(Debug, Default, Copy, Clone)]
+pub struct HudDisabled;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HudGauge {
+    pub label: &'static str,
+    pub fill: f32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PlayerHud {
+    pub lives: Option<usize>,
+    pub score: Option<usize>,
+    pub gauges: Vec<HudGauge>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BossHud {
+    pub boss: Entity,
+    pub name: String,
+    pub fill: f32,
+}
+
+/// Readings displayed by the HUD, gathered anew each frame
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HudState {
+    pub players: BTreeMap<PlayerId, PlayerHud>,
+    pub bosses: Vec<BossHud>,
+}
+
+impl HudState {
+    /// Whether two states can be displayed by the same set of nodes
+    pub fn same_layout(&self, other: &HudState) -> bool {
+        self.players.len() == other.players.len()
+            && self
+                .players
+                .iter()
+                .zip(other.players.iter())
+                .all(|((id_a, a), (id_b, b))| {
+                    id_a == id_b
+                        && a.lives == b.lives
+                        && a.score.is_some() == b.score.is_some()
+                        && a.gauges.len() == b.gauges.len()
+                        && a.gauges
+                            .iter()
+                            .zip(b.gauges.iter())
+                            .all(|(a, b)| a.label == b.label)
+                })
+            && self.bosses.len() == other.bosses.len()
+            && self
+                .bosses
+                .iter()
+                .zip(other.bosses.iter())
+                .all(|(a, b)| a.boss == b.boss && a.name == b.name)
+    }
+}
+
+/// Which side of the playfield a letterbox panel sits on
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HudSide {
+    /// Left in landscape, top in portrait
+    Min,
+    /// Right in landscape, bottom in portrait
+    Max,
+}
+
+#[derive(Debug, Copy, Clone, Component)]
+pub struct HudPanel {
+    pub side: HudSide,
+}
+
+/// Marks a node whose text or fill is driven by a HUD reading
+#[derive(Debug, Copy, Clone, PartialEq, Component)]
+pub enum HudValue {
+    Score(PlayerId),
+    Gauge(PlayerId, usize),
+    Boss(Entity),
+}
+
+/// Handles to the persistent HUD nodes
+pub struct HudNodes {
+    pub font: Handle<Font>,
+    pub panel_min: Entity,
+    pub panel_max: Entity,
+}
+
+pub fn setup_hud(asset_server: Res<AssetServer>, mut commands: Commands) {
+    let font = asset_server.load(HUD_FONT);
+
+    let mut panel = |side: HudSide| {
+        commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    padding: UiRect::all(Val::Px(HUD_PADDING)),
+                    align_items: AlignItems::FlexStart,
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .insert(Name::new(format!("HUD Panel {side:?}")))
+            .insert(HudPanel { side })
+            .id()
+    };
+
+    let panel_min = panel(HudSide::Min);
+    let panel_max = panel(HudSide::Max);
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(Name::new("HUD"))
+        .insert(PlayfieldLayer::Hud)
+        .push_children(&[panel_min, panel_max]);
+
+    commands.insert_resource(HudNodes {
+        font,
+        panel_min,
+        panel_max,
+    });
+}
+
+pub fn hud_players(
+    query_player: Query<(&PlayerId, Option<&Lives>, Option<&Score>)>,
+    mut state: ResMut<HudState>,
+) {
+    state.players.clear();
+
+    for (player, lives, score) in query_player.iter() {
+        if lives.is_none() && score.is_none() {
+            continue;
+        }
+
+        let player_hud = state.players.entry(*player).or_default();
+
+        if let Some(lives) = lives {
+            player_hud.lives = Some(lives.lives);
+        }
+
+        if let Some(score) = score {
+            player_hud.score = Some(score.score);
+        }
+    }
+}
+
+pub fn hud_gauges<C>(query: Query<(&PlayerId, &C)>, mut state: ResMut<HudState>)
+where
+    C: HudGauges,
+{
+    for (player, component) in query.iter() {
+        state.players.entry(*player).or_default().gauges.extend(
+            component
+                .gauges()
+                .into_iter()
+                .map(|(label, fill)| HudGauge {
+                    label,
+                    fill: fill.clamp(0.0, 1.0),
+                }),
+        );
+    }
+}
+
+/// Read each boss' core health against the hit points it spawned with
+pub fn hud_bosses(
+    query_boss: Query<(Entity, &Boss, Option<&Name>)>,
+    query_core: Query<(&HitPoints, &TimelineDamage)>,
+    query_hit_points_stops: Query<&Discrete<HitPoints>>,
+    mut state: ResMut<HudState>,
+) {
+    state.bosses.clear();
+
+    for (entity, boss, name) in query_boss.iter() {
+        let (hit_points, timeline_damage) = if let Ok(components) = query_core.get(boss.core) {
+            components
+        } else {
+            continue;
+        };
+
+        if hit_points.0 == 0 {
+            continue;
+        }
+
+        let max = query_hit_points_stops
+            .get(timeline_damage.hit_points_stops)
+            .ok()
+            .and_then(|stops| stops.stops().next())
+            .map(|stop| stop.value.0)
+            .unwrap_or(hit_points.0)
+            .max(1);
+
+        state.bosses.push(BossHud {
+            boss: entity,
+            name: name.map(ToString::to_string).unwrap_or_default(),
+            fill: (hit_points.0 as f32 / max as f32).min(1.0),
+        });
+    }
+
+    state.bosses.sort_by_key(|boss| boss.boss);
+}
+
+/// Fit the HUD panels to the letterbox areas left over by the playfield's aspect ratio
+pub fn hud_letterbox(
+    windows: Res<Windows>,
+    base_aspect: Res<AspectRatio>,
+    timeline_ui_state: Res<TimelineUiState>,
+    mut query_panel: Query<(&HudPanel, &mut Style)>,
+) {
+    let window = if let Some(window) = windows.get_primary() {
+        window
+    } else {
+        return;
+    };
+
+    // Match the camera viewports, which stop short of the timeline UI
+    let width = window.width();
+    let height = if timeline_ui_state.rect.top() > 0.0 {
+        timeline_ui_state.rect.top().min(window.height())
+    } else {
+        window.height()
+    };
+
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    }
+
+    let landscape = width / height >= **base_aspect;
+
+    let letterbox_width = {
+        let half_width = width * 0.5;
+        let fac = width / (height * **base_aspect);
+        half_width - half_width / fac
+    };
+
+    let letterbox_height = {
+        let half_height = height * 0.5;
+        let fac = height / (width / **base_aspect);
+        half_height - half_height / fac
+    };
+
+    // UI space is Y-up, so top offsets are measured from the bottom of the window
+    let bottom = window.height() - height;
+
+    for (panel, mut style) in query_panel.iter_mut() {
+        let (position, size, flex_direction) = if landscape {
+            let left = match panel.side {
+                HudSide::Min => 0.0,
+                HudSide::Max => width - letterbox_width,
+            };
+
+            (
+                UiRect {
+                    left: Val::Px(left),
+                    top: Val::Px(bottom),
+                    ..default()
+                },
+                Size::new(Val::Px(letterbox_width), Val::Px(height)),
+                // Likewise, columns are reversed to read top to bottom
+                FlexDirection::ColumnReverse,
+            )
+        } else {
+            let top = match panel.side {
+                HudSide::Min => bottom + height - letterbox_height,
+                HudSide::Max => bottom,
+            };
+
+            (
+                UiRect {
+                    left: Val::Px(0.0),
+                    top: Val::Px(top),
+                    ..default()
+                },
+                Size::new(Val::Px(width), Val::Px(letterbox_height)),
+                FlexDirection::Row,
+            )
+        };
+
+        if style.position != position
+            || style.size != size
+            || style.flex_direction != flex_direction
+        {
+            style.position = position;
+            style.size = size;
+            style.flex_direction = flex_direction;
+        }
+    }
+}
+
+fn text(value: impl Into<String>, font: &Handle<Font>, color: Color) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: font.clone(),
+            font_size: HUD_FONT_SIZE,
+            color,
+        },
+    )
+}
+
+fn column() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::FlexStart,
+            margin: UiRect::all(Val::Px(HUD_PADDING * 0.5)),
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    }
+}
+
+fn row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::FlexStart,
+            margin: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    }
+}
+
+fn block(width: f32, height: f32, color: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Px(width), Val::Px(height)),
+            margin: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        color: color.into(),
+        ..default()
+    }
+}
+
+/// Spawn a bar whose fill is driven by the given value
+fn bar(commands: &mut Commands, width: f32, color: Color, value: HudValue) -> Entity {
+    let fill = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..default()
+            },
+            color: color.into(),
+            ..default()
+        })
+        .insert(value)
+        .id();
+
+    commands
+        .spawn_bundle(block(
+            width,
+            HUD_GAUGE_HEIGHT,
+            Color::rgba(1.0, 1.0, 1.0, 0.2),
+        ))
+        .push_children(&[fill])
+        .id()
+}
+
+fn player_block(
+    commands: &mut Commands,
+    font: &Handle<Font>,
+    player: PlayerId,
+    player_hud: &PlayerHud,
+) -> Entity {
+    let mut children = vec![commands
+        .spawn_bundle(text(format!("{}P", player.0 + 1), font, Color::WHITE))
+        .id()];
+
+    if player_hud.score.is_some() {
+        children.push(
+            commands
+                .spawn_bundle(text("", font, Color::WHITE))
+                .insert(HudValue::Score(player))
+                .id(),
+        );
+    }
+
+    if let Some(lives) = player_hud.lives {
+        let pips = (0..lives)
+            .map(|_| {
+                commands
+                    .spawn_bundle(block(HUD_LIFE_SIZE, HUD_LIFE_SIZE, Color::CYAN))
+                    .id()
+            })
+            .collect::<Vec<_>>();
+
+        children.push(commands.spawn_bundle(row()).push_children(&pips).id());
+    }
+
+    for (i, gauge) in player_hud.gauges.iter().enumerate() {
+        let label = commands
+            .spawn_bundle(text(gauge.label, font, Color::GRAY))
+            .id();
+
+        let bar = bar(
+            commands,
+            HUD_GAUGE_WIDTH,
+            Color::YELLOW,
+            HudValue::Gauge(player, i),
+        );
+
+        children.push(
+            commands
+                .spawn_bundle(row())
+                .push_children(&[label, bar])
+                .id(),
+        );
+    }
+
+    commands
+        .spawn_bundle(column())
+        .insert(Name::new(format!("HUD Player {}", player.0)))
+        .push_children(&children)
+        .id()
+}
+
+fn boss_block(commands: &mut Commands, font: &Handle<Font>, boss_hud: &BossHud) -> Entity {
+    let label = commands
+        .spawn_bundle(text(boss_hud.name.clone(), font, Color::WHITE))
+        .id();
+
+    let bar = bar(
+        commands,
+        HUD_BOSS_WIDTH,
+        Color::RED,
+        HudValue::Boss(boss_hud.boss),
+    );
+
+    commands
+        .spawn_bundle(column())
+        .insert(Name::new(format!("HUD Boss {:?}", boss_hud.boss)))
+        .push_children(&[label, bar])
+        .id()
+}
+
+/// Rebuild the contents of the HUD panels whenever the shape of the displayed state changes
+///
+/// The first player sits in the leading panel, with other players and bosses opposite.
+pub fn hud_layout(
+    state: Res<HudState>,
+    nodes: Res<HudNodes>,
+    mut prev: Local<Option<HudState>>,
+    mut commands: Commands,
+) {
+    if let Some(prev) = &*prev {
+        if prev.same_layout(&state) {
+            return;
+        }
+    }
+
+    *prev = Some(state.clone());
+
+    commands.entity(nodes.panel_min).despawn_descendants();
+    commands.entity(nodes.panel_max).despawn_descendants();
+
+    let mut children_min = vec![];
+    let mut children_max = vec![];
+
+    for (player, player_hud) in state.players.iter() {
+        let block = player_block(&mut commands, &nodes.font, *player, player_hud);
+
+        if player.0 % 2 == 0 {
+            children_min.push(block);
+        } else {
+            children_max.push(block);
+        }
+    }
+
+    for boss_hud in state.bosses.iter() {
+        children_max.push(boss_block(&mut commands, &nodes.font, boss_hud));
+    }
+
+    commands
+        .entity(nodes.panel_min)
+        .push_children(&children_min);
+
+    commands
+        .entity(nodes.panel_max)
+        .push_children(&children_max);
+}
+
+/// Write the current readings into the nodes spawned by hud_layout
+pub fn hud_values(
+    state: Res<HudState>,
+    mut query_value: Query<(&HudValue, Option<&mut Text>, Option<&mut Style>)>,
+) {
+    for (value, text, style) in query_value.iter_mut() {
+        match value {
+            HudValue::Score(player) => {
+                let (score, mut text) =
+                    if let (Some(player_hud), Some(text)) = (state.players.get(player), text) {
+                        (player_hud.score.unwrap_or_default(), text)
+                    } else {
+                        continue;
+                    };
+
+                let value = format!("{score:08}");
+                if text.sections[0].value != value {
+                    text.sections[0].value = value;
+                }
+            }
+            HudValue::Gauge(player, i) => {
+                let fill = state
+                    .players
+                    .get(player)
+                    .and_then(|player_hud| player_hud.gauges.get(*i))
+                    .map(|gauge| gauge.fill);
+
+                if let (Some(fill), Some(style)) = (fill, style) {
+                    set_fill(style, fill);
+                }
+            }
+            HudValue::Boss(boss) => {
+                let fill = state
+                    .bosses
+                    .iter()
+                    .find(|boss_hud| boss_hud.boss == *boss)
+                    .map(|boss_hud| boss_hud.fill);
+
+                if let (Some(fill), Some(style)) = (fill, style) {
+                    set_fill(style, fill);
+                }
+            }
+        }
+    }
+}
+
+fn set_fill(mut style: Mut<Style>, fill: f32) {
+    let width = Val::Percent(fill * 100.0);
+    if style.size.width != width {
+        style.size.width = width;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(lives: usize, score: usize, fill: f32) -> HudState {
+        let mut state = HudState::default();
+        state.players.insert(
+            PlayerId(0),
+            PlayerHud {
+                lives: Some(lives),
+                score: Some(score),
+                gauges: vec![HudGauge { label: "TIME", fill }],
+            },
+        );
+        state
+    }
+
+    #[test]
+    fn values_keep_layout() {
+        assert!(state(3, 0, 1.0).same_layout(&state(3, 500, 0.25)));
+    }
+
+    #[test]
+    fn lost_life_changes_layout() {
+        assert!(!state(3, 0, 1.0).same_layout(&state(2, 0, 1.0)));
+    }
+
+    #[test]
+    fn add_hud_gauges_without_hud_is_allowed_when_disabled() {
+        let mut app = App::new();
+        app.insert_resource(HudDisabled);
+        app.add_hud_gauges::<TimeAbilities>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn add_hud_gauges_before_shmup_plugin_panics() {
+        App::new().add_hud_gauges::<TimeAbilities>();
+    }
+}
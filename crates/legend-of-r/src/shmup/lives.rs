@@ -4,9 +4,10 @@ use bevy::{
     ecs::{reflect::ReflectComponent, system::Command},
     prelude::{
         default, info, BuildWorldChildren, Component, CoreStage, Entity, EventReader, EventWriter,
-        Name, ParallelSystemDescriptorCoercion, Plugin, Query,
+        Name, ParallelSystemDescriptorCoercion, Plugin, Query, Res, With,
     },
     reflect::Reflect,
+    utils::HashMap,
 };
 
 use crate::{
@@ -19,7 +20,10 @@ use crate::{
         timeline::{Timeline, TimelineTime},
         AnimationTagTrait, BuildAnimation, Last,
     },
-    prelude::{timeline_death, Alive, DeathEvent, HitPoints, TimelineAlive, TimelineDamage},
+    prelude::{
+        timeline_death, Alive, CoopSettings, DeathEvent, HitPoints, PlayerId, Sharing,
+        TimelineAlive, TimelineDamage,
+    },
     util::default_entity,
 };
 
@@ -29,7 +33,8 @@ impl Plugin for LivesPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.register_type::<Lives>();
 
-        app.add_event::<RespawnEvent>();
+        app.add_event::<RespawnEvent>()
+            .add_event::<GameOverEvent>();
 
         app.add_system_to_stage(CoreStage::PostUpdate, lives_death.after(timeline_death))
             .add_system_to_stage(CoreStage::PostUpdate, lives_respawn.after(lives_death));
@@ -114,17 +119,35 @@ impl Command for InsertTimelineLives {
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub struct RespawnEvent {
     pub entity: Entity,
+    /// Entity whose lives are spent on the respawn
+    pub lives: Entity,
+    pub t: f64,
+    pub determinism: DeterminismId,
+}
+
+/// Sent when a death can't be paid for with lives
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct GameOverEvent {
+    pub entity: Entity,
+    /// Entity whose lives ran out
+    pub lives: Entity,
     pub t: f64,
     pub determinism: DeterminismId,
 }
 
 pub fn lives_death(
+    coop_settings: Res<CoopSettings>,
     mut death_events: EventReader<DeathEvent>,
     query_lives: Query<(&TimelineDamage, &TimelineLives)>,
+    query_player_lives: Query<(Entity, &TimelineLives), With<PlayerId>>,
     query_timeline: Query<&Timeline>,
     mut query_lives_stops: Query<&mut Discrete<Lives>>,
     mut respawn_events: EventWriter<RespawnEvent>,
+    mut game_over_events: EventWriter<GameOverEvent>,
 ) {
+    // Lives already committed to respawns this frame
+    let mut pending = HashMap::<Entity, usize>::default();
+
     for event in death_events.iter() {
         let (timeline_damage, timeline_lives) =
             if let Ok(components) = query_lives.get(event.entity) {
@@ -138,24 +161,58 @@ pub fn lives_death(
         let timeline = query_timeline.get(timeline_damage.timeline).unwrap();
         let t = timeline.t;
 
-        // If lives are available, queue a respawn
-        let mut lives_stops = query_lives_stops
-            .get_mut(timeline_lives.lives_stops)
-            .unwrap();
+        let available = |entity: Entity, timeline_lives: &TimelineLives| {
+            let lives_stops = query_lives_stops.get(timeline_lives.lives_stops).unwrap();
+            let lives = lives_stops.stops().last().unwrap().value.lives;
+            lives.saturating_sub(pending.get(&entity).copied().unwrap_or_default())
+        };
+
+        // Pick whose lives pay for the respawn
+        let payer = match coop_settings.lives {
+            Sharing::Separate => Some((event.entity, *timeline_lives)),
+            Sharing::Shared if query_player_lives.get(event.entity).is_ok() => {
+                query_player_lives
+                    .iter()
+                    .max_by_key(|(entity, timeline_lives)| available(*entity, *timeline_lives))
+                    .map(|(entity, timeline_lives)| (entity, *timeline_lives))
+            }
+            Sharing::Shared => Some((event.entity, *timeline_lives)),
+        };
 
-        let lives = lives_stops.stops().last().unwrap().value.lives;
+        let (payer, payer_lives) = if let Some(payer) = payer {
+            payer
+        } else {
+            continue;
+        };
 
+        // If lives are available, queue a respawn
+        let lives = available(payer, &payer_lives);
         if lives == 0 {
+            game_over_events.send(GameOverEvent {
+                entity: event.entity,
+                lives: payer,
+                t,
+                determinism: event.determinism,
+            });
             continue;
         }
 
+        *pending.entry(payer).or_default() += 1;
+
         respawn_events.send(RespawnEvent {
             entity: event.entity,
+            lives: payer,
             t: t + 1.0,
             determinism: event.determinism,
         });
 
         // Record the current lives count so the respawn event can have a determism anchor
+        let mut lives_stops = query_lives_stops
+            .get_mut(payer_lives.lives_stops)
+            .unwrap();
+
+        let lives = lives_stops.stops().last().unwrap().value.lives;
+
         lives_stops.insert_stop(DiscreteStop {
             t,
             value: Lives { lives },
@@ -167,55 +224,63 @@ pub fn lives_death(
 
 pub fn lives_respawn(
     mut respawn_events: EventReader<RespawnEvent>,
-    query_lives: Query<(Entity, &TimelineDamage, &TimelineAlive, &TimelineLives)>,
+    query_respawn: Query<(&TimelineDamage, &TimelineAlive)>,
+    query_lives: Query<&TimelineLives>,
     mut query_hit_points_stops: Query<&mut Discrete<HitPoints>>,
     mut query_alive_stops: Query<&mut Discrete<Alive>>,
     mut query_lives_stops: Query<&mut Discrete<Lives>>,
 ) {
-    for (ship_entity, timeline_damage, timeline_death, timeline_lives) in query_lives.iter() {
-        for event in respawn_events.iter() {
-            if event.entity != ship_entity {
+    for event in respawn_events.iter() {
+        let (timeline_damage, timeline_alive) =
+            if let Ok(components) = query_respawn.get(event.entity) {
+                components
+            } else {
                 continue;
-            }
+            };
 
-            let t = event.t;
+        let timeline_lives = if let Ok(components) = query_lives.get(event.lives) {
+            components
+        } else {
+            continue;
+        };
 
-            // Decrement lives
-            let mut lives_stops = query_lives_stops
-                .get_mut(timeline_lives.lives_stops)
-                .unwrap();
+        let t = event.t;
 
-            let lives = lives_stops.stops().last().unwrap().value.lives;
+        // Decrement lives
+        let mut lives_stops = query_lives_stops
+            .get_mut(timeline_lives.lives_stops)
+            .unwrap();
 
-            lives_stops.insert_stop(DiscreteStop {
-                t,
-                value: Lives { lives: lives - 1 },
-                determinism: event.determinism,
-                ..default()
-            });
+        let lives = lives_stops.stops().last().unwrap().value.lives;
 
-            // Reset hit points
-            let mut hit_points_stops = query_hit_points_stops
-                .get_mut(timeline_damage.hit_points_stops)
-                .unwrap();
+        lives_stops.insert_stop(DiscreteStop {
+            t,
+            value: Lives { lives: lives - 1 },
+            determinism: event.determinism,
+            ..default()
+        });
 
-            hit_points_stops.insert_stop(DiscreteStop {
-                t: event.t,
-                value: HitPoints(1),
-                determinism: event.determinism,
-                ..default()
-            });
+        // Reset hit points
+        let mut hit_points_stops = query_hit_points_stops
+            .get_mut(timeline_damage.hit_points_stops)
+            .unwrap();
 
-            // Reset alive
-            let mut alive_stops = query_alive_stops
-                .get_mut(timeline_death.alive_stops)
-                .unwrap();
-            alive_stops.insert_stop(DiscreteStop {
-                t,
-                value: Alive(true),
-                determinism: event.determinism,
-                ..default()
-            });
-        }
+        hit_points_stops.insert_stop(DiscreteStop {
+            t: event.t,
+            value: HitPoints(1),
+            determinism: event.determinism,
+            ..default()
+        });
+
+        // Reset alive
+        let mut alive_stops = query_alive_stops
+            .get_mut(timeline_alive.alive_stops)
+            .unwrap();
+        alive_stops.insert_stop(DiscreteStop {
+            t,
+            value: Alive(true),
+            determinism: event.determinism,
+            ..default()
+        });
     }
 }
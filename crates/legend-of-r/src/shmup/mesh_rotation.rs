@@ -0,0 +1,175 @@
+//! Mesh rotation independent of movement
+//!
+//! Enemies move via animations that drive their whole top-level transform,
+//! and playfield rotation overwrites the rotation of its targets outright,
+//! so neither can also carry a spin or bank without fighting over the same quaternion.
+//! Scenes assembled with a mesh rotation pivot sit under an extra child entity instead,
+//! whose rotation is animated separately and composed by transform propagation.
+
+use bevy::{
+    ecs::system::Command,
+    prelude::{default, warn, BuildWorldChildren, Component, Entity, Name, Transform, Vec3, World},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    default_entity, AngularVelocity, AngularVelocityTrait, AnimateComponentFieldsTrait,
+    AnimationTagTrait, BuildAnimation, EvaluateTrait, HierarchyBundle, InterpolateTrait,
+    MultiplyTrait, OffsetTrait, RotationStopsTrait, TimeSourceTrait, TimelineTime, Update,
+};
+
+/// Child entity between an entity's movement transform and its scene
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct MeshRotationPivot;
+
+/// Points an entity at the pivot its scene was assembled under
+#[derive(Debug, Copy, Clone, Component)]
+pub struct MeshRotation {
+    pub pivot: Entity,
+}
+
+/// Continuous spin about a fixed axis
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeshSpin {
+    pub axis: (f32, f32, f32),
+    /// Initial angular velocity, in radians per second
+    pub velocity: f64,
+    #[serde(default)]
+    pub acceleration: f64,
+    #[serde(default = "MeshSpin::default_max_velocity")]
+    pub max_velocity: f64,
+}
+
+impl MeshSpin {
+    fn default_max_velocity() -> f64 {
+        f64::INFINITY
+    }
+}
+
+impl From<MeshSpin> for AngularVelocity {
+    fn from(spin: MeshSpin) -> Self {
+        let (x, y, z) = spin.axis;
+        AngularVelocity::new(Vec3::new(x, y, z), spin.velocity)
+            .with_acceleration(spin.acceleration, spin.max_velocity)
+    }
+}
+
+/// Rotation applied to a mesh rotation pivot over an entity's lifetime
+///
+/// Stops and spin are composed, with the spin applied in the frame of the stops.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeshRotationDesc {
+    /// XYZ euler angles in radians, keyed by seconds since spawn
+    #[serde(default)]
+    pub stops: Vec<(f64, (f32, f32, f32))>,
+    #[serde(default)]
+    pub spin: Option<MeshSpin>,
+}
+
+impl MeshRotationDesc {
+    pub fn is_empty(&self) -> bool {
+        self.stops.is_empty() && self.spin.is_none()
+    }
+}
+
+/// Spawn a pivot under the given entity to parent its scene to
+pub struct InsertMeshRotationPivot {
+    pub entity: Entity,
+    pub pivot: Entity,
+}
+
+impl Default for InsertMeshRotationPivot {
+    fn default() -> Self {
+        Self {
+            entity: default_entity(),
+            pivot: default_entity(),
+        }
+    }
+}
+
+impl Command for InsertMeshRotationPivot {
+    fn write(self, world: &mut World) {
+        world
+            .entity_mut(self.pivot)
+            .insert(Name::new("Mesh Rotation"))
+            .insert(MeshRotationPivot)
+            .insert_bundle(HierarchyBundle::default());
+
+        world
+            .entity_mut(self.entity)
+            .insert(MeshRotation { pivot: self.pivot })
+            .push_children(&[self.pivot]);
+    }
+}
+
+/// Animate the mesh rotation pivot of an entity on the given timeline
+pub struct InsertMeshRotation {
+    pub entity: Entity,
+    pub timeline: Entity,
+    /// Timeline time that stops and spin are relative to
+    pub t: f64,
+    pub desc: MeshRotationDesc,
+}
+
+impl Default for InsertMeshRotation {
+    fn default() -> Self {
+        Self {
+            entity: default_entity(),
+            timeline: default_entity(),
+            t: default(),
+            desc: default(),
+        }
+    }
+}
+
+impl Command for InsertMeshRotation {
+    fn write(self, world: &mut World) {
+        if self.desc.is_empty() {
+            return;
+        }
+
+        let pivot = if let Some(mesh_rotation) = world.get::<MeshRotation>(self.entity) {
+            mesh_rotation.pivot
+        } else {
+            warn!("Entity {:?} has no mesh rotation pivot", self.entity);
+            return;
+        };
+
+        let stops = if self.desc.stops.is_empty() {
+            vec![(0.0, Vec3::ZERO)]
+        } else {
+            self.desc
+                .stops
+                .iter()
+                .map(|(t, (x, y, z))| (*t, Vec3::new(*x, *y, *z)))
+                .collect()
+        };
+
+        let spin = self
+            .desc
+            .spin
+            .map(AngularVelocity::from)
+            .unwrap_or_default();
+
+        let animation = world
+            .build_animation()
+            .from_rotation_stops(stops)
+            .interpolate_clamped()
+            .multiplied_with(|builder| builder.from_angular_velocity(spin))
+            .with_offset(-self.t)
+            .animating_component_field::<Transform>(
+                pivot,
+                |transform| &transform.rotation,
+                |transform| &mut transform.rotation,
+            )
+            .with_time_source(TimelineTime {
+                timeline: self.timeline,
+            })
+            .evaluate()
+            .tagged::<Update>()
+            .insert(Name::new(format!("Mesh {:?} Rotation", self.entity)))
+            .id();
+
+        world.entity_mut(pivot).push_children(&[animation]);
+    }
+}
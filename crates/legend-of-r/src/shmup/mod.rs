@@ -1,19 +1,28 @@
 pub mod archive;
+pub mod audio;
 pub mod background;
+pub mod boss;
 pub mod camera;
 pub mod collision_group;
 pub mod damage;
 pub mod enemy;
 pub mod entity_pool;
+pub mod hud;
 pub mod lives;
+pub mod mesh_rotation;
+pub mod particle;
 pub mod plane_collider;
+pub mod player;
 pub mod player_input;
 pub mod playfield;
+pub mod playfield_layer;
+pub mod score;
 pub mod shift_speed;
 pub mod ship;
+pub mod time_ability;
 pub mod vulcan;
 
-use bevy::prelude::{default, Plugin, Transform};
+use bevy::prelude::{default, Plugin, Quat, Transform};
 
 use bevy_rapier2d::{
     prelude::{NoUserData, RapierPhysicsPlugin},
@@ -21,39 +30,70 @@ use bevy_rapier2d::{
 };
 
 use crate::prelude::{
-    Alive, AnimationPlugin, BackgroundPlugin, CameraPlugin, ContactDepenetrationPlugin,
-    DamagePlugin, Disable, HitPoints, LivesPlugin, PlaneColliderPlugin, PlayerInputPlugin,
-    PlayfieldPlugin, RegisterAnimationType, ShapecastDepenetrationPlugin, ShipPlugin, VulcanPlugin,
-    ShiftSpeedPlugin,
+    Alive, AnimationPlugin, BackgroundPlugin, BossPlugin, CameraPlugin, ContactDepenetrationPlugin,
+    DamagePlugin, Disable, HitPoints, HudDisabled, HudPlugin, LivesPlugin, ParticlePlugin,
+    PlaneColliderPlugin, PlayerInputPlugin, PlayerPlugin, PlayfieldLayerPlugin, PlayfieldPlugin,
+    RegisterAnimationType, ScorePlugin, ShapecastDepenetrationPlugin, ShipPlugin, VulcanPlugin,
+    ShiftSpeedPlugin, TimeAbilityPlugin, TimelineAudioPlugin,
 };
 
-pub struct ShmupPlugin;
+pub struct ShmupPlugin {
+    /// Whether to add egui, the timeline UI, collider debug rendering and the HUD
+    pub ui: bool,
+}
+
+impl Default for ShmupPlugin {
+    fn default() -> Self {
+        Self { ui: true }
+    }
+}
+
+impl ShmupPlugin {
+    pub fn headless() -> Self {
+        ShmupPlugin { ui: false }
+    }
+}
 
 impl Plugin for ShmupPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_plugin(bevy_egui::EguiPlugin)
-            .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(1.0))
-            .add_plugin(RapierDebugRenderPlugin {
-                mode: DebugRenderMode::COLLIDER_SHAPES,
-                ..default()
-            })
+        app.add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(1.0))
             .add_plugin(ContactDepenetrationPlugin)
             .add_plugin(ShapecastDepenetrationPlugin)
             .add_plugin(CameraPlugin)
             .add_plugin(PlaneColliderPlugin)
+            .add_plugin(PlayerPlugin)
             .add_plugin(PlayerInputPlugin)
             .add_plugin(BackgroundPlugin)
             .add_plugin(PlayfieldPlugin)
-            .add_plugin(AnimationPlugin::with_ui())
+            .add_plugin(PlayfieldLayerPlugin)
             .add_plugin(ShiftSpeedPlugin)
             .add_plugin(LivesPlugin)
+            .add_plugin(ScorePlugin)
             .add_plugin(VulcanPlugin)
             .add_plugin(DamagePlugin)
-            .add_plugin(ShipPlugin);
+            .add_plugin(ParticlePlugin)
+            .add_plugin(BossPlugin)
+            .add_plugin(ShipPlugin)
+            .add_plugin(TimeAbilityPlugin)
+            .add_plugin(TimelineAudioPlugin);
+
+        if self.ui {
+            app.add_plugin(bevy_egui::EguiPlugin)
+                .add_plugin(HudPlugin)
+                .add_plugin(RapierDebugRenderPlugin {
+                    mode: DebugRenderMode::COLLIDER_SHAPES,
+                    ..default()
+                })
+                .add_plugin(AnimationPlugin::with_ui());
+        } else {
+            app.insert_resource(HudDisabled)
+                .add_plugin(AnimationPlugin::default());
+        }
 
         app.register_animation_type::<()>();
         app.register_animation_type::<HitPoints>();
         app.register_animation_type::<Transform>();
+        app.register_animation_type::<Quat>();
         app.register_animation_type::<Alive>();
         app.register_animation_type::<Disable<Alive>>();
     }
@@ -0,0 +1,758 @@
+//! Timeline-driven particle effects
+//!
+//! Effects are emitted as bursts stamped with the timeline time they were emitted at.
+//! Each particle's state is a pure function of the burst's seed and its age,
+//! so bursts play forward deterministically, replay correctly when scrubbed,
+//! and are despawned once the timeline is rewound to before their emission.
+//! Finished bursts are kept for [`PARTICLE_REWIND_HORIZON`] so rewinding can replay them.
+//!
+//! Hit sparks and explosions are emitted from damage and death events,
+//! and `ParticleEmitter` emits at a fixed rate for trails such as ship engines.
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::{
+    ecs::system::Command,
+    prelude::{
+        default, shape::Quad, Assets, BuildWorldChildren, Color, Commands, Component, CoreStage,
+        DespawnRecursiveExt, Entity, EventReader, FromWorld, GlobalTransform, Handle, Image, Mesh,
+        Name, ParallelSystemDescriptorCoercion, Parent, Plugin, Query, Res, ResMut, Sprite,
+        SpriteBundle, Transform, TransformBundle, Vec2, Vec3, With, Without, World,
+    },
+    sprite::{ColorMaterial, MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use crate::prelude::{
+    default_entity, timeline_damage, timeline_death, Alive, DamageEvent, DeathEvent,
+    Invulnerable, PlayfieldLayer, Timeline, TimelineAlive, TimelineDamage,
+};
+
+/// Timeline seconds a finished burst is kept for, after which rewinding won't replay it
+///
+/// Covers a full gauge of limited rewind.
+pub const PARTICLE_REWIND_HORIZON: f64 = 12.0;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<ParticleMeshes>()
+            .init_resource::<ParticleEffects>();
+
+        app.add_system(particle_emitter)
+            .add_system(particle_burst.after(particle_emitter))
+            .add_system_to_stage(CoreStage::PostUpdate, particle_damage.after(timeline_damage))
+            .add_system_to_stage(CoreStage::PostUpdate, particle_death.after(timeline_death));
+    }
+}
+
+/// How individual particles are drawn
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParticleShape {
+    /// Untextured square
+    Quad,
+    /// 2D mesh tinted by a per-burst color material
+    Mesh(Handle<Mesh>),
+    /// Textured sprite
+    Sprite(Handle<Image>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleEffect {
+    pub count: usize,
+    /// Seconds each particle lives for
+    pub lifetime: f32,
+    /// Minimum and maximum initial speed
+    pub speed: (f32, f32),
+    /// Central emission angle in radians, where 0 is +X
+    pub direction: f32,
+    /// Angle around the direction that particles are spread across, TAU for all directions
+    pub spread: f32,
+    /// Velocity lost per second, as a fraction of current velocity
+    pub drag: f32,
+    /// Size at the start and end of each particle's life
+    pub size: (f32, f32),
+    /// Color at the start and end of each particle's life
+    pub color: (Color, Color),
+    pub shape: ParticleShape,
+}
+
+impl Default for ParticleEffect {
+    fn default() -> Self {
+        Self {
+            count: 8,
+            lifetime: 0.5,
+            speed: (2.0, 6.0),
+            direction: 0.0,
+            spread: TAU,
+            drag: 4.0,
+            size: (0.2, 0.0),
+            color: (Color::WHITE, Color::rgba(1.0, 1.0, 1.0, 0.0)),
+            shape: ParticleShape::Quad,
+        }
+    }
+}
+
+impl ParticleEffect {
+    /// Small, short-lived burst for bullet impacts
+    pub fn sparks() -> Self {
+        ParticleEffect {
+            count: 6,
+            lifetime: 0.25,
+            speed: (4.0, 10.0),
+            drag: 8.0,
+            size: (0.15, 0.0),
+            color: (Color::rgb(1.0, 1.0, 0.8), Color::rgba(1.0, 0.6, 0.1, 0.0)),
+            ..default()
+        }
+    }
+
+    /// Large burst for destroyed entities
+    pub fn explosion() -> Self {
+        ParticleEffect {
+            count: 24,
+            lifetime: 0.6,
+            speed: (1.0, 8.0),
+            drag: 3.0,
+            size: (0.4, 0.05),
+            color: (Color::rgb(1.0, 0.9, 0.5), Color::rgba(0.8, 0.2, 0.05, 0.0)),
+            ..default()
+        }
+    }
+
+    /// Extended burst for destroyed bosses
+    pub fn large_explosion() -> Self {
+        ParticleEffect {
+            count: 64,
+            lifetime: 1.2,
+            speed: (1.0, 12.0),
+            drag: 2.0,
+            size: (0.8, 0.1),
+            ..ParticleEffect::explosion()
+        }
+    }
+
+    /// Short, narrow exhaust for engine trails
+    pub fn exhaust() -> Self {
+        ParticleEffect {
+            count: 2,
+            lifetime: 0.3,
+            speed: (3.0, 5.0),
+            direction: PI,
+            spread: 0.3,
+            drag: 6.0,
+            size: (0.15, 0.0),
+            color: (Color::rgb(0.6, 0.8, 1.0), Color::rgba(0.2, 0.3, 1.0, 0.0)),
+            ..default()
+        }
+    }
+
+    /// Offset from the burst origin of a given particle at a given age
+    pub fn offset(&self, seed: u64, index: usize, age: f32) -> Vec3 {
+        let angle =
+            self.direction + (random(seed, index, 0) - 0.5) * self.spread.clamp(0.0, TAU);
+        let speed = self.speed.0 + (self.speed.1 - self.speed.0) * random(seed, index, 1);
+        let velocity = Vec2::new(angle.cos(), angle.sin()) * speed;
+
+        // Integral of velocity decaying exponentially with drag
+        let distance = if self.drag > 0.0 {
+            (1.0 - (-self.drag * age).exp()) / self.drag
+        } else {
+            age
+        };
+
+        (velocity * distance).extend(0.0)
+    }
+
+    pub fn size_at(&self, age: f32) -> f32 {
+        let f = (age / self.lifetime).clamp(0.0, 1.0);
+        self.size.0 + (self.size.1 - self.size.0) * f
+    }
+
+    pub fn color_at(&self, age: f32) -> Color {
+        let f = (age / self.lifetime).clamp(0.0, 1.0);
+        let [r0, g0, b0, a0] = self.color.0.as_rgba_f32();
+        let [r1, g1, b1, a1] = self.color.1.as_rgba_f32();
+        Color::rgba(
+            r0 + (r1 - r0) * f,
+            g0 + (g1 - g0) * f,
+            b0 + (b1 - b0) * f,
+            a0 + (a1 - a0) * f,
+        )
+    }
+}
+
+/// Deterministic pseudo-random number in the 0..1 range
+fn random(seed: u64, index: usize, channel: u64) -> f32 {
+    // SplitMix64 finalizer
+    let mut z = seed
+        .wrapping_add((index as u64).wrapping_mul(0x9E3779B97F4A7C15))
+        .wrapping_add(channel.wrapping_mul(0xD1B54A32D192ED03));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Seed for a burst emitted by a given entity at a given time
+pub fn particle_seed(entity: Entity, t: f64) -> u64 {
+    entity.to_bits() ^ t.to_bits().rotate_left(32)
+}
+
+/// Meshes shared by all particle effects
+#[derive(Debug, Clone)]
+pub struct ParticleMeshes {
+    pub quad: Handle<Mesh>,
+}
+
+impl FromWorld for ParticleMeshes {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        ParticleMeshes {
+            quad: meshes.add(Quad::new(Vec2::ONE).into()),
+        }
+    }
+}
+
+/// Effects used for entities without their own HitEffect or DeathEffect
+#[derive(Debug, Clone)]
+pub struct ParticleEffects {
+    pub hit: ParticleEffect,
+    pub death: ParticleEffect,
+}
+
+impl Default for ParticleEffects {
+    fn default() -> Self {
+        Self {
+            hit: ParticleEffect::sparks(),
+            death: ParticleEffect::explosion(),
+        }
+    }
+}
+
+/// Overrides the effect emitted when this entity is damaged
+#[derive(Debug, Clone, Component)]
+pub struct HitEffect(pub Option<ParticleEffect>);
+
+/// Overrides the effect emitted when this entity dies
+#[derive(Debug, Clone, Component)]
+pub struct DeathEffect(pub Option<ParticleEffect>);
+
+/// A single emission of particles
+#[derive(Debug, Clone, Component)]
+pub struct ParticleBurst {
+    pub timeline: Entity,
+    /// Timeline time of emission
+    pub t: f64,
+    pub seed: u64,
+    pub effect: ParticleEffect,
+    /// Particles currently spawned, or empty if the burst isn't playing
+    pub particles: Vec<Entity>,
+    /// Color material shared by the burst's mesh particles
+    pub material: Option<Handle<ColorMaterial>>,
+}
+
+impl Default for ParticleBurst {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            t: default(),
+            seed: default(),
+            effect: default(),
+            particles: default(),
+            material: default(),
+        }
+    }
+}
+
+/// Spawn a burst at a position relative to an optional parent
+pub struct InsertParticleBurst {
+    pub entity: Entity,
+    pub parent: Option<Entity>,
+    pub translation: Vec3,
+    pub timeline: Entity,
+    pub t: f64,
+    pub seed: u64,
+    pub effect: ParticleEffect,
+}
+
+impl Default for InsertParticleBurst {
+    fn default() -> Self {
+        Self {
+            entity: default_entity(),
+            parent: default(),
+            translation: default(),
+            timeline: default_entity(),
+            t: default(),
+            seed: default(),
+            effect: default(),
+        }
+    }
+}
+
+impl Command for InsertParticleBurst {
+    fn write(self, world: &mut World) {
+        world
+            .entity_mut(self.entity)
+            .insert(Name::new("Particle Burst"))
+            .insert(PlayfieldLayer::Effects)
+            .insert_bundle(TransformBundle::from_transform(Transform::from_translation(
+                self.translation,
+            )))
+            .insert(ParticleBurst {
+                timeline: self.timeline,
+                t: self.t,
+                seed: self.seed,
+                effect: self.effect,
+                ..default()
+            });
+
+        if let Some(parent) = self.parent {
+            if let Some(mut parent) = world.get_entity_mut(parent) {
+                parent.push_children(&[self.entity]);
+            }
+        }
+    }
+}
+
+/// Continuously emits bursts at a fixed rate, e.g. for trails
+#[derive(Debug, Clone, Component)]
+pub struct ParticleEmitter {
+    pub timeline: Entity,
+    pub effect: ParticleEffect,
+    /// Bursts emitted per timeline second
+    pub rate: f64,
+    /// Emission point in the emitter's local space
+    pub offset: Vec3,
+    /// Timeline time of the latest burst
+    pub emitted_t: f64,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            effect: default(),
+            rate: 30.0,
+            offset: default(),
+            emitted_t: default(),
+        }
+    }
+}
+
+/// Burst position relative to the parent of an entity, which bursts are attached to
+fn burst_origin(
+    entity: Entity,
+    at: Entity,
+    offset: Vec3,
+    query_parent: &Query<&Parent>,
+    query_global: &Query<&GlobalTransform>,
+) -> (Option<Entity>, Vec3) {
+    let parent = query_parent.get(entity).ok().map(Parent::get);
+
+    let translation = query_global
+        .get(at)
+        .map(|global| global.compute_matrix().transform_point3(offset))
+        .unwrap_or_default();
+
+    let translation = match parent.and_then(|parent| query_global.get(parent).ok()) {
+        Some(parent) => parent.compute_matrix().inverse().transform_point3(translation),
+        None => translation,
+    };
+
+    (parent, translation)
+}
+
+/// Emit hit sparks where hitboxes strike damageable entities
+pub fn particle_damage(
+    effects: Res<ParticleEffects>,
+    mut damage_events: EventReader<DamageEvent>,
+    query_timeline: Query<&Timeline>,
+    query_timeline_damage: Query<(&TimelineDamage, Option<&Alive>), Without<Invulnerable>>,
+    query_hit_effect: Query<&HitEffect>,
+    query_parent: Query<&Parent>,
+    query_global: Query<&GlobalTransform>,
+    mut commands: Commands,
+) {
+    for event in damage_events.iter() {
+        let (timeline_damage, alive) =
+            if let Ok(components) = query_timeline_damage.get(event.target) {
+                components
+            } else {
+                continue;
+            };
+
+        // Hitboxes can linger over entities that have already been destroyed
+        if matches!(alive, Some(Alive(false))) {
+            continue;
+        }
+
+        let timeline = query_timeline.get(timeline_damage.timeline).unwrap();
+        if timeline.paused || timeline.scrub_rate != 0.0 {
+            continue;
+        }
+
+        let effect = match query_hit_effect.get(event.target) {
+            Ok(HitEffect(Some(effect))) => effect.clone(),
+            Ok(HitEffect(None)) => continue,
+            Err(_) => effects.hit.clone(),
+        };
+
+        let (parent, translation) =
+            burst_origin(event.target, event.source, Vec3::ZERO, &query_parent, &query_global);
+
+        let entity = commands.spawn().id();
+        commands.add(InsertParticleBurst {
+            entity,
+            parent,
+            translation,
+            timeline: timeline_damage.timeline,
+            t: timeline.t,
+            seed: particle_seed(event.source, timeline.t),
+            effect,
+        });
+    }
+}
+
+/// Emit explosions where entities die during regular playback
+///
+/// A death replayed at the time of an existing burst leaves that burst to play,
+/// so rewinding and replaying doesn't stack explosions.
+pub fn particle_death(
+    effects: Res<ParticleEffects>,
+    mut death_events: EventReader<DeathEvent>,
+    query_timeline: Query<&Timeline>,
+    query_timeline_alive: Query<&TimelineAlive>,
+    query_death_effect: Query<&DeathEffect>,
+    query_burst: Query<&ParticleBurst>,
+    query_parent: Query<&Parent>,
+    query_global: Query<&GlobalTransform>,
+    mut commands: Commands,
+) {
+    // Bursts inserted this frame aren't visible to the query until commands are applied
+    let mut emitted = vec![];
+
+    for event in death_events.iter() {
+        let timeline_alive = if let Ok(components) = query_timeline_alive.get(event.entity) {
+            components
+        } else {
+            continue;
+        };
+
+        let timeline = if let Ok(components) = query_timeline.get(timeline_alive.timeline) {
+            components
+        } else {
+            continue;
+        };
+
+        if timeline.paused || timeline.scrub_rate != 0.0 {
+            continue;
+        }
+
+        let t = timeline.t;
+        let seed = particle_seed(event.entity, t);
+
+        let exists = emitted.contains(&(timeline_alive.timeline, seed))
+            || query_burst
+                .iter()
+                .any(|burst| burst.timeline == timeline_alive.timeline && burst.seed == seed);
+
+        if exists {
+            continue;
+        }
+
+        let effect = match query_death_effect.get(event.entity) {
+            Ok(DeathEffect(Some(effect))) => effect.clone(),
+            Ok(DeathEffect(None)) => continue,
+            Err(_) => effects.death.clone(),
+        };
+
+        emitted.push((timeline_alive.timeline, seed));
+
+        let (parent, translation) =
+            burst_origin(event.entity, event.entity, Vec3::ZERO, &query_parent, &query_global);
+
+        let entity = commands.spawn().id();
+        commands.add(InsertParticleBurst {
+            entity,
+            parent,
+            translation,
+            timeline: timeline_alive.timeline,
+            t,
+            seed,
+            effect,
+        });
+    }
+}
+
+/// Emit bursts at a fixed rate during regular playback, while the emitter is alive
+pub fn particle_emitter(
+    mut query_emitter: Query<(Entity, &mut ParticleEmitter, Option<&Alive>)>,
+    query_timeline: Query<&Timeline>,
+    query_parent: Query<&Parent>,
+    query_global: Query<&GlobalTransform>,
+    mut commands: Commands,
+) {
+    for (entity, mut emitter, alive) in query_emitter.iter_mut() {
+        let timeline = if let Ok(timeline) = query_timeline.get(emitter.timeline) {
+            timeline
+        } else {
+            continue;
+        };
+
+        let t = timeline.t;
+
+        // Rewound bursts despawn themselves, so emit them again from the rewound time
+        if emitter.emitted_t > t {
+            emitter.emitted_t = t;
+        }
+
+        if timeline.paused || timeline.scrub_rate != 0.0 || emitter.rate <= 0.0 {
+            continue;
+        }
+
+        // Skip the time spent dead, rather than catching up on respawn
+        if matches!(alive, Some(Alive(false))) {
+            emitter.emitted_t = t;
+            continue;
+        }
+
+        let interval = 1.0 / emitter.rate;
+        let (parent, translation) =
+            burst_origin(entity, entity, emitter.offset, &query_parent, &query_global);
+
+        // Emit on multiples of the interval so replays match
+        let mut burst_t = (emitter.emitted_t / interval).floor() * interval + interval;
+        while burst_t <= t {
+            let burst = commands.spawn().id();
+            commands.add(InsertParticleBurst {
+                entity: burst,
+                parent,
+                translation,
+                timeline: emitter.timeline,
+                t: burst_t,
+                seed: particle_seed(entity, burst_t),
+                effect: emitter.effect.clone(),
+            });
+
+            emitter.emitted_t = burst_t;
+            burst_t += interval;
+        }
+    }
+}
+
+/// Spawn, animate and despawn the particles of each burst from its age on the timeline
+///
+/// Bursts are despawned entirely once rewound to before their emission,
+/// or once they've been finished for longer than the rewind horizon.
+pub fn particle_burst(
+    meshes: Res<ParticleMeshes>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut query_burst: Query<(Entity, &mut ParticleBurst)>,
+    query_timeline: Query<&Timeline>,
+    mut query_particle: Query<(&mut Transform, Option<&mut Sprite>), With<Particle>>,
+    mut commands: Commands,
+) {
+    for (entity, mut burst) in query_burst.iter_mut() {
+        let timeline = if let Ok(timeline) = query_timeline.get(burst.timeline) {
+            timeline
+        } else {
+            continue;
+        };
+
+        let age = timeline.t - burst.t;
+
+        if age < 0.0 || age > burst.effect.lifetime as f64 + PARTICLE_REWIND_HORIZON {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let age = age as f32;
+        let playing = age <= burst.effect.lifetime;
+
+        // Finished bursts keep only their record, so scrubbing back can replay them
+        if !playing {
+            if !burst.particles.is_empty() {
+                for particle in burst.particles.drain(..) {
+                    commands.entity(particle).despawn_recursive();
+                }
+            }
+            continue;
+        }
+
+        let color = burst.effect.color_at(age);
+        let size = burst.effect.size_at(age);
+
+        if burst.particles.is_empty() {
+            let particles = (0..burst.effect.count)
+                .map(|_| commands.spawn().insert(Particle).id())
+                .collect::<Vec<_>>();
+
+            let material = match &burst.effect.shape {
+                ParticleShape::Sprite(_) => None,
+                _ => Some(
+                    burst
+                        .material
+                        .get_or_insert_with(|| materials.add(ColorMaterial::default()))
+                        .clone(),
+                ),
+            };
+
+            for (i, particle) in particles.iter().copied().enumerate() {
+                let transform = Transform {
+                    translation: burst.effect.offset(burst.seed, i, age),
+                    scale: Vec3::splat(size),
+                    ..default()
+                };
+
+                let mut particle = commands.entity(particle);
+                match &burst.effect.shape {
+                    ParticleShape::Quad => particle.insert_bundle(MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(meshes.quad.clone()),
+                        material: material.clone().unwrap(),
+                        transform,
+                        ..default()
+                    }),
+                    ParticleShape::Mesh(mesh) => particle.insert_bundle(MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(mesh.clone()),
+                        material: material.clone().unwrap(),
+                        transform,
+                        ..default()
+                    }),
+                    ParticleShape::Sprite(image) => particle.insert_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color,
+                            custom_size: Some(Vec2::ONE),
+                            ..default()
+                        },
+                        texture: image.clone(),
+                        transform,
+                        ..default()
+                    }),
+                };
+            }
+
+            commands.entity(entity).push_children(&particles);
+            burst.particles = particles;
+        }
+
+        if let Some(material) = burst
+            .material
+            .as_ref()
+            .and_then(|material| materials.get_mut(material))
+        {
+            if material.color != color {
+                material.color = color;
+            }
+        }
+
+        for (i, particle) in burst.particles.iter().enumerate() {
+            let (mut transform, sprite) = if let Ok(components) = query_particle.get_mut(*particle)
+            {
+                components
+            } else {
+                continue;
+            };
+
+            transform.translation = burst.effect.offset(burst.seed, i, age);
+            transform.scale = Vec3::splat(size);
+
+            if let Some(mut sprite) = sprite {
+                if sprite.color != color {
+                    sprite.color = color;
+                }
+            }
+        }
+    }
+}
+
+/// Marker for entities spawned as part of a burst
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct Particle;
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::event::Events,
+        prelude::{Stage, SystemStage},
+    };
+
+    use super::*;
+
+    #[test]
+    fn random_is_deterministic_and_in_range() {
+        for index in 0..64 {
+            let value = random(42, index, 0);
+            assert!((0.0..1.0).contains(&value));
+            assert_eq!(value, random(42, index, 0));
+        }
+
+        assert_ne!(random(42, 0, 0), random(42, 0, 1));
+    }
+
+    #[test]
+    fn offset_starts_at_origin_and_slows_with_drag() {
+        let effect = ParticleEffect::sparks();
+        assert_eq!(effect.offset(7, 0, 0.0), Vec3::ZERO);
+
+        let early = effect.offset(7, 0, 0.1).length();
+        let late = effect.offset(7, 0, 0.2).length();
+        assert!(late > early);
+        assert!(late - early < early);
+    }
+
+    #[test]
+    fn size_and_color_clamp_to_lifetime() {
+        let effect = ParticleEffect::default();
+        assert_eq!(effect.size_at(0.0), effect.size.0);
+        assert_eq!(effect.size_at(effect.lifetime * 2.0), effect.size.1);
+        assert_eq!(effect.color_at(effect.lifetime * 2.0), effect.color.1);
+    }
+
+    #[test]
+    fn rewind_and_replay_does_not_duplicate_death_bursts() {
+        let mut world = World::default();
+        world.insert_resource(ParticleEffects::default());
+        world.insert_resource(Events::<DeathEvent>::default());
+
+        let mut stage = SystemStage::single(particle_death);
+
+        let timeline = world
+            .spawn()
+            .insert(Timeline {
+                t: 1.0,
+                ..default()
+            })
+            .id();
+
+        let entity = world
+            .spawn()
+            .insert(TimelineAlive {
+                timeline,
+                ..default()
+            })
+            .id();
+
+        let mut die = |world: &mut World| {
+            world.resource_mut::<Events<DeathEvent>>().send(DeathEvent {
+                entity,
+                source: entity,
+                determinism: default(),
+            });
+            stage.run(world);
+            world.query::<&ParticleBurst>().iter(world).count()
+        };
+
+        assert_eq!(die(&mut world), 1);
+
+        // Hitboxes lingering over the entity while rewinding
+        world.get_mut::<Timeline>(timeline).unwrap().scrub_rate = -3.0;
+        world.get_mut::<Timeline>(timeline).unwrap().t = 0.5;
+        assert_eq!(die(&mut world), 1);
+
+        // Replaying up to the original time of death
+        world.get_mut::<Timeline>(timeline).unwrap().scrub_rate = 0.0;
+        world.get_mut::<Timeline>(timeline).unwrap().t = 1.0;
+        assert_eq!(die(&mut world), 1);
+    }
+}
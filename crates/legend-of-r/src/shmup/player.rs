@@ -0,0 +1,81 @@
+//! Plugin for identifying player-owned entities and configuring co-op play
+
+use bevy::{
+    ecs::reflect::ReflectComponent,
+    prelude::{Component, Deref, DerefMut, Entity, Parent, Plugin, Query},
+    reflect::Reflect,
+};
+use serde::{Deserialize, Serialize};
+
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.register_type::<PlayerId>();
+
+        app.init_resource::<CoopSettings>();
+    }
+}
+
+/// Identifies the player that owns an entity
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deref,
+    DerefMut,
+    Component,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+#[reflect(Component)]
+pub struct PlayerId(pub usize);
+
+impl PlayerId {
+    /// Walk up the hierarchy from `entity` to find its owning player
+    pub fn of(
+        entity: Entity,
+        query_player: &Query<&PlayerId>,
+        query_parent: &Query<&Parent>,
+    ) -> Option<PlayerId> {
+        let mut candidate = entity;
+        loop {
+            if let Ok(player) = query_player.get(candidate) {
+                return Some(*player);
+            }
+
+            if let Ok(parent) = query_parent.get(candidate) {
+                candidate = **parent;
+            } else {
+                return None;
+            }
+        }
+    }
+}
+
+/// Whether a per-player resource is pooled between players
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
+pub enum Sharing {
+    Separate,
+    Shared,
+}
+
+impl Default for Sharing {
+    fn default() -> Self {
+        Sharing::Separate
+    }
+}
+
+/// Resource controlling how lives and score are distributed in co-op
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Reflect)]
+pub struct CoopSettings {
+    pub lives: Sharing,
+    pub score: Sharing,
+}
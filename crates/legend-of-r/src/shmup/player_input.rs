@@ -1,19 +1,27 @@
+use std::hash::Hash;
+
 use bevy::{
     input::{gamepad::gamepad_event_system, keyboard::keyboard_input_system},
     prelude::{
-        CoreStage, GamepadButton, GamepadButtonType, Input, KeyCode,
-        ParallelSystemDescriptorCoercion, Plugin, Res, ResMut, SystemLabel, SystemSet,
+        Axis, CoreStage, Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType,
+        Gamepads, Input, KeyCode, ParallelSystemDescriptorCoercion, Plugin, Res, ResMut,
+        SystemLabel, SystemSet,
     },
     reflect::Reflect,
     utils::HashMap,
 };
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{AxisDirection, InputBinding, InputBindings, InputState, PlayerId};
 
 #[derive(SystemLabel)]
 pub struct PlayerInputLabel;
 
 pub struct PlayerInputPlugin;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, Serialize, Deserialize,
+)]
 pub enum PlayerInput {
     Up,
     Down,
@@ -23,12 +31,16 @@ pub enum PlayerInput {
     Force,
     SpeedUp,
     SlowDown,
+    Dash,
+    SlowTime,
+    StopTime,
+    RewindTime,
 }
 
 impl Plugin for PlayerInputPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.init_resource::<Input<PlayerInput>>()
-            .init_resource::<PlayerInputMap>();
+        app.init_resource::<PlayerInputs>()
+            .init_resource::<PlayerInputMaps>();
 
         app.add_system_set_to_stage(
             CoreStage::PreUpdate,
@@ -37,38 +49,101 @@ impl Plugin for PlayerInputPlugin {
                     player_input_clear
                         .after(keyboard_input_system)
                         .after(gamepad_event_system)
-                        .before(player_input_press)
-                        .before(player_input_release),
+                        .before(player_input_update),
                 )
-                .with_system(player_input_press)
-                .with_system(player_input_release)
+                .with_system(player_input_update)
                 .label(PlayerInputLabel),
         );
     }
 }
 
+/// Per-player input state
 #[derive(Debug, Clone)]
+pub struct PlayerInputs<T = PlayerInput>
+where
+    T: 'static + Send + Sync + Copy + Eq + Hash,
+{
+    inputs: HashMap<PlayerId, Input<T>>,
+    values: HashMap<PlayerId, HashMap<T, f32>>,
+}
+
+impl<T> Default for PlayerInputs<T>
+where
+    T: 'static + Send + Sync + Copy + Eq + Hash,
+{
+    fn default() -> Self {
+        Self {
+            inputs: Default::default(),
+            values: Default::default(),
+        }
+    }
+}
+
+impl<T> PlayerInputs<T>
+where
+    T: 'static + Send + Sync + Copy + Eq + Hash,
+{
+    pub fn get(&self, player: PlayerId) -> Option<&Input<T>> {
+        self.inputs.get(&player)
+    }
+
+    pub fn get_mut(&mut self, player: PlayerId) -> &mut Input<T> {
+        self.inputs.entry(player).or_default()
+    }
+
+    pub fn pressed(&self, player: PlayerId, input: T) -> bool {
+        self.get(player)
+            .map(|inputs| inputs.pressed(input))
+            .unwrap_or_default()
+    }
+
+    pub fn just_pressed(&self, player: PlayerId, input: T) -> bool {
+        self.get(player)
+            .map(|inputs| inputs.just_pressed(input))
+            .unwrap_or_default()
+    }
+
+    pub fn just_released(&self, player: PlayerId, input: T) -> bool {
+        self.get(player)
+            .map(|inputs| inputs.just_released(input))
+            .unwrap_or_default()
+    }
+
+    pub fn values_mut(&mut self, player: PlayerId) -> &mut HashMap<T, f32> {
+        self.values.entry(player).or_default()
+    }
+
+    /// Analog strength of an input in the 0..1 range, or 1 if it's pressed without one
+    pub fn value(&self, player: PlayerId, input: T) -> f32 {
+        self.values
+            .get(&player)
+            .and_then(|values| values.get(&input))
+            .copied()
+            .unwrap_or_else(|| self.pressed(player, input) as usize as f32)
+    }
+
+    pub fn clear(&mut self) {
+        for input in self.inputs.values_mut() {
+            input.clear();
+        }
+    }
+}
+
+/// Input bindings for a single player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PlayerInputMap {
-    pub gamepad: HashMap<GamepadButtonType, PlayerInput>,
-    pub keyboard: HashMap<KeyCode, PlayerInput>,
+    pub player: PlayerId,
+    /// Gamepad to read from, or None to accept input from any gamepad
+    pub gamepad_id: Option<usize>,
+    pub bindings: InputBindings<PlayerInput>,
 }
 
 impl Default for PlayerInputMap {
     fn default() -> Self {
         Self {
-            gamepad: [
-                (GamepadButtonType::DPadLeft, PlayerInput::Left),
-                (GamepadButtonType::DPadDown, PlayerInput::Down),
-                (GamepadButtonType::DPadUp, PlayerInput::Up),
-                (GamepadButtonType::DPadRight, PlayerInput::Right),
-                (GamepadButtonType::East, PlayerInput::Fire),
-                (GamepadButtonType::North, PlayerInput::Force),
-                (GamepadButtonType::LeftTrigger, PlayerInput::SpeedUp),
-                (GamepadButtonType::RightTrigger, PlayerInput::SlowDown),
-            ]
-            .into_iter()
-            .collect(),
-            keyboard: [
+            player: PlayerId(0),
+            gamepad_id: None,
+            bindings: [
                 (KeyCode::H, PlayerInput::Left),
                 (KeyCode::J, PlayerInput::Down),
                 (KeyCode::K, PlayerInput::Up),
@@ -77,61 +152,194 @@ impl Default for PlayerInputMap {
                 (KeyCode::D, PlayerInput::Force),
                 (KeyCode::S, PlayerInput::SpeedUp),
                 (KeyCode::A, PlayerInput::SlowDown),
+                (KeyCode::G, PlayerInput::Dash),
+                (KeyCode::E, PlayerInput::SlowTime),
+                (KeyCode::W, PlayerInput::StopTime),
+                (KeyCode::R, PlayerInput::RewindTime),
             ]
             .into_iter()
+            .map(|(key_code, input)| (input, InputBinding::Key(key_code)))
+            .chain(Self::default_gamepad())
             .collect(),
         }
     }
 }
 
 impl PlayerInputMap {
-    pub fn gamepad(&self, gamepad_button: &GamepadButtonType) -> Option<PlayerInput> {
-        self.gamepad.get(gamepad_button).copied()
+    fn default_gamepad() -> impl Iterator<Item = (PlayerInput, InputBinding)> {
+        [
+            (GamepadButtonType::DPadLeft, PlayerInput::Left),
+            (GamepadButtonType::DPadDown, PlayerInput::Down),
+            (GamepadButtonType::DPadUp, PlayerInput::Up),
+            (GamepadButtonType::DPadRight, PlayerInput::Right),
+            (GamepadButtonType::East, PlayerInput::Fire),
+            (GamepadButtonType::North, PlayerInput::Force),
+            (GamepadButtonType::LeftTrigger, PlayerInput::SpeedUp),
+            (GamepadButtonType::RightTrigger, PlayerInput::SlowDown),
+            (GamepadButtonType::South, PlayerInput::Dash),
+            (GamepadButtonType::LeftTrigger2, PlayerInput::SlowTime),
+            (GamepadButtonType::West, PlayerInput::StopTime),
+            (GamepadButtonType::RightTrigger2, PlayerInput::RewindTime),
+        ]
+        .into_iter()
+        .map(|(button_type, input)| (input, InputBinding::GamepadButton(button_type)))
+        .chain(
+            [
+                (
+                    GamepadAxisType::LeftStickX,
+                    AxisDirection::Negative,
+                    PlayerInput::Left,
+                ),
+                (
+                    GamepadAxisType::LeftStickY,
+                    AxisDirection::Negative,
+                    PlayerInput::Down,
+                ),
+                (
+                    GamepadAxisType::LeftStickY,
+                    AxisDirection::Positive,
+                    PlayerInput::Up,
+                ),
+                (
+                    GamepadAxisType::LeftStickX,
+                    AxisDirection::Positive,
+                    PlayerInput::Right,
+                ),
+            ]
+            .into_iter()
+            .map(|(axis, direction, input)| (input, InputBinding::axis(axis, direction))),
+        )
     }
 
-    pub fn keyboard(&self, key_code: &KeyCode) -> Option<PlayerInput> {
-        self.keyboard.get(key_code).copied()
+    /// Right-hand side of a split keyboard, for a second local player
+    pub fn keyboard_right(player: PlayerId) -> Self {
+        Self {
+            player,
+            gamepad_id: Some(player.0),
+            bindings: [
+                (KeyCode::Left, PlayerInput::Left),
+                (KeyCode::Down, PlayerInput::Down),
+                (KeyCode::Up, PlayerInput::Up),
+                (KeyCode::Right, PlayerInput::Right),
+                (KeyCode::RShift, PlayerInput::Fire),
+                (KeyCode::RControl, PlayerInput::Force),
+                (KeyCode::Slash, PlayerInput::SpeedUp),
+                (KeyCode::Period, PlayerInput::SlowDown),
+                (KeyCode::Comma, PlayerInput::Dash),
+                (KeyCode::M, PlayerInput::SlowTime),
+                (KeyCode::N, PlayerInput::StopTime),
+                (KeyCode::B, PlayerInput::RewindTime),
+            ]
+            .into_iter()
+            .map(|(key_code, input)| (input, InputBinding::Key(key_code)))
+            .chain(Self::default_gamepad())
+            .collect(),
+        }
+    }
+
+    pub fn gamepad(&self) -> Option<Gamepad> {
+        self.gamepad_id.map(|id| Gamepad { id })
     }
 }
 
-fn player_input_clear(mut player_input: ResMut<Input<PlayerInput>>) {
-    player_input.clear();
+/// Input bindings for all local players
+#[derive(Debug, Clone)]
+pub struct PlayerInputMaps {
+    pub maps: Vec<PlayerInputMap>,
 }
 
-pub fn player_input_press(
-    key_input: Res<Input<KeyCode>>,
-    gamepad_input: Res<Input<GamepadButton>>,
-    player_input_map: Res<PlayerInputMap>,
-    mut player_input: ResMut<Input<PlayerInput>>,
-) {
-    for press in key_input.get_just_pressed() {
-        if let Some(input) = player_input_map.keyboard(press) {
-            player_input.press(input);
+impl Default for PlayerInputMaps {
+    fn default() -> Self {
+        Self {
+            maps: vec![PlayerInputMap::default()],
         }
     }
+}
 
-    for press in gamepad_input.get_just_pressed() {
-        if let Some(input) = player_input_map.gamepad(&press.button_type) {
-            player_input.press(input);
+impl PlayerInputMaps {
+    /// Two players sharing a keyboard, each with their own gamepad
+    pub fn coop() -> Self {
+        Self {
+            maps: vec![
+                PlayerInputMap {
+                    gamepad_id: Some(0),
+                    ..PlayerInputMap::default()
+                },
+                PlayerInputMap::keyboard_right(PlayerId(1)),
+            ],
         }
     }
+
+    pub fn players(&self) -> impl Iterator<Item = PlayerId> + '_ {
+        self.maps.iter().map(|map| map.player)
+    }
 }
 
-pub fn player_input_release(
+fn player_input_clear(mut player_inputs: ResMut<PlayerInputs>) {
+    player_inputs.clear();
+}
+
+pub fn player_input_update(
     key_input: Res<Input<KeyCode>>,
     gamepad_input: Res<Input<GamepadButton>>,
-    player_input_map: Res<PlayerInputMap>,
-    mut player_input: ResMut<Input<PlayerInput>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    player_input_maps: Res<PlayerInputMaps>,
+    mut player_inputs: ResMut<PlayerInputs>,
 ) {
-    for press in key_input.get_just_released() {
-        if let Some(input) = player_input_map.keyboard(press) {
-            player_input.release(input);
-        }
+    let state = InputState {
+        keys: &key_input,
+        buttons: &gamepad_input,
+        axes: &gamepad_axes,
+        gamepads: &gamepads,
+    };
+
+    for player_input_map in player_input_maps.maps.iter() {
+        player_input_map.bindings.update(
+            &state,
+            player_input_map.gamepad(),
+            player_inputs.get_mut(player_input_map.player),
+        );
+
+        player_input_map.bindings.update_values(
+            &state,
+            player_input_map.gamepad(),
+            player_inputs.values_mut(player_input_map.player),
+        );
     }
+}
 
-    for press in gamepad_input.get_just_released() {
-        if let Some(input) = player_input_map.gamepad(&press.button_type) {
-            player_input.release(input);
-        }
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::GamepadAxisType;
+
+    use super::*;
+
+    #[test]
+    fn partial_axis_deflection_is_not_full_strength() {
+        let binding = InputBinding::axis(GamepadAxisType::LeftStickX, AxisDirection::Positive);
+        let deadzone = match binding {
+            InputBinding::GamepadAxis { deadzone, .. } => deadzone,
+            _ => unreachable!(),
+        };
+
+        let mut player_inputs = PlayerInputs::default();
+        player_inputs.get_mut(PlayerId(0)).press(PlayerInput::Right);
+        player_inputs.values_mut(PlayerId(0)).insert(
+            PlayerInput::Right,
+            InputBinding::axis_value(0.7, AxisDirection::Positive, deadzone),
+        );
+
+        let value = player_inputs.value(PlayerId(0), PlayerInput::Right);
+        assert!(value > 0.0 && value < 1.0, "0.7 deflection read as {value}");
+    }
+
+    #[test]
+    fn pressed_without_value_is_full_strength() {
+        let mut player_inputs = PlayerInputs::default();
+        player_inputs.get_mut(PlayerId(0)).press(PlayerInput::Fire);
+
+        assert_eq!(player_inputs.value(PlayerId(0), PlayerInput::Fire), 1.0);
+        assert_eq!(player_inputs.value(PlayerId(0), PlayerInput::Left), 0.0);
     }
 }
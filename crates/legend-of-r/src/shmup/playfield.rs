@@ -5,9 +5,10 @@ use bevy::{
     ecs::system::Command,
     prelude::{
         default, BuildWorldChildren, Bundle, Camera, Camera2d, Camera2dBundle, Camera3d,
-        Camera3dBundle, Component, Entity, Name, OrthographicProjection, Plugin, Quat, Query,
+        Camera3dBundle, Component, Entity, Name, OrthographicProjection,
+        ParallelSystemDescriptorCoercion, Plugin, Quat, Query,
         ReflectComponent, ReflectDefault, ReflectDeserialize, ReflectSerialize, Res, Transform,
-        Vec2, Vec3, With, Without,
+        UiCameraConfig, Vec2, Vec3, With, Without,
     },
     reflect::Reflect,
     render::{
@@ -16,14 +17,15 @@ use bevy::{
     },
     transform::TransformBundle,
 };
-use bevy_rapier2d::prelude::{Collider, CollisionGroups, Vect};
+use bevy_rapier2d::prelude::{Collider, Vect};
 use serde::{Serialize, Deserialize};
 
 use crate::{
     hierarchy::HierarchyBundle,
     prelude::{
-        AspectRatio, CameraPivotSource, CameraPivotTarget, CameraViewportTarget, CollisionGroup,
-        OrthographicCameraTarget,
+        linear_move_timeline, AspectRatio, CameraPivotSource, CameraPivotTarget,
+        CameraViewportTarget, CollisionGroupNames, LinearMove, OrthographicCameraTarget,
+        PLAYFIELD_RENDER_LAYER,
     },
     util::default_entity,
 };
@@ -36,6 +38,7 @@ impl Plugin for PlayfieldPlugin {
 
         app.add_system(playfield_projection)
             .add_system(playfield_boundary)
+            .add_system(playfield_clamp.before(linear_move_timeline))
             .add_system(playfield_rotation);
     }
 }
@@ -130,7 +133,9 @@ impl Command for AssemblePlayfield {
                 ..default()
             })
             .insert(OrthographicProjection::default())
-            .insert(RenderLayers::layer(0))
+            .insert(RenderLayers::layer(PLAYFIELD_RENDER_LAYER))
+            // The HUD is drawn once, by the 2D camera on top
+            .insert(UiCameraConfig { show_ui: false })
             .id();
 
         let camera_ortho_2d = world
@@ -150,7 +155,7 @@ impl Command for AssemblePlayfield {
                 ..default()
             })
             .insert(Transform::default())
-            .insert(RenderLayers::layer(0))
+            .insert(RenderLayers::layer(PLAYFIELD_RENDER_LAYER))
             .id();
 
         let camera_rig = world
@@ -194,10 +199,7 @@ impl Command for SpawnBoundary {
             .insert(Boundary::<Bottom>::default())
             .insert_bundle(TransformBundle::default())
             .insert(Collider::halfspace(Vect::Y).unwrap())
-            .insert(CollisionGroups {
-                memberships: CollisionGroup::STATIC.bits(),
-                filters: CollisionGroup::all().bits(),
-            })
+            .insert(CollisionGroupNames::new(["STATIC"]))
             .id();
 
         let ceiling = world
@@ -206,10 +208,7 @@ impl Command for SpawnBoundary {
             .insert(Boundary::<Top>::default())
             .insert_bundle(TransformBundle::default())
             .insert(Collider::halfspace(Vect::NEG_Y).unwrap())
-            .insert(CollisionGroups {
-                memberships: CollisionGroup::STATIC.bits(),
-                filters: CollisionGroup::all().bits(),
-            })
+            .insert(CollisionGroupNames::new(["STATIC"]))
             .id();
 
         let wall_left = world
@@ -218,10 +217,7 @@ impl Command for SpawnBoundary {
             .insert(Boundary::<Left>::default())
             .insert_bundle(TransformBundle::default())
             .insert(Collider::halfspace(Vect::X).unwrap())
-            .insert(CollisionGroups {
-                memberships: CollisionGroup::STATIC.bits(),
-                filters: CollisionGroup::all().bits(),
-            })
+            .insert(CollisionGroupNames::new(["STATIC"]))
             .id();
 
         let wall_right = world
@@ -230,10 +226,7 @@ impl Command for SpawnBoundary {
             .insert(Boundary::<Right>::default())
             .insert_bundle(TransformBundle::default())
             .insert(Collider::halfspace(Vect::NEG_X).unwrap())
-            .insert(CollisionGroups {
-                memberships: CollisionGroup::STATIC.bits(),
-                filters: CollisionGroup::all().bits(),
-            })
+            .insert(CollisionGroupNames::new(["STATIC"]))
             .id();
 
         world
@@ -313,6 +306,39 @@ pub fn playfield_boundary(
     boundary_bottom.translation.y = -half_size.y;
 }
 
+/// Keeps an entity's LinearMove inside the playfield boundary
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct PlayfieldClamp {
+    /// Half-size of the entity, kept inside the boundary along with its origin
+    pub extents: Vec2,
+}
+
+pub fn playfield_clamp(
+    aspect: Res<AspectRatio>,
+    query_playfield: Query<&Playfield>,
+    query_camera_pivot: Query<&Transform, With<CameraPivotSource>>,
+    mut query_clamp: Query<(&PlayfieldClamp, &mut LinearMove)>,
+) {
+    let playfield = if let Some(playfield) = query_playfield.iter().next() {
+        playfield
+    } else {
+        return;
+    };
+
+    let camera_pivot = query_camera_pivot.iter().next().unwrap();
+    let half_size = playfield.half_size(**aspect, camera_pivot.translation.z);
+
+    for (clamp, mut linear_move) in query_clamp.iter_mut() {
+        let max = (half_size - clamp.extents).max(Vec2::ZERO);
+        let min = -max;
+
+        if linear_move.min.truncate() != min || linear_move.max.truncate() != max {
+            linear_move.min = min.extend(linear_move.min.z);
+            linear_move.max = max.extend(linear_move.max.z);
+        }
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone, Component)]
 pub struct PlayfieldRotationSource;
 
@@ -0,0 +1,285 @@
+//! Named draw layers for playfield entities
+//!
+//! Each layer maps to a Z offset within the playfield and a set of render layers.
+//! Render layers are applied to layered entities and their descendants anywhere,
+//! so background scenery is layered too, while Z is only applied under a [`Playfield`].
+//! Nested layered entities are offset relative to their nearest layered ancestor,
+//! so e.g. a ship's bullets land on the player bullet layer regardless of the ship's own Z.
+//!
+//! The Z offset replaces the translation Z of layered entities every frame,
+//! so any authored Z should be placed on an unlayered child instead.
+//! The HUD sits outside of any playfield, so its UI nodes keep the Z assigned by layout.
+
+use bevy::{
+    ecs::reflect::ReflectComponent,
+    prelude::{
+        Changed, Children, Commands, Component, CoreStage, Entity, Or,
+        ParallelSystemDescriptorCoercion, Parent, Plugin, Query, ReflectDefault,
+        ReflectDeserialize, ReflectSerialize, Transform, With,
+    },
+    reflect::Reflect,
+    render::view::RenderLayers,
+    transform::TransformSystem,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{Enum, Playfield, ReflectEnum};
+
+/// Render layer drawn by the playfield cameras
+pub const PLAYFIELD_RENDER_LAYER: u8 = 0;
+
+/// Render layer drawn by the background camera
+pub const BACKGROUND_RENDER_LAYER: u8 = 1;
+
+pub struct PlayfieldLayerPlugin;
+
+impl Plugin for PlayfieldLayerPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.register_type::<PlayfieldLayer>();
+
+        // Animations overwrite whole transforms during Update,
+        // so Z is reapplied each frame ahead of propagation
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            playfield_layer_z.before(TransformSystem::TransformPropagate),
+        )
+        .add_system_to_stage(CoreStage::PostUpdate, playfield_layer_render_layers);
+    }
+}
+
+/// Draw layer of a playfield entity, ordered from back to front
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Default, Enum, Component, Serialize, Deserialize)]
+pub enum PlayfieldLayer {
+    Background,
+    Enemies,
+    PlayerBullets,
+    Player,
+    Effects,
+    EnemyBullets,
+    Hud,
+}
+
+impl Default for PlayfieldLayer {
+    fn default() -> Self {
+        PlayfieldLayer::Player
+    }
+}
+
+impl Enum for PlayfieldLayer {
+    fn variants() -> &'static [&'static str] {
+        &[
+            "Background",
+            "Enemies",
+            "PlayerBullets",
+            "Player",
+            "Effects",
+            "EnemyBullets",
+            "Hud",
+        ]
+    }
+}
+
+impl PlayfieldLayer {
+    /// Z offset from the playfield plane
+    ///
+    /// The player sits on the plane itself, and enemy bullets are drawn over
+    /// everything but the HUD so they stay readable through effects.
+    pub fn z(&self) -> f32 {
+        match self {
+            PlayfieldLayer::Background => -8.0,
+            PlayfieldLayer::Enemies => -2.0,
+            PlayfieldLayer::PlayerBullets => -1.0,
+            PlayfieldLayer::Player => 0.0,
+            PlayfieldLayer::Effects => 1.0,
+            PlayfieldLayer::EnemyBullets => 2.0,
+            PlayfieldLayer::Hud => 4.0,
+        }
+    }
+
+    pub fn render_layers(&self) -> RenderLayers {
+        match self {
+            PlayfieldLayer::Background => RenderLayers::layer(BACKGROUND_RENDER_LAYER),
+            _ => RenderLayers::layer(PLAYFIELD_RENDER_LAYER),
+        }
+    }
+}
+
+/// Find the nearest layered ancestor of an entity, provided it sits under a playfield
+///
+/// Returns `Some(None)` for entities under a playfield with no layered ancestor,
+/// and `None` for entities outside of any playfield.
+fn layered_ancestor(
+    entity: Entity,
+    query_parent: &Query<&Parent>,
+    query_layer: &Query<&PlayfieldLayer>,
+    query_playfield: &Query<(), With<Playfield>>,
+) -> Option<Option<PlayfieldLayer>> {
+    let mut layer = None;
+    let mut candidate = entity;
+
+    while let Ok(parent) = query_parent.get(candidate) {
+        candidate = parent.get();
+
+        if query_playfield.get(candidate).is_ok() {
+            return Some(layer);
+        }
+
+        if layer.is_none() {
+            layer = query_layer.get(candidate).ok().copied();
+        }
+    }
+
+    None
+}
+
+/// Find the layer of the nearest layered ancestor of an entity, whether or not it's in a playfield
+fn inherited_layer(
+    entity: Entity,
+    query_parent: &Query<&Parent>,
+    query_layer: &Query<&PlayfieldLayer>,
+) -> Option<PlayfieldLayer> {
+    let mut candidate = entity;
+
+    while let Ok(parent) = query_parent.get(candidate) {
+        candidate = parent.get();
+
+        if let Ok(layer) = query_layer.get(candidate) {
+            return Some(*layer);
+        }
+    }
+
+    None
+}
+
+/// Overwrite the translation Z of layered playfield entities with their layer's offset
+pub fn playfield_layer_z(
+    mut query_layered: Query<(Entity, &PlayfieldLayer, &mut Transform)>,
+    query_parent: Query<&Parent>,
+    query_layer: Query<&PlayfieldLayer>,
+    query_playfield: Query<(), With<Playfield>>,
+) {
+    for (entity, layer, mut transform) in query_layered.iter_mut() {
+        let ancestor = if let Some(ancestor) =
+            layered_ancestor(entity, &query_parent, &query_layer, &query_playfield)
+        {
+            ancestor
+        } else {
+            continue;
+        };
+
+        let z = layer.z() - ancestor.map(|ancestor| ancestor.z()).unwrap_or_default();
+
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}
+
+fn apply_render_layers(
+    entity: Entity,
+    layer: PlayfieldLayer,
+    query_children: &Query<&Children>,
+    query_layer: &Query<&PlayfieldLayer>,
+    query_render_layers: &Query<&RenderLayers>,
+    commands: &mut Commands,
+) {
+    let render_layers = layer.render_layers();
+    if query_render_layers.get(entity).ok() != Some(&render_layers) {
+        commands.entity(entity).insert(render_layers);
+    }
+
+    if let Ok(children) = query_children.get(entity) {
+        for child in children.iter() {
+            // Nested layers take over for their own subtree
+            let layer = query_layer.get(*child).copied().unwrap_or(layer);
+
+            apply_render_layers(
+                *child,
+                layer,
+                query_children,
+                query_layer,
+                query_render_layers,
+                commands,
+            );
+        }
+    }
+}
+
+/// Propagate render layers down from layered entities whenever their layer or hierarchy changes
+///
+/// Scenes spawn their meshes some time after the entity they're attached to,
+/// so newly-parented descendants are picked up as well.
+pub fn playfield_layer_render_layers(
+    query_changed: Query<Entity, Or<(Changed<PlayfieldLayer>, Changed<Parent>)>>,
+    query_parent: Query<&Parent>,
+    query_children: Query<&Children>,
+    query_layer: Query<&PlayfieldLayer>,
+    query_render_layers: Query<&RenderLayers>,
+    mut commands: Commands,
+) {
+    for entity in query_changed.iter() {
+        let layer = if let Some(layer) = query_layer
+            .get(entity)
+            .ok()
+            .copied()
+            .or_else(|| inherited_layer(entity, &query_parent, &query_layer))
+        {
+            layer
+        } else {
+            continue;
+        };
+
+        apply_render_layers(
+            entity,
+            layer,
+            &query_children,
+            &query_layer,
+            &query_render_layers,
+            &mut commands,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYERS: [PlayfieldLayer; 7] = [
+        PlayfieldLayer::Background,
+        PlayfieldLayer::Enemies,
+        PlayfieldLayer::PlayerBullets,
+        PlayfieldLayer::Player,
+        PlayfieldLayer::Effects,
+        PlayfieldLayer::EnemyBullets,
+        PlayfieldLayer::Hud,
+    ];
+
+    #[test]
+    fn z_follows_draw_order() {
+        for pair in LAYERS.windows(2) {
+            assert!(pair[0] < pair[1]);
+            assert!(pair[0].z() < pair[1].z());
+        }
+    }
+
+    #[test]
+    fn variants_match_layers() {
+        assert_eq!(PlayfieldLayer::variants().len(), LAYERS.len());
+    }
+
+    #[test]
+    fn only_background_uses_background_render_layer() {
+        for layer in LAYERS {
+            let expected = if layer == PlayfieldLayer::Background {
+                BACKGROUND_RENDER_LAYER
+            } else {
+                PLAYFIELD_RENDER_LAYER
+            };
+
+            assert_eq!(layer.render_layers(), RenderLayers::layer(expected));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Plugin for awarding timeline-aware score to players
+
+use bevy::{
+    ecs::{reflect::ReflectComponent, system::Command},
+    prelude::{
+        default, info, BuildWorldChildren, Component, CoreStage, Entity, EventReader, Name,
+        ParallelSystemDescriptorCoercion, Parent, Plugin, Query, Res,
+    },
+    reflect::Reflect,
+};
+
+use crate::{
+    animation::{
+        adapters::{
+            evaluate::EvaluateTrait, time_source::TimeSourceTrait,
+            try_animate_components::TryAnimateComponentsTrait,
+        },
+        animations::discrete::{Discrete, DiscreteStop, DiscreteStopsTrait},
+        timeline::{Timeline, TimelineTime},
+        AnimationTagTrait, BuildAnimation, Last,
+    },
+    prelude::{timeline_damage, CoopSettings, DeathEvent, PlayerId, Sharing},
+    util::default_entity,
+};
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.register_type::<Score>()
+            .register_type::<ScoreValue>()
+            .register_type::<TimelineScore>();
+
+        app.add_system_to_stage(CoreStage::PostUpdate, score_death.after(timeline_damage));
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Component, Reflect)]
+#[reflect(Component)]
+pub struct Score {
+    pub score: usize,
+}
+
+/// Points awarded to the responsible player when this entity dies
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Component, Reflect)]
+#[reflect(Component)]
+pub struct ScoreValue {
+    pub points: usize,
+}
+
+impl Default for ScoreValue {
+    fn default() -> Self {
+        Self { points: 100 }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Component, Reflect)]
+#[reflect(Component)]
+pub struct TimelineScore {
+    pub timeline: Entity,
+    pub score_stops: Entity,
+}
+
+impl Default for TimelineScore {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            score_stops: default_entity(),
+        }
+    }
+}
+
+pub struct InsertTimelineScore {
+    pub timeline: Entity,
+    pub entity: Entity,
+}
+
+impl Default for InsertTimelineScore {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            entity: default_entity(),
+        }
+    }
+}
+
+impl Command for InsertTimelineScore {
+    fn write(self, world: &mut bevy::prelude::World) {
+        let animation_score = world.build_animation().from_discrete_stops([DiscreteStop {
+            t: 0.0,
+            value: Score::default(),
+            ..default()
+        }]);
+
+        let animation_score_stops = animation_score.id();
+
+        let animation_score = animation_score
+            .try_animating_component(self.entity)
+            .with_time_source(TimelineTime {
+                timeline: self.timeline,
+            })
+            .evaluate()
+            .tagged::<Last>()
+            .insert(Name::new(format!("Entity {:?} Score", self.entity)))
+            .id();
+
+        world
+            .entity_mut(self.entity)
+            .insert(TimelineScore {
+                timeline: self.timeline,
+                score_stops: animation_score_stops,
+            })
+            .push_children(&[animation_score]);
+    }
+}
+
+/// Award points to the player responsible for a death
+pub fn score_death(
+    coop_settings: Res<CoopSettings>,
+    mut death_events: EventReader<DeathEvent>,
+    query_score_value: Query<&ScoreValue>,
+    query_player: Query<&PlayerId>,
+    query_parent: Query<&Parent>,
+    query_score: Query<(&PlayerId, &TimelineScore)>,
+    query_timeline: Query<&Timeline>,
+    mut query_score_stops: Query<&mut Discrete<Score>>,
+) {
+    for event in death_events.iter() {
+        let score_value = if let Ok(components) = query_score_value.get(event.entity) {
+            components
+        } else {
+            continue;
+        };
+
+        let player =
+            if let Some(player) = PlayerId::of(event.source, &query_player, &query_parent) {
+                player
+            } else {
+                continue;
+            };
+
+        info!(
+            "Player {:?} scored {} points from {:?}",
+            player, score_value.points, event.entity
+        );
+
+        for (candidate, timeline_score) in query_score.iter() {
+            if coop_settings.score == Sharing::Separate && *candidate != player {
+                continue;
+            }
+
+            let timeline = query_timeline.get(timeline_score.timeline).unwrap();
+            let t = timeline.t;
+
+            let mut score_stops = query_score_stops
+                .get_mut(timeline_score.score_stops)
+                .unwrap();
+
+            let score = score_stops.stops().last().unwrap().value.score;
+
+            score_stops.insert_stop(DiscreteStop {
+                t,
+                value: Score {
+                    score: score + score_value.points,
+                },
+                determinism: event.determinism,
+                ..default()
+            });
+        }
+    }
+}
@@ -2,11 +2,14 @@
 
 use bevy::{
     ecs::reflect::ReflectComponent,
-    prelude::{Changed, Component, Input, Plugin, Query, Res},
+    prelude::{Changed, Component, Plugin, Query, Res},
     reflect::Reflect,
 };
 
-use crate::{movement::linear_move::LinearMoveInput, prelude::PlayerInput};
+use crate::{
+    movement::linear_move::LinearMoveInput,
+    prelude::{PlayerId, PlayerInput, PlayerInputs},
+};
 
 pub struct ShiftSpeedPlugin;
 
@@ -47,21 +50,17 @@ impl ShiftSpeed {
 }
 
 pub fn shift_speed_input(
-    input: Res<Input<PlayerInput>>,
-    mut query_shift_speed: Query<&mut ShiftSpeed>,
+    inputs: Res<PlayerInputs>,
+    mut query_shift_speed: Query<(&PlayerId, &mut ShiftSpeed)>,
 ) {
-    let mut shift_speed = if let Some(components) = query_shift_speed.iter_mut().next() {
-        components
-    } else {
-        return;
-    };
-
-    if input.just_pressed(PlayerInput::SlowDown) {
-        shift_speed.shift_down();
-    }
+    for (player, mut shift_speed) in query_shift_speed.iter_mut() {
+        if inputs.just_pressed(*player, PlayerInput::SlowDown) {
+            shift_speed.shift_down();
+        }
 
-    if input.just_pressed(PlayerInput::SpeedUp) {
-        shift_speed.shift_up();
+        if inputs.just_pressed(*player, PlayerInput::SpeedUp) {
+            shift_speed.shift_up();
+        }
     }
 }
 
@@ -4,8 +4,8 @@ use bevy::{
     ecs::system::Command,
     prelude::{
         default, BuildWorldChildren, Bundle, Changed, Commands, Component, Entity, Name,
-        ParallelSystemDescriptorCoercion, Plugin, Quat, Query, ResMut, Transform, Vec3, With,
-        World,
+        ParallelSystemDescriptorCoercion, Plugin, Quat, Query, ResMut, Transform, Vec2, Vec3,
+        With, World,
     },
 };
 use bevy_rapier2d::prelude::{ActiveCollisionTypes, Collider};
@@ -24,10 +24,12 @@ use crate::{
     scene::InsertSceneArchive,
     prelude::{
         default_entity, evaluate_tagged, shift_speed_input, shift_speed_linear_factor, Alive,
-        ArchiveBundle, CollisionGroup, CollisionGroupFlags, Discrete, InsertEntityPool,
-        InsertTimelineAlive, InsertTimelineDamage, InsertTimelineLives, LinearMove,
-        LinearMoveInput, LinearMoveInputMap, LinearMovePlugin, PlayerInput, ShiftSpeed,
-        SpawnBullet, Timeline, Update, Vulcan, VulcanBundle, SCENE_PLAYER_BULLET,
+        ArchiveBundle, CollisionGroupNames, Discrete, EntityPool, HitEffect, InsertEntityPool,
+        InsertTimelineAlive, InsertTimelineDamage, InsertTimelineLinearMove, InsertTimelineLives,
+        InsertTimelineScore, LinearMove, LinearMoveInput, LinearMoveInputMap, LinearMovePlugin,
+        MomentumMove, MomentumMovePlugin, ParticleEffect, ParticleEmitter, PlayerId, PlayerInput,
+        PlayfieldClamp, PlayfieldLayer, RegisterArchiveBundle, ShiftSpeed, SpawnBullet,
+        TimeAbilities, Timeline, Update, Vulcan, VulcanBundle, SCENE_PLAYER_BULLET,
     },
 };
 
@@ -46,6 +48,10 @@ impl Plugin for ShipPlugin {
             }
         }));
 
+        app.add_plugin(MomentumMovePlugin::<PlayerInput>::default());
+
+        app.register_archive_bundle::<ShipArchiveBundle>();
+
         app.add_system(shift_speed_linear_factor::<PlayerInput>.after(shift_speed_input))
             .add_system(ship_rotation.before(evaluate_tagged::<Update>))
             .add_system(ship_alive);
@@ -76,9 +82,12 @@ pub struct ShipBundle {
     #[bundle]
     hierarchy: HierarchyBundle,
     ship: Ship,
+    player: PlayerId,
     speed: ShiftSpeed,
+    playfield_clamp: PlayfieldClamp,
     #[bundle]
     body: KinematicBodyBundle,
+    collision_groups: CollisionGroupNames,
     #[bundle]
     archive: ArchiveBundle<ShipArchiveBundle>,
 }
@@ -88,25 +97,36 @@ impl Default for ShipBundle {
         Self {
             name: Name::new("Ship"),
             ship: default(),
+            player: default(),
             speed: default(),
+            playfield_clamp: PlayfieldClamp {
+                extents: Vec2::new(1.1, 0.6),
+            },
             hierarchy: HierarchyBundle {
                 transform: Transform::from_xyz(-5.0, 0.0, 0.0).into(),
                 ..default()
             },
             body: KinematicBodyBundle {
-                collision_groups: CollisionGroupFlags {
-                    memberships: CollisionGroup::SHIP_BODY,
-                    filters: CollisionGroup::STATIC,
-                },
                 active_collision_types: ActiveCollisionTypes::KINEMATIC_STATIC
                     | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
                 ..default()
             },
+            collision_groups: CollisionGroupNames::new(["SHIP_BODY"]),
             archive: default(),
         }
     }
 }
 
+impl ShipBundle {
+    /// Ship for the given player, staggered vertically so co-op ships don't spawn overlapping
+    pub fn for_player(player: PlayerId) -> Self {
+        let mut ship_bundle = ShipBundle::default();
+        ship_bundle.player = player;
+        ship_bundle.hierarchy.transform.local.translation.y -= *player as f32 * 2.0;
+        ship_bundle
+    }
+}
+
 #[derive(Bundle)]
 pub struct ShipArchiveBundle {
     linear_move: LinearMove,
@@ -126,6 +146,7 @@ impl Default for ShipArchiveBundle {
                 right: Some(PlayerInput::Right),
                 up: Some(PlayerInput::Up),
                 down: Some(PlayerInput::Down),
+                dash: Some(PlayerInput::Dash),
                 ..default()
             },
             vulcan: VulcanBundle {
@@ -152,7 +173,14 @@ where
     pub insert_timeline_damage: InsertTimelineDamage,
     pub insert_timeline_alive: InsertTimelineAlive,
     pub insert_timeline_lives: InsertTimelineLives,
+    pub insert_timeline_score: InsertTimelineScore,
     pub insert_entity_pool: InsertEntityPool<C>,
+    /// Accelerate toward player input instead of moving at a fixed speed
+    pub momentum_move: Option<MomentumMove>,
+    /// Time manipulation, targeting a timeline other than the ship's own
+    pub time_abilities: Option<TimeAbilities>,
+    /// Engine exhaust, emitted on the ship's timeline
+    pub trail: Option<ParticleEmitter>,
 }
 
 impl Default for AssembleShip<fn(&mut World, Entity, Entity)> {
@@ -172,6 +200,7 @@ impl Default for AssembleShip<fn(&mut World, Entity, Entity)> {
                 ..default()
             },
             insert_timeline_lives: default(),
+            insert_timeline_score: default(),
             insert_entity_pool: InsertEntityPool {
                 construct_instances: Some((3, |world, entity, timeline| {
                     SpawnBullet {
@@ -181,12 +210,21 @@ impl Default for AssembleShip<fn(&mut World, Entity, Entity)> {
                             path: SCENE_PLAYER_BULLET.into(),
                             ..default()
                         },
+                        playfield_layer: PlayfieldLayer::PlayerBullets,
                         ..default()
                     }
                     .write(world)
                 })),
                 ..default()
             },
+            momentum_move: None,
+            time_abilities: None,
+            trail: Some(ParticleEmitter {
+                effect: ParticleEffect::exhaust(),
+                rate: 20.0,
+                offset: Vec3::new(-0.9, 0.0, 0.0),
+                ..default()
+            }),
         }
     }
 }
@@ -205,12 +243,30 @@ where
         // Main ship entity
         let mut ship_bundle = self.ship_bundle;
         ship_bundle.archive.bundle.vulcan.vulcan.timeline = self.timeline;
+        let player = ship_bundle.player;
 
+        // Hits on ships are lethal, so their explosion stands in for sparks
         world
             .entity_mut(self.entity)
             .insert_bundle(ship_bundle)
+            .insert(PlayfieldLayer::Player)
+            .insert(HitEffect(None))
             .push_children(&[scene_entity]);
 
+        if let Some(momentum_move) = self.momentum_move {
+            world.entity_mut(self.entity).insert(momentum_move);
+        }
+
+        if let Some(mut time_abilities) = self.time_abilities {
+            time_abilities.timeline = self.timeline;
+            world.entity_mut(self.entity).insert(time_abilities);
+        }
+
+        if let Some(mut trail) = self.trail {
+            trail.timeline = self.timeline;
+            world.entity_mut(self.entity).insert(trail);
+        }
+
         let mut timeline_damage = self.insert_timeline_damage;
         timeline_damage.timeline = self.timeline;
         timeline_damage.entity = self.entity;
@@ -226,6 +282,17 @@ where
         timeline_lives.entity = self.entity;
         timeline_lives.write(world);
 
+        let mut timeline_score = self.insert_timeline_score;
+        timeline_score.timeline = self.timeline;
+        timeline_score.entity = self.entity;
+        timeline_score.write(world);
+
+        InsertTimelineLinearMove {
+            timeline: self.timeline,
+            entity: self.entity,
+        }
+        .write(world);
+
         InsertDynamicAnimation::<Transform, Quat> {
             timeline: self.timeline,
             entity: self.entity,
@@ -255,6 +322,17 @@ where
         insert_bullet_pool.entity = self.entity;
         insert_bullet_pool.timeline = self.timeline;
         insert_bullet_pool.write(world);
+
+        // Attribute bullets to the owning player
+        let bullets = world
+            .get::<EntityPool>(self.entity)
+            .unwrap()
+            .entities()
+            .collect::<Vec<_>>();
+
+        for bullet in bullets {
+            world.entity_mut(bullet).insert(player);
+        }
     }
 }
 
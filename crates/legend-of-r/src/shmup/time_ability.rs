@@ -0,0 +1,307 @@
+//! Player-controlled time manipulation
+//!
+//! Abilities act on a world timeline shared by enemies and stages,
+//! while the ship using them runs on its own timeline so its actions aren't undone.
+//! Slowing or stopping time scales the world tick rate, and rewinding scrubs it backwards,
+//! each draining a gauge that recharges while the ability is unused.
+
+use bevy::{
+    prelude::{
+        default, Component, CoreStage, Entity, ParallelSystemDescriptorCoercion, Plugin, Query,
+        Res, With,
+    },
+    time::Time,
+    utils::HashMap,
+};
+
+use crate::prelude::{default_entity, Alive, PlayerId, PlayerInput, PlayerInputs, Timeline};
+
+/// Gauge regained per second while no ability draining it is active
+pub const TIME_GAUGE_RECHARGE: f32 = 0.05;
+
+/// Limited slow motion, available from generation 3
+pub const SLOW_TIME: TimeScale = TimeScale {
+    tick_rate: 0.25,
+    drain: 0.2,
+};
+
+/// Limited time stop, available from generation 3
+pub const STOP_TIME: TimeScale = TimeScale {
+    tick_rate: 0.0,
+    drain: 0.5,
+};
+
+/// Limited rewind, available from generation 4
+pub const REWIND_TIME: TimeRewind = TimeRewind {
+    scrub_rate: -3.0,
+    drain: 0.25,
+};
+
+/// Unlimited rewind, available from generation 5
+pub const REWIND_TIME_UNLIMITED: TimeRewind = TimeRewind {
+    scrub_rate: -3.0,
+    drain: 0.0,
+};
+
+pub struct TimeAbilityPlugin;
+
+impl Plugin for TimeAbilityPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        // Runs after the timeline UI has written its scrub rate for the frame
+        app.add_system_to_stage(CoreStage::Update, time_abilities)
+            .add_system_to_stage(CoreStage::Update, time_controlled.after(time_abilities));
+    }
+}
+
+/// Scales the tick rate of the target timeline while held
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimeScale {
+    /// Multiplier applied to the target timeline's tick rate
+    pub tick_rate: f64,
+    /// Gauge spent per second of use
+    pub drain: f32,
+}
+
+/// Scrubs the target timeline backwards while held
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimeRewind {
+    /// Rate added to the target timeline's tick rate
+    pub scrub_rate: f64,
+    /// Gauge spent per second of use, or 0 for unlimited rewind
+    pub drain: f32,
+}
+
+/// Time abilities available to a player ship
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct TimeAbilities {
+    /// Timeline the ship itself runs on, which keeps playing during abilities
+    pub timeline: Entity,
+    /// World timeline acted upon by abilities
+    pub target: Entity,
+    pub slow: Option<TimeScale>,
+    pub stop: Option<TimeScale>,
+    pub rewind: Option<TimeRewind>,
+    /// Gauge shared by slow and stop, from empty at 0 to full at 1
+    pub scale_gauge: f32,
+    /// Gauge spent by rewinding, from empty at 0 to full at 1
+    pub rewind_gauge: f32,
+}
+
+impl Default for TimeAbilities {
+    fn default() -> Self {
+        Self {
+            timeline: default_entity(),
+            target: default_entity(),
+            slow: default(),
+            stop: default(),
+            rewind: default(),
+            scale_gauge: 1.0,
+            rewind_gauge: 1.0,
+        }
+    }
+}
+
+impl TimeAbilities {
+    pub fn any(&self) -> bool {
+        self.slow.is_some() || self.stop.is_some() || self.rewind.is_some()
+    }
+}
+
+/// Marks a timeline that time abilities can act upon
+#[derive(Debug, Copy, Clone, PartialEq, Component)]
+pub struct TimeControlled {
+    /// Unscaled tick rate, captured when a slow or stop begins and restored once it ends
+    pub tick_rate: f64,
+    /// Tick rate multiplier requested this frame
+    pub scale: f64,
+    /// Rewind scrub rate requested this frame
+    pub rewind: Option<f64>,
+    scaling: bool,
+    rewinding: bool,
+}
+
+impl Default for TimeControlled {
+    fn default() -> Self {
+        Self {
+            tick_rate: 1.0,
+            scale: 1.0,
+            rewind: None,
+            scaling: false,
+            rewinding: false,
+        }
+    }
+}
+
+fn drain(gauge: &mut f32, rate: f32, dt: f32) -> bool {
+    if *gauge <= 0.0 {
+        return false;
+    }
+
+    *gauge = (*gauge - rate * dt).max(0.0);
+    true
+}
+
+/// Read ability inputs, spend gauges and request effects from the targeted timelines
+pub fn time_abilities(
+    time: Res<Time>,
+    inputs: Res<PlayerInputs>,
+    mut query_abilities: Query<(&PlayerId, Option<&Alive>, &mut TimeAbilities)>,
+    query_timeline: Query<&Timeline>,
+    mut query_controlled: Query<&mut TimeControlled, With<Timeline>>,
+) {
+    let mut requests = HashMap::<Entity, (f64, Option<f64>)>::default();
+
+    for (player, alive, mut abilities) in query_abilities.iter_mut() {
+        if !abilities.any() {
+            continue;
+        }
+
+        let timeline = if let Ok(timeline) = query_timeline.get(abilities.timeline) {
+            timeline
+        } else {
+            continue;
+        };
+
+        if timeline.paused {
+            continue;
+        }
+
+        let dt = time.delta_seconds() * timeline.tick_rate as f32;
+        let (mut scale, mut rewind) = (1.0, None);
+
+        // Abilities can't be used while waiting to respawn
+        let alive = alive.map(|alive| alive.0).unwrap_or(true);
+        let held = |input| alive && inputs.pressed(*player, input);
+
+        // Stop takes priority over slow when both are held
+        let scale_ability = [
+            (PlayerInput::StopTime, abilities.stop),
+            (PlayerInput::SlowTime, abilities.slow),
+        ]
+        .into_iter()
+        .find_map(|(input, ability)| ability.filter(|_| held(input)));
+
+        if let Some(ability) = scale_ability {
+            if drain(&mut abilities.scale_gauge, ability.drain, dt) {
+                scale = ability.tick_rate;
+            }
+        } else if abilities.scale_gauge < 1.0 {
+            abilities.scale_gauge = (abilities.scale_gauge + TIME_GAUGE_RECHARGE * dt).min(1.0);
+        }
+
+        match abilities.rewind {
+            Some(ability) if held(PlayerInput::RewindTime) => {
+                if drain(&mut abilities.rewind_gauge, ability.drain, dt) {
+                    rewind = Some(ability.scrub_rate);
+                }
+            }
+            _ if abilities.rewind_gauge < 1.0 => {
+                abilities.rewind_gauge =
+                    (abilities.rewind_gauge + TIME_GAUGE_RECHARGE * dt).min(1.0);
+            }
+            _ => (),
+        }
+
+        // Slowest scale and fastest rewind win when several players use abilities at once
+        let request = requests.entry(abilities.target).or_insert((1.0, None));
+        request.0 = f64::min(request.0, scale);
+        request.1 = match (request.1, rewind) {
+            (Some(a), Some(b)) => Some(f64::min(a, b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    for (target, (scale, rewind)) in requests {
+        let mut controlled = if let Ok(controlled) = query_controlled.get_mut(target) {
+            controlled
+        } else {
+            continue;
+        };
+
+        if controlled.scale != scale || controlled.rewind != rewind {
+            controlled.scale = scale;
+            controlled.rewind = rewind;
+        }
+    }
+}
+
+/// Apply requested abilities to controlled timelines
+pub fn time_controlled(mut query: Query<(&mut TimeControlled, &mut Timeline)>) {
+    for (mut controlled, mut timeline) in query.iter_mut() {
+        // Only touch the tick rate while scaling, so as not to fight the timeline UI
+        if controlled.scale != 1.0 {
+            if !controlled.scaling {
+                controlled.tick_rate = timeline.tick_rate;
+                controlled.scaling = true;
+            }
+
+            let tick_rate = controlled.tick_rate * controlled.scale;
+            if timeline.tick_rate != tick_rate {
+                timeline.tick_rate = tick_rate;
+            }
+        } else if controlled.scaling {
+            timeline.tick_rate = controlled.tick_rate;
+            controlled.scaling = false;
+        }
+
+        match controlled.rewind {
+            Some(rewind) => {
+                timeline.scrub_rate = rewind;
+                controlled.rewinding = true;
+            }
+            // Likewise, only clear a scrub rate this set
+            None if controlled.rewinding => {
+                timeline.scrub_rate = 0.0;
+                controlled.rewinding = false;
+            }
+            None => (),
+        }
+
+        // Requests are renewed each frame by time_abilities
+        if controlled.scale != 1.0 || controlled.rewind.is_some() {
+            controlled.scale = 1.0;
+            controlled.rewind = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{Stage, SystemStage, World};
+
+    use super::*;
+
+    #[test]
+    fn drain_stops_at_empty() {
+        let mut gauge = 0.1;
+        assert!(drain(&mut gauge, 1.0, 0.5));
+        assert_eq!(gauge, 0.0);
+        assert!(!drain(&mut gauge, 1.0, 0.5));
+    }
+
+    #[test]
+    fn time_controlled_restores_tick_rate() {
+        let mut world = World::default();
+        let mut stage = SystemStage::single(time_controlled);
+
+        let timeline = world
+            .spawn()
+            .insert(TimeControlled::default())
+            .insert(Timeline {
+                tick_rate: 2.0,
+                ..default()
+            })
+            .id();
+
+        // Leaves externally set rates alone while idle
+        stage.run(&mut world);
+        assert_eq!(world.get::<Timeline>(timeline).unwrap().tick_rate, 2.0);
+
+        world.get_mut::<TimeControlled>(timeline).unwrap().scale = 0.25;
+        stage.run(&mut world);
+        assert_eq!(world.get::<Timeline>(timeline).unwrap().tick_rate, 0.5);
+
+        stage.run(&mut world);
+        assert_eq!(world.get::<Timeline>(timeline).unwrap().tick_rate, 2.0);
+    }
+}
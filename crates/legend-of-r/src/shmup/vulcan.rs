@@ -4,7 +4,7 @@ use bevy::{
     ecs::system::Command,
     prelude::{
         default, BuildWorldChildren, Bundle, Commands, Component, CoreStage, Deref, DerefMut,
-        Entity, Input, Name, ParallelSystemDescriptorCoercion, Plugin, Query, ReflectComponent,
+        Entity, Name, ParallelSystemDescriptorCoercion, Plugin, Query, ReflectComponent,
         Res, ResMut, Transform, Vec3, With, World,
     },
     reflect::Reflect,
@@ -19,9 +19,9 @@ use crate::{
     prelude::{
         default_entity, evaluate_tagged, hitbox_collision, Alive, AnimateComponentsTrait,
         AnimationTagTrait, BuildAnimation, Discrete, DiscreteStop, DiscreteStopsTrait, EntityPool,
-        EvaluateTrait, InsertTimelineAlive, InsertTimelineDamage, InterpolateTrait, PlayerInput,
-        TimeSourceTrait, Timeline, TimelineAlive, TimelineTime, TryAnimateComponentFieldsTrait,
-        UnpoolEntity, Update,
+        EvaluateTrait, InsertTimelineAlive, InsertTimelineDamage, InterpolateTrait, PlayerId,
+        PlayerInput, PlayerInputs, PlayfieldLayer, TimeSourceTrait, Timeline, TimelineAlive,
+        TimelineTime, TryAnimateComponentFieldsTrait, UnpoolEntity, Update,
     },
 };
 
@@ -99,6 +99,7 @@ pub struct SpawnBullet {
     pub bullet_animation: InsertBulletAnimation,
     pub timeline_damage: InsertTimelineDamage,
     pub timeline_alive: InsertTimelineAlive,
+    pub playfield_layer: PlayfieldLayer,
 }
 
 impl Default for SpawnBullet {
@@ -110,12 +111,15 @@ impl Default for SpawnBullet {
             bullet_animation: default(),
             timeline_damage: default(),
             timeline_alive: default(),
+            playfield_layer: PlayfieldLayer::EnemyBullets,
         }
     }
 }
 
 impl Command for SpawnBullet {
     fn write(self, world: &mut bevy::prelude::World) {
+        world.entity_mut(self.entity).insert(self.playfield_layer);
+
         let mut timeline_alive = self.timeline_alive;
         timeline_alive.timeline = self.timeline;
         timeline_alive.entity = self.entity;
@@ -138,6 +142,20 @@ impl Command for SpawnBullet {
     }
 }
 
+/// Pooled instance constructor for enemy bullets
+pub fn construct_enemy_bullet(world: &mut World, entity: Entity, timeline: Entity) {
+    SpawnBullet {
+        timeline,
+        entity,
+        scene: InsertSceneArchive {
+            path: SCENE_ENEMY_BULLET.into(),
+            ..default()
+        },
+        ..default()
+    }
+    .write(world)
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct InsertBulletAnimation {
     pub entity: Entity,
@@ -255,21 +273,21 @@ impl Command for InsertBulletAnimation {
 
 pub fn vulcan_fire(
     time: Res<Time>,
-    input: Res<Input<PlayerInput>>,
+    inputs: Res<PlayerInputs>,
     mut query_vulcan: Query<
-        (Entity, &Vulcan, &mut VulcanTimer),
+        (Entity, &PlayerId, &Vulcan, &mut VulcanTimer),
         (With<Transform>, With<EntityPool>),
     >,
     query_timeline: Query<&Timeline>,
     mut commands: Commands,
 ) {
-    for (vulcan_entity, vulcan, mut vulcan_timer) in query_vulcan.iter_mut() {
+    for (vulcan_entity, player, vulcan, mut vulcan_timer) in query_vulcan.iter_mut() {
         vulcan_timer.tick(time.delta());
 
         let timeline = query_timeline.get(vulcan.timeline).unwrap();
 
         if vulcan_timer.finished() {
-            if input.pressed(PlayerInput::Fire) {
+            if inputs.pressed(*player, PlayerInput::Fire) {
                 commands.add(UnpoolEntity {
                     source: vulcan_entity,
                     entity_pool: vulcan_entity,
@@ -0,0 +1,574 @@
+//! Headless simulation mode for catching gameplay regressions without a window or GPU
+//!
+//! Runs the game on a fixed timestep against a scripted input file,
+//! then dumps the final lives, hit points, score and position of gameplay entities as JSON.
+//!
+//! Expects TimePlugin to be disabled, as Time is advanced by the simulated clock.
+//! The clock is held until scenes and stages have loaded, so timelines start from zero
+//! regardless of how long loading takes.
+//!
+//! Script times and the run duration are measured on the timeline marked SimulationTimeline.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context};
+use bevy::{
+    asset::LoadState,
+    prelude::{
+        default, error, info, warn, App, AssetServer, Component, CoreStage, Entity,
+        GlobalTransform, IntoExclusiveSystem, Local, Name, Or, ParallelSystemDescriptorCoercion,
+        Plugin, Query, Res, ResMut, With, World,
+    },
+    time::Time,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{
+    ArchiveScene, AspectRatio, GltfScenesReady, HitPoints, Lives, PendingArchiveScene,
+    PendingScene, PlayerId, PlayerInput, PlayerInputLabel, PlayerInputMaps, PlayerInputs, Score,
+    StageInstance, Timeline,
+};
+
+/// Default simulation timestep in seconds
+pub const SIMULATION_STEP: f64 = 1.0 / 60.0;
+
+/// Wall-clock time to wait for scenes and stages to load before giving up
+pub const SIMULATION_LOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Consecutive frames that loading must be complete for,
+/// so spawns triggered by a freshly-loaded asset are waited on too
+pub const SIMULATION_READY_FRAMES: usize = 2;
+
+/// Command-line configuration for a headless run
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationArgs {
+    /// Timeline seconds to simulate before dumping state
+    pub seconds: f64,
+    pub step: f64,
+    pub script: Option<PathBuf>,
+    /// File to write the dump to, or None to print it to stdout
+    pub output: Option<PathBuf>,
+}
+
+impl Default for SimulationArgs {
+    fn default() -> Self {
+        Self {
+            seconds: 10.0,
+            step: SIMULATION_STEP,
+            script: None,
+            output: None,
+        }
+    }
+}
+
+impl SimulationArgs {
+    /// Parse `<seconds> [--script <path>] [--output <path>] [--step <seconds>]`
+    pub fn parse(args: &[String]) -> anyhow::Result<Self> {
+        let mut args = args.iter();
+
+        let seconds = args
+            .next()
+            .ok_or_else(|| anyhow!("Missing simulation length in seconds"))?;
+
+        let mut simulation = SimulationArgs {
+            seconds: seconds
+                .parse()
+                .with_context(|| format!("Invalid simulation length {seconds:?}"))?,
+            ..default()
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("Missing value for {arg}"))
+            };
+
+            match arg.as_str() {
+                "--script" => simulation.script = Some(value()?.into()),
+                "--output" => simulation.output = Some(value()?.into()),
+                "--step" => {
+                    let step = value()?;
+                    simulation.step = step
+                        .parse()
+                        .with_context(|| format!("Invalid timestep {step:?}"))?;
+                }
+                _ => return Err(anyhow!("Unknown simulation argument {arg}")),
+            }
+        }
+
+        if simulation.step <= 0.0 {
+            return Err(anyhow!("Timestep must be positive"));
+        }
+
+        Ok(simulation)
+    }
+}
+
+/// A single press or release of a player input at a given timeline time
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptedInput {
+    pub t: f64,
+    #[serde(default)]
+    pub player: PlayerId,
+    pub input: PlayerInput,
+    pub pressed: bool,
+}
+
+/// Sequence of inputs fed to the simulation in place of physical devices
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationScript {
+    pub inputs: Vec<ScriptedInput>,
+}
+
+impl SimulationScript {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let script = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read simulation script {path:?}"))?;
+
+        let mut script: SimulationScript = ron::from_str(&script)
+            .with_context(|| format!("Failed to parse simulation script {path:?}"))?;
+
+        script
+            .inputs
+            .sort_by(|lhs, rhs| lhs.t.partial_cmp(&rhs.t).unwrap());
+
+        Ok(script)
+    }
+}
+
+/// Marks the world timeline that scripted inputs and the run duration are measured against
+///
+/// Other timelines, such as the player timeline, keep running at full speed
+/// while time abilities slow or rewind the world.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Component)]
+pub struct SimulationTimeline;
+
+/// Fixed-step clock that drives Time during a simulation
+#[derive(Debug, Copy, Clone)]
+pub struct SimulationClock {
+    pub instant: Instant,
+    pub step: Duration,
+    /// Whether the clock advances each frame, false while waiting on assets
+    pub running: bool,
+}
+
+pub struct SimulationPlugin {
+    pub args: SimulationArgs,
+    pub script: SimulationScript,
+}
+
+impl SimulationPlugin {
+    pub fn from_args(args: &[String]) -> anyhow::Result<Self> {
+        let args = SimulationArgs::parse(args)?;
+
+        let script = match &args.script {
+            Some(path) => SimulationScript::load(path)?,
+            None => default(),
+        };
+
+        Ok(SimulationPlugin { args, script })
+    }
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        // Normally provided by UserInterfacePlugin, read by the playfield and camera systems
+        app.init_resource::<AspectRatio>();
+
+        // Without bindings, the absent keyboard and gamepads can't release scripted input
+        for map in app
+            .world
+            .get_resource_or_insert_with(PlayerInputMaps::default)
+            .maps
+            .iter_mut()
+        {
+            map.bindings = default();
+        }
+
+        app.insert_resource(self.args.clone())
+            .insert_resource(self.script.clone());
+
+        // Normally provided by TimePlugin
+        app.init_resource::<Time>();
+        let startup = app.world.resource::<Time>().startup();
+
+        app.insert_resource(SimulationClock {
+            instant: startup,
+            step: Duration::from_secs_f64(self.args.step),
+            running: false,
+        });
+
+        // Ahead of the parallel First systems that advance timelines
+        app.add_system_to_stage(
+            CoreStage::First,
+            simulation_time.exclusive_system().at_start(),
+        );
+
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            scripted_input.after(PlayerInputLabel),
+        );
+
+        app.set_runner(simulation_runner);
+    }
+}
+
+/// Advance Time by one fixed step, if the clock is running
+pub fn simulation_time(mut clock: ResMut<SimulationClock>, mut time: ResMut<Time>) {
+    if clock.running {
+        let step = clock.step;
+        clock.instant += step;
+    }
+
+    time.update_with_instant(clock.instant);
+}
+
+/// Apply scripted inputs whose time has been reached on the simulation timeline
+pub fn scripted_input(
+    script: Res<SimulationScript>,
+    query_timeline: Query<&Timeline, With<SimulationTimeline>>,
+    mut cursor: Local<usize>,
+    mut player_inputs: ResMut<PlayerInputs>,
+) {
+    let t = if let Ok(timeline) = query_timeline.get_single() {
+        timeline.t
+    } else {
+        return;
+    };
+
+    for scripted in script.inputs[*cursor..]
+        .iter()
+        .take_while(|scripted| scripted.t <= t)
+    {
+        let input = player_inputs.get_mut(scripted.player);
+        if scripted.pressed {
+            input.press(scripted.input);
+        } else {
+            input.release(scripted.input);
+        }
+
+        *cursor += 1;
+    }
+}
+
+/// App runner that steps the simulation at a fixed rate until the requested timeline time
+pub fn simulation_runner(mut app: App) {
+    let args = app.world.resource::<SimulationArgs>().clone();
+
+    info!("Waiting for scenes and stages to load");
+
+    let loading = Instant::now();
+    let mut ready_frames = 0;
+
+    while ready_frames < SIMULATION_READY_FRAMES {
+        app.update();
+
+        if simulation_ready(&mut app.world) {
+            ready_frames += 1;
+        } else {
+            ready_frames = 0;
+        }
+
+        if loading.elapsed() >= SIMULATION_LOAD_TIMEOUT {
+            error!("Timed out waiting for scenes and stages to load");
+            std::process::exit(1);
+        }
+
+        // Give the asset server's IO tasks a chance to make progress
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    app.world.resource_mut::<SimulationClock>().running = true;
+
+    // Guard against runs that never reach the target, such as a paused timeline
+    let max_frames = (args.seconds / args.step).ceil() as usize * 4 + 1;
+    let mut frames = 0;
+
+    info!("Simulating {} seconds at {}s per frame", args.seconds, args.step);
+
+    loop {
+        app.update();
+        frames += 1;
+
+        if timeline_t(&mut app.world) >= args.seconds {
+            break;
+        }
+
+        if frames >= max_frames {
+            warn!("Simulation stopped after {frames} frames without reaching the target time");
+            break;
+        }
+    }
+
+    let dump = SimulationDump::from_world(&mut app.world, frames);
+    let json = serde_json::to_string_pretty(&dump).unwrap();
+
+    match &args.output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, json) {
+                error!("Failed to write simulation dump to {path:?}: {e}");
+                std::process::exit(1);
+            }
+            info!("Wrote simulation dump to {path:?}");
+        }
+        None => println!("{json}"),
+    }
+}
+
+/// Returns true once every scene and stage in the world can be spawned in full
+fn simulation_ready(world: &mut World) -> bool {
+    let pending = world
+        .query_filtered::<Entity, Or<(With<PendingScene>, With<PendingArchiveScene>)>>()
+        .iter(world)
+        .next()
+        .is_some();
+
+    if pending {
+        return false;
+    }
+
+    let scenes = world
+        .query::<&ArchiveScene>()
+        .iter(world)
+        .map(|scene| scene.0.clone_weak())
+        .collect::<Vec<_>>();
+
+    let stages = world
+        .query::<&StageInstance>()
+        .iter(world)
+        .map(|instance| instance.stage.clone_weak())
+        .collect::<Vec<_>>();
+
+    let asset_server = world.resource::<AssetServer>();
+    let scenes_ready = world.resource::<GltfScenesReady>();
+
+    scenes
+        .iter()
+        .all(|scene| scenes_ready.is_ready(asset_server, scene))
+        && stages.iter().all(|stage| {
+            matches!(
+                asset_server.get_load_state(stage),
+                LoadState::Loaded | LoadState::Failed
+            )
+        })
+}
+
+fn timeline_t(world: &mut World) -> f64 {
+    world
+        .query_filtered::<&Timeline, With<SimulationTimeline>>()
+        .get_single(world)
+        .map(|timeline| timeline.t)
+        .unwrap_or_default()
+}
+
+/// Final state of a single gameplay entity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityDump {
+    pub entity: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub player: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lives: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_points: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<usize>,
+    pub translation: [f32; 3],
+}
+
+/// Final state of a simulation run
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationDump {
+    pub t: f64,
+    pub frames: usize,
+    pub entities: Vec<EntityDump>,
+}
+
+impl SimulationDump {
+    pub fn from_world(world: &mut World, frames: usize) -> Self {
+        let t = timeline_t(world);
+
+        let mut entities = world
+            .query::<(
+                Entity,
+                Option<&Name>,
+                Option<&PlayerId>,
+                Option<&Lives>,
+                Option<&HitPoints>,
+                Option<&Score>,
+                Option<&GlobalTransform>,
+            )>()
+            .iter(world)
+            .filter(|(_, _, player, lives, hit_points, score, _)| {
+                player.is_some() || lives.is_some() || hit_points.is_some() || score.is_some()
+            })
+            .map(
+                |(entity, name, player, lives, hit_points, score, transform)| EntityDump {
+                    entity: entity.id(),
+                    name: name.map(ToString::to_string),
+                    player: player.map(|player| player.0),
+                    lives: lives.map(|lives| lives.lives),
+                    hit_points: hit_points.map(|hit_points| hit_points.0),
+                    score: score.map(|score| score.score),
+                    translation: transform
+                        .map(|transform| transform.translation().to_array())
+                        .unwrap_or_default(),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        entities.sort_by(|lhs, rhs| (&lhs.name, lhs.entity).cmp(&(&rhs.name, rhs.entity)));
+
+        SimulationDump {
+            t,
+            frames,
+            entities,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{Stage, SystemStage};
+
+    use crate::prelude::{
+        time_abilities, time_controlled, timeline, TimeAbilities, TimeControlled, SLOW_TIME,
+    };
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn parse_seconds_only() {
+        assert_eq!(
+            SimulationArgs::parse(&args(&["5"])).unwrap(),
+            SimulationArgs {
+                seconds: 5.0,
+                ..default()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_all_options() {
+        let parsed = SimulationArgs::parse(&args(&[
+            "2.5",
+            "--script",
+            "script.ron",
+            "--output",
+            "dump.json",
+            "--step",
+            "0.5",
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            parsed,
+            SimulationArgs {
+                seconds: 2.5,
+                step: 0.5,
+                script: Some("script.ron".into()),
+                output: Some("dump.json".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_args() {
+        assert!(SimulationArgs::parse(&args(&[])).is_err());
+        assert!(SimulationArgs::parse(&args(&["soon"])).is_err());
+        assert!(SimulationArgs::parse(&args(&["5", "--script"])).is_err());
+        assert!(SimulationArgs::parse(&args(&["5", "--step", "0"])).is_err());
+        assert!(SimulationArgs::parse(&args(&["5", "--unknown"])).is_err());
+    }
+
+    #[test]
+    fn script_follows_simulation_timeline_while_slowed() {
+        let mut world = World::default();
+
+        let time = Time::default();
+        world.insert_resource(SimulationClock {
+            instant: time.startup(),
+            step: Duration::from_secs_f64(0.1),
+            running: true,
+        });
+        world.insert_resource(time);
+        world.init_resource::<PlayerInputs>();
+
+        // Hold slow time until one second has passed on the world timeline
+        world.insert_resource(SimulationScript {
+            inputs: vec![
+                ScriptedInput {
+                    t: 0.0,
+                    player: PlayerId(0),
+                    input: PlayerInput::SlowTime,
+                    pressed: true,
+                },
+                ScriptedInput {
+                    t: 1.0,
+                    player: PlayerId(0),
+                    input: PlayerInput::SlowTime,
+                    pressed: false,
+                },
+            ],
+        });
+
+        // Spawned first, so it isn't picked up by accident
+        let player_timeline = world.spawn().insert(Timeline::default()).id();
+
+        let world_timeline = world
+            .spawn()
+            .insert(Timeline::default())
+            .insert(TimeControlled::default())
+            .insert(SimulationTimeline)
+            .id();
+
+        world.spawn().insert(PlayerId(0)).insert(TimeAbilities {
+            timeline: player_timeline,
+            target: world_timeline,
+            slow: Some(SLOW_TIME),
+            ..default()
+        });
+
+        let mut stage = SystemStage::single_threaded()
+            .with_system(simulation_time)
+            .with_system(timeline.after(simulation_time))
+            .with_system(scripted_input.after(timeline))
+            .with_system(time_abilities.after(scripted_input))
+            .with_system(time_controlled.after(time_abilities));
+
+        let t = |world: &World, entity| world.get::<Timeline>(entity).unwrap().t;
+        let slowed = |world: &World| {
+            world
+                .resource::<PlayerInputs>()
+                .pressed(PlayerId(0), PlayerInput::SlowTime)
+        };
+
+        while t(&world, player_timeline) < 1.5 {
+            stage.run(&mut world);
+        }
+
+        // The player timeline is past the release time, but the slowed world timeline isn't
+        assert!(t(&world, world_timeline) < 1.0);
+        assert!(slowed(&world));
+        assert_eq!(timeline_t(&mut world), t(&world, world_timeline));
+
+        while t(&world, world_timeline) < 1.0 {
+            stage.run(&mut world);
+        }
+
+        assert!(!slowed(&world));
+        assert_eq!(
+            world.get::<Timeline>(world_timeline).unwrap().tick_rate,
+            1.0
+        );
+    }
+}
@@ -1,25 +1,48 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use bevy::{
     diagnostic::FrameTimeDiagnosticsPlugin,
-    prelude::{default, Deref, DerefMut, IntoExclusiveSystem, Plugin, Res, World},
+    prelude::{default, warn, Deref, DerefMut, IntoExclusiveSystem, Plugin, Res, World},
 };
 use bevy_egui::EguiContext;
 use bevy_framepace::FramePaceDiagnosticsPlugin;
 use bevy_inspector_egui::{
     egui::{
-        panel::{Side, TopBottomSide},
-        Align, CentralPanel, Color32, Context, Frame, Layout, Pos2, Rect, Response, RichText,
-        ScrollArea, Ui, Widget as EguiWidget,
+        Align, CollapsingHeader, Context, Id, Layout, Pos2, Response, RichText, ScrollArea,
+        SidePanel, TopBottomPanel, Ui, Widget as EguiWidget, Window,
     },
     world_inspector::WorldUIContext,
     InspectableRegistry, WorldInspectorParams,
 };
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{EguiDiagnosticLabel, EguiDiagnosticPlot, HudState};
+
+pub const DOCK_LAYOUT_FILE: &str = "layout.ron";
+
+/// Smallest default size for a docked panel, used when the letterbox area is too thin
+pub const DOCK_MIN_SIZE: f32 = 200.0;
 
-use crate::prelude::{EguiDiagnosticLabel, EguiDiagnosticPlot};
+/// Offset between the default positions of successive floating widgets
+pub const DOCK_FLOATING_STEP: f32 = 32.0;
 
-pub struct UserInterfacePlugin;
+pub struct UserInterfacePlugin {
+    pub layout_path: PathBuf,
+}
+
+impl Default for UserInterfacePlugin {
+    fn default() -> Self {
+        Self {
+            layout_path: dock_layout_path(),
+        }
+    }
+}
 
 impl Plugin for UserInterfacePlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
@@ -27,11 +50,110 @@ impl Plugin for UserInterfacePlugin {
         app.init_resource::<InspectableRegistry>();
         app.init_resource::<Widgets>();
         app.init_resource::<AspectRatio>();
+        app.insert_resource(DockLayout::load(&self.layout_path).unwrap_or_default());
+        app.insert_resource(DockLayoutPath(self.layout_path.clone()));
         app.add_system(user_interface.exclusive_system());
         app.add_startup_system(setup);
     }
 }
 
+/// Location that the dock layout is loaded from and saved to
+#[derive(Debug, Clone)]
+pub struct DockLayoutPath(pub PathBuf);
+
+/// Path to the layout file next to the running executable
+pub fn dock_layout_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+        .join(DOCK_LAYOUT_FILE)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DockPosition {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Floating,
+}
+
+impl DockPosition {
+    pub const ALL: [DockPosition; 5] = [
+        DockPosition::Left,
+        DockPosition::Right,
+        DockPosition::Top,
+        DockPosition::Bottom,
+        DockPosition::Floating,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DockPosition::Left => "Dock Left",
+            DockPosition::Right => "Dock Right",
+            DockPosition::Top => "Dock Top",
+            DockPosition::Bottom => "Dock Bottom",
+            DockPosition::Floating => "Float",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DockedWidget {
+    pub position: DockPosition,
+    pub collapsed: bool,
+    pub floating_pos: (f32, f32),
+}
+
+impl Default for DockedWidget {
+    fn default() -> Self {
+        Self {
+            position: DockPosition::Floating,
+            collapsed: false,
+            floating_pos: (64.0, 64.0),
+        }
+    }
+}
+
+/// Placement of each widget, keyed by title, along with the size of each docked panel
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DockLayout {
+    pub widgets: BTreeMap<String, DockedWidget>,
+    pub sizes: BTreeMap<DockPosition, f32>,
+    #[serde(skip)]
+    pub dirty: bool,
+}
+
+impl DockLayout {
+    pub fn load(path: &Path) -> Option<Self> {
+        let layout = std::fs::read_to_string(path).ok()?;
+        match ron::from_str(&layout) {
+            Ok(layout) => Some(layout),
+            Err(e) => {
+                warn!("Failed to parse dock layout {path:?}: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let layout = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, layout)?;
+        Ok(())
+    }
+
+    /// Layout key for a widget, falling back to its registration slot if it has no title
+    pub fn key(widget: &WidgetVariant, list: &str, index: usize) -> String {
+        let title = widget.title();
+        if title.is_empty() {
+            format!("{list:}#{index:}")
+        } else {
+            title.into_owned()
+        }
+    }
+}
+
 pub enum WidgetVariant {
     WorldInspector,
     Owned(Box<dyn Widget>),
@@ -128,6 +250,10 @@ impl Default for AspectRatio {
     }
 }
 
+/// Widgets registered against each letterbox area
+///
+/// Their initial dock position is derived from the area and window aspect ratio,
+/// after which placement is driven by DockLayout
 #[derive(Clone, Default)]
 pub struct Widgets {
     pub panel_outer_min: WidgetList,
@@ -137,6 +263,7 @@ pub struct Widgets {
 pub fn user_interface(world: &mut World) {
     let widgets = world.resource::<Widgets>().clone();
     let base_aspect = world.resource::<AspectRatio>().clone();
+    let path = world.resource::<DockLayoutPath>().0.clone();
 
     let world_ptr: *mut _ = world;
 
@@ -144,71 +271,223 @@ pub fn user_interface(world: &mut World) {
     let mut ctx = world.resource_mut::<EguiContext>();
     let ctx = ctx.ctx_mut();
 
-    let style_inner = (*ctx.style()).clone();
-
-    let mut style_panel = style_inner.clone();
-    style_panel.visuals.widgets.noninteractive.bg_fill = Color32::TRANSPARENT;
-
     let world: &mut World = unsafe { &mut *world_ptr };
     let mut params = world.resource_mut::<WorldInspectorParams>();
 
     let world: &mut World = unsafe { &mut *world_ptr };
+    let mut layout = world.resource_mut::<DockLayout>();
 
+    let world: &mut World = unsafe { &mut *world_ptr };
+
+    // Widgets without a saved layout default to the letterbox area they were registered against,
+    // unless the HUD occupies it, in which case they float over the playfield instead
     let rect = ctx.available_rect();
     let aspect = rect.aspect_ratio();
-    let (position_min, position_max) = if aspect > *base_aspect {
-        (PanelPosition::left(), PanelPosition::right())
-    } else if aspect < *base_aspect {
-        (PanelPosition::top(), PanelPosition::bottom())
+    let (position_min, position_max) = if world.contains_resource::<HudState>() {
+        (DockPosition::Floating, DockPosition::Floating)
+    } else if aspect >= *base_aspect {
+        (DockPosition::Left, DockPosition::Right)
     } else {
-        return;
+        (DockPosition::Top, DockPosition::Bottom)
     };
 
-    CentralPanel::default()
-        .frame(Frame::default())
-        .show(ctx, |ui| {
-            UserInterfacePanel::new(position_min, *base_aspect).show(ui, |ui| {
-                let mut widgets = widgets.panel_outer_min.lock();
-                let last_idx = widgets.len() - 1;
-
-                for (widget, is_last) in widgets
-                    .iter_mut()
-                    .enumerate()
-                    .map(|(i, widget)| (widget, i == last_idx))
-                {
-                    ui.vertical(|ui| {
-                        ui.heading(RichText::new(widget.title()));
-                        widget.update(world);
-                        widget.ui(world, ctx, &mut params, ui);
-                    });
+    let letterbox_width = {
+        let half_width = rect.width() * 0.5;
+        let fac = rect.width() / (rect.height() * *base_aspect);
+        half_width - half_width / fac
+    };
 
-                    if !is_last {
-                        ui.separator();
-                    }
-                }
+    let letterbox_height = {
+        let half_height = rect.height() * 0.5;
+        let fac = rect.height() / (rect.width() / *base_aspect);
+        half_height - half_height / fac
+    };
+
+    let mut widgets_min = widgets.panel_outer_min.lock();
+    let mut widgets_max = widgets.panel_outer_max.lock();
+
+    let mut entries = widgets_min
+        .iter_mut()
+        .enumerate()
+        .map(|(i, widget)| (DockLayout::key(widget, "min", i), position_min, widget))
+        .chain(
+            widgets_max
+                .iter_mut()
+                .enumerate()
+                .map(|(i, widget)| (DockLayout::key(widget, "max", i), position_max, widget)),
+        )
+        .collect::<Vec<_>>();
+
+    for (i, (key, position, widget)) in entries.iter_mut().enumerate() {
+        layout.widgets.entry(key.clone()).or_insert_with(|| {
+            let (x, y) = DockedWidget::default().floating_pos;
+            let offset = i as f32 * DOCK_FLOATING_STEP;
+
+            DockedWidget {
+                position: *position,
+                floating_pos: (x + offset, y + offset),
+                ..default()
+            }
+        });
+        widget.update(world);
+    }
+
+    for position in [
+        DockPosition::Top,
+        DockPosition::Bottom,
+        DockPosition::Left,
+        DockPosition::Right,
+    ] {
+        let docked = entries
+            .iter_mut()
+            .filter(|(key, _, _)| layout.widgets[key.as_str()].position == position)
+            .collect::<Vec<_>>();
+
+        if docked.is_empty() {
+            continue;
+        }
+
+        let default_size = match position {
+            DockPosition::Left | DockPosition::Right => letterbox_width,
+            _ => letterbox_height,
+        }
+        .max(DOCK_MIN_SIZE);
+
+        let size = layout
+            .sizes
+            .get(&position)
+            .copied()
+            .unwrap_or(default_size);
+
+        let add_contents = |ui: &mut Ui| {
+            for (key, _, widget) in docked {
+                let entry = layout.widgets.get_mut(key.as_str()).unwrap();
+                let changed = dock_widget_ui(ui, key, entry, widget, world, ctx, &mut params);
+                layout.dirty |= changed;
+            }
+        };
+
+        let response = match position {
+            DockPosition::Left => SidePanel::left("dock_left")
+                .resizable(true)
+                .default_width(size)
+                .show(ctx, |ui| {
+                    ui.with_layout(Layout::top_down(Align::Min), add_contents)
+                })
+                .response,
+            DockPosition::Right => SidePanel::right("dock_right")
+                .resizable(true)
+                .default_width(size)
+                .show(ctx, |ui| {
+                    ui.with_layout(Layout::top_down(Align::Min), add_contents)
+                })
+                .response,
+            DockPosition::Top => TopBottomPanel::top("dock_top")
+                .resizable(true)
+                .default_height(size)
+                .show(ctx, |ui| {
+                    ui.with_layout(Layout::left_to_right(Align::Min), add_contents)
+                })
+                .response,
+            DockPosition::Bottom => TopBottomPanel::bottom("dock_bottom")
+                .resizable(true)
+                .default_height(size)
+                .show(ctx, |ui| {
+                    ui.with_layout(Layout::left_to_right(Align::Min), add_contents)
+                })
+                .response,
+            DockPosition::Floating => unreachable!(),
+        };
+
+        let new_size = match position {
+            DockPosition::Left | DockPosition::Right => response.rect.width(),
+            _ => response.rect.height(),
+        };
+
+        if (new_size - size).abs() > 0.5 {
+            layout.sizes.insert(position, new_size);
+            layout.dirty = true;
+        }
+    }
+
+    for (key, _, widget) in entries.iter_mut() {
+        let entry = layout.widgets.get_mut(key.as_str()).unwrap();
+        if entry.position != DockPosition::Floating {
+            continue;
+        }
+
+        let (x, y) = entry.floating_pos;
+        let response = Window::new(widget.title().into_owned())
+            .id(Id::new(&key))
+            .title_bar(false)
+            .resizable(true)
+            .default_pos(Pos2::new(x, y))
+            .show(ctx, |ui| {
+                dock_widget_ui(ui, key, entry, widget, world, ctx, &mut params)
             });
 
-            UserInterfacePanel::new(position_max, *base_aspect).show(ui, |ui| {
-                let mut widgets = widgets.panel_outer_max.lock();
-                let last_idx = widgets.len() - 1;
+        if let Some(response) = response {
+            layout.dirty |= response.inner.unwrap_or_default();
 
-                for (widget, is_last) in widgets
-                    .iter_mut()
-                    .enumerate()
-                    .map(|(i, widget)| (widget, i == last_idx))
-                {
-                    ui.vertical(|ui| {
-                        ui.heading(RichText::new(widget.title()));
-                        widget.update(world);
-                        widget.ui(world, ctx, &mut params, ui);
-                    });
+            let pos = response.response.rect.min;
+            let entry = layout.widgets.get_mut(key.as_str()).unwrap();
+            if (pos.x, pos.y) != entry.floating_pos {
+                entry.floating_pos = (pos.x, pos.y);
+                layout.dirty = true;
+            }
+        }
+    }
+
+    // Persist once any drag or resize has finished
+    if layout.dirty && !ctx.input().pointer.any_down() {
+        layout.dirty = false;
+        if let Err(e) = layout.save(&path) {
+            warn!("Failed to save dock layout to {path:?}: {e}");
+        }
+    }
+}
+
+/// Draw a single widget under a collapsible header,
+/// with a context menu for moving it between dock positions
+///
+/// Returns true if its layout entry was modified
+fn dock_widget_ui(
+    ui: &mut Ui,
+    key: &str,
+    entry: &mut DockedWidget,
+    widget: &mut WidgetVariant,
+    world: &mut World,
+    ctx: &Context,
+    params: &mut WorldInspectorParams,
+) -> bool {
+    let mut changed = false;
+
+    ui.vertical(|ui| {
+        let header = CollapsingHeader::new(RichText::new(widget.title()).heading())
+            .id_source(key)
+            .open(Some(!entry.collapsed))
+            .show(ui, |ui| widget.ui(world, ctx, params, ui));
+
+        if header.header_response.clicked() {
+            entry.collapsed = !entry.collapsed;
+            changed = true;
+        }
 
-                    if !is_last {
-                        ui.separator();
-                    }
+        header.header_response.context_menu(|ui| {
+            for position in DockPosition::ALL {
+                if ui
+                    .selectable_label(entry.position == position, position.label())
+                    .clicked()
+                {
+                    entry.position = position;
+                    changed = true;
+                    ui.close_menu();
                 }
-            });
+            }
         });
+    });
+
+    changed
 }
 
 pub fn setup(widgets: Res<Widgets>) {
@@ -245,105 +524,3 @@ pub fn setup(widgets: Res<Widgets>) {
             ..default()
         });
 }
-
-#[derive(Debug, Copy, Clone)]
-enum PanelPosition {
-    Horizontal(Side),
-    Vertical(TopBottomSide),
-}
-
-impl PanelPosition {
-    pub fn top() -> Self {
-        PanelPosition::Vertical(TopBottomSide::Top)
-    }
-
-    pub fn bottom() -> Self {
-        PanelPosition::Vertical(TopBottomSide::Bottom)
-    }
-
-    pub fn left() -> Self {
-        PanelPosition::Horizontal(Side::Left)
-    }
-
-    pub fn right() -> Self {
-        PanelPosition::Horizontal(Side::Right)
-    }
-}
-
-#[derive(Debug, Copy, Clone)]
-struct UserInterfacePanel {
-    position: PanelPosition,
-    aspect: f32,
-}
-
-impl UserInterfacePanel {
-    pub fn new(position: PanelPosition, aspect: f32) -> Self {
-        UserInterfacePanel { position, aspect }
-    }
-
-    pub fn show(self, ui: &mut Ui, add_contents: impl FnOnce(&mut Ui)) -> Response {
-        let available = ui.max_rect();
-
-        let available_width = available.width();
-        let available_height = available.height();
-
-        let style_inner = (*ui.style()).clone();
-
-        let mut style_panel = (*style_inner).clone();
-        style_panel.visuals.widgets.noninteractive.bg_fill = Color32::TRANSPARENT;
-
-        ui.set_style(style_panel.clone());
-        match self.position {
-            PanelPosition::Horizontal(side) => {
-                let fac = available_width / (available_height * self.aspect);
-
-                let half_width = available_width * 0.5;
-                let width = half_width - half_width / fac;
-
-                let rect = Rect {
-                    min: match side {
-                        Side::Left => Pos2::new(0.0, 0.0),
-                        Side::Right => Pos2::new(available_width - width, 0.0),
-                    },
-                    max: match side {
-                        Side::Left => Pos2::new(width, available_height),
-                        Side::Right => Pos2::new(available_width, available_height),
-                    },
-                };
-
-                ui.set_clip_rect(rect);
-
-                ui.allocate_ui_at_rect(rect, |ui| {
-                    ui.set_style(style_inner.clone());
-                    ui.with_layout(Layout::top_down(Align::Min), add_contents)
-                })
-                .response
-            }
-            PanelPosition::Vertical(side) => {
-                let fac = available_height / (available_width / self.aspect);
-
-                let half_height = available_height * 0.5;
-                let height = half_height - half_height / fac;
-
-                let rect = Rect {
-                    min: match side {
-                        TopBottomSide::Top => Pos2::new(0.0, 0.0),
-                        TopBottomSide::Bottom => Pos2::new(0.0, available_height - height),
-                    },
-                    max: match side {
-                        TopBottomSide::Top => Pos2::new(available_width, height),
-                        TopBottomSide::Bottom => Pos2::new(available_width, available_height),
-                    },
-                };
-
-                ui.set_clip_rect(rect);
-
-                ui.allocate_ui_at_rect(rect, |ui| {
-                    ui.set_style(style_inner.clone());
-                    ui.with_layout(Layout::left_to_right(Align::Min), add_contents)
-                })
-                .response
-            }
-        }
-    }
-}
@@ -0,0 +1,320 @@
+use bevy::{
+    ecs::reflect::ReflectComponent,
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::{
+        AlphaMode, Assets, Children, Color, Commands, Component, CoreStage, Entity, Handle,
+        Material, MaterialPlugin, Mesh, Plugin, Query, ReflectDefault, Res, ResMut,
+        StandardMaterial, Vec3,
+    },
+    reflect::{Reflect, TypeUuid},
+    render::{
+        mesh::{Indices, MeshVertexBufferLayout, PrimitiveTopology, VertexAttributeValues},
+        render_resource::{
+            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+        },
+    },
+};
+
+pub struct LineMaterialPlugin;
+
+impl Plugin for LineMaterialPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_plugin(MaterialPlugin::<LineMaterial>::default());
+
+        app.register_type::<LineMaterialOverride>();
+
+        app.add_system_to_stage(CoreStage::PreUpdate, line_material_override);
+    }
+}
+
+/// Unlit flat-color material for line and point meshes
+///
+/// The shader only reads vertex positions, but bevy's mesh pipeline still requires normals
+/// and UVs, so meshes without them should be passed through [`with_placeholder_attributes`].
+#[derive(AsBindGroup, TypeUuid, Debug, Clone)]
+#[uuid = "5a8f6a3c-5c0e-4d0e-9a43-7f1e2b9d64c1"]
+pub struct LineMaterial {
+    #[uniform(0)]
+    pub color: Color,
+}
+
+impl From<Color> for LineMaterial {
+    fn from(color: Color) -> Self {
+        LineMaterial { color }
+    }
+}
+
+impl Material for LineMaterial {
+    fn vertex_shader() -> ShaderRef {
+        "shaders/line.wgsl".into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        "shaders/line.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        if self.color.a() < 1.0 {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        }
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayout,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let vertex_layout =
+            layout.get_layout(&[Mesh::ATTRIBUTE_POSITION.at_shader_location(0)])?;
+        descriptor.vertex.buffers = vec![vertex_layout];
+
+        // Widened lines are flat quads, which may face either way
+        descriptor.primitive.cull_mode = None;
+
+        Ok(())
+    }
+}
+
+/// Replaces the StandardMaterial assigned to line and point meshes on load with a LineMaterial
+///
+/// Placed on the root of a scene, and applied to each of its line and point mesh descendants.
+/// Triangle meshes are left untouched.
+#[derive(Debug, Copy, Clone, PartialEq, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct LineMaterialOverride {
+    pub color: Color,
+    /// Use the base color of the replaced StandardMaterial instead of `color`
+    pub inherit_color: bool,
+    /// Width to expand lines and points to, or 0 to draw them as single-pixel primitives
+    pub width: f32,
+}
+
+impl Default for LineMaterialOverride {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            inherit_color: true,
+            width: 0.0,
+        }
+    }
+}
+
+/// Add zeroed normals and UVs to a mesh that lacks them, as required by bevy's mesh pipeline
+pub fn with_placeholder_attributes(mesh: &mut Mesh) {
+    let count = mesh.count_vertices();
+
+    if mesh.attribute(Mesh::ATTRIBUTE_NORMAL).is_none() {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0; 3]; count]);
+    }
+
+    if mesh.attribute(Mesh::ATTRIBUTE_UV_0).is_none() {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]; count]);
+    }
+}
+
+/// Convert a line or point mesh into a triangle mesh with a quad for each segment or point
+///
+/// Quads are expanded within the XY plane,
+/// so lines keep a constant width when viewed down the playfield's Z axis.
+pub fn widen_lines(mesh: &Mesh, width: f32) -> Option<Mesh> {
+    let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION)? {
+        VertexAttributeValues::Float32x3(positions) => positions,
+        _ => return None,
+    };
+
+    let vertex_indices = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.iter().map(|i| *i as usize).collect(),
+        Some(Indices::U32(indices)) => indices.iter().map(|i| *i as usize).collect(),
+        None => (0..positions.len()).collect::<Vec<_>>(),
+    };
+
+    let position = |i: usize| Vec3::from(positions[i]);
+
+    let half_width = width * 0.5;
+    let mut quads = vec![];
+
+    match mesh.primitive_topology() {
+        PrimitiveTopology::LineList | PrimitiveTopology::LineStrip => {
+            let segments = if mesh.primitive_topology() == PrimitiveTopology::LineList {
+                vertex_indices
+                    .chunks_exact(2)
+                    .map(|pair| (pair[0], pair[1]))
+                    .collect::<Vec<_>>()
+            } else {
+                vertex_indices
+                    .windows(2)
+                    .map(|pair| (pair[0], pair[1]))
+                    .collect()
+            };
+
+            for (from, to) in segments {
+                let (from, to) = (position(from), position(to));
+                let tangent = (to - from).truncate().normalize_or_zero();
+                let normal = Vec3::new(-tangent.y, tangent.x, 0.0) * half_width;
+
+                quads.push([from + normal, from - normal, to - normal, to + normal]);
+            }
+        }
+        PrimitiveTopology::PointList => {
+            for i in vertex_indices {
+                let point = position(i);
+                let (x, y) = (Vec3::X * half_width, Vec3::Y * half_width);
+
+                quads.push([point - x - y, point + x - y, point + x + y, point - x + y]);
+            }
+        }
+        _ => return None,
+    }
+
+    let positions = quads
+        .iter()
+        .flatten()
+        .map(|position| position.to_array())
+        .collect::<Vec<_>>();
+
+    let indices = (0..quads.len() as u32)
+        .flat_map(|quad| [0, 1, 2, 0, 2, 3].map(|i| quad * 4 + i))
+        .collect();
+
+    let mut widened = Mesh::new(PrimitiveTopology::TriangleList);
+    widened.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    widened.set_indices(Some(Indices::U32(indices)));
+    with_placeholder_attributes(&mut widened);
+    Some(widened)
+}
+
+fn descendants(entity: Entity, query_children: &Query<&Children>, out: &mut Vec<Entity>) {
+    if let Ok(children) = query_children.get(entity) {
+        for child in children.iter() {
+            out.push(*child);
+            descendants(*child, query_children, out);
+        }
+    }
+}
+
+pub fn line_material_override(
+    query_override: Query<(Entity, &LineMaterialOverride)>,
+    query_children: Query<&Children>,
+    query_mesh: Query<(&Handle<Mesh>, &Handle<StandardMaterial>)>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut line_materials: ResMut<Assets<LineMaterial>>,
+    mut commands: Commands,
+) {
+    for (entity, material_override) in query_override.iter() {
+        let mut entities = vec![entity];
+        descendants(entity, &query_children, &mut entities);
+
+        // Wait for the scene to spawn in
+        if entities.len() == 1 && query_mesh.get(entity).is_err() {
+            continue;
+        }
+
+        let mut pending = false;
+
+        for candidate in entities {
+            let (mesh_handle, material_handle) =
+                if let Ok(components) = query_mesh.get(candidate) {
+                    components
+                } else {
+                    continue;
+                };
+
+            let mesh = if let Some(mesh) = meshes.get(mesh_handle) {
+                mesh
+            } else {
+                pending = true;
+                continue;
+            };
+
+            if !matches!(
+                mesh.primitive_topology(),
+                PrimitiveTopology::LineList
+                    | PrimitiveTopology::LineStrip
+                    | PrimitiveTopology::PointList
+            ) {
+                continue;
+            }
+
+            let color = match standard_materials.get(material_handle) {
+                Some(material) if material_override.inherit_color => material.base_color,
+                _ => material_override.color,
+            };
+
+            let widened = if material_override.width > 0.0 {
+                widen_lines(mesh, material_override.width)
+            } else {
+                None
+            };
+
+            let mut candidate = commands.entity(candidate);
+            candidate
+                .remove::<Handle<StandardMaterial>>()
+                .insert(line_materials.add(color.into()));
+
+            if let Some(widened) = widened {
+                candidate.insert(meshes.add(widened));
+            } else if let Some(mesh) = meshes.get_mut(mesh_handle) {
+                with_placeholder_attributes(mesh);
+            }
+        }
+
+        if !pending {
+            commands.entity(entity).remove::<LineMaterialOverride>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mesh(topology: PrimitiveTopology) -> Mesh {
+        let mut mesh = Mesh::new(topology);
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![[0.0, 0.0, 0.0], [2.0, 0.0, 0.0]],
+        );
+        mesh
+    }
+
+    #[test]
+    fn widen_line_list_into_quad() {
+        let widened = widen_lines(&mesh(PrimitiveTopology::LineList), 1.0).unwrap();
+
+        assert_eq!(widened.primitive_topology(), PrimitiveTopology::TriangleList);
+        assert_eq!(widened.count_vertices(), 4);
+        assert_eq!(widened.indices().unwrap().len(), 6);
+
+        let positions = match widened.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => positions,
+            _ => panic!("Widened mesh has no positions"),
+        };
+        assert_eq!(positions[0], [0.0, 0.5, 0.0]);
+        assert_eq!(positions[2], [2.0, -0.5, 0.0]);
+    }
+
+    #[test]
+    fn widen_points_into_quads() {
+        let widened = widen_lines(&mesh(PrimitiveTopology::PointList), 1.0).unwrap();
+        assert_eq!(widened.count_vertices(), 8);
+        assert_eq!(widened.indices().unwrap().len(), 12);
+    }
+
+    #[test]
+    fn widen_ignores_triangles() {
+        assert!(widen_lines(&mesh(PrimitiveTopology::TriangleList), 1.0).is_none());
+    }
+
+    #[test]
+    fn placeholder_attributes_match_vertex_count() {
+        let mut mesh = mesh(PrimitiveTopology::LineList);
+        with_placeholder_attributes(&mut mesh);
+
+        assert_eq!(mesh.attribute(Mesh::ATTRIBUTE_NORMAL).unwrap().len(), 2);
+        assert_eq!(mesh.attribute(Mesh::ATTRIBUTE_UV_0).unwrap().len(), 2);
+    }
+}
@@ -1,6 +1,7 @@
 pub mod component_bundle;
 pub mod delayed_input;
 pub mod depth_material;
+pub mod line_material;
 pub mod reciprocal;
 pub mod sign;
 